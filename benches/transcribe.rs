@@ -0,0 +1,99 @@
+//! Transcription latency benchmarks
+//!
+//! Run with `cargo bench --bench transcribe`. The engine runs on the CPU so numbers are
+//! comparable across machines and runs.
+//!
+//! - `MEMO_BENCH_MODEL` - model to load (default: the default model, downloaded if needed)
+//! - `MEMO_BENCH_WAV` - 16-bit mono WAV with real speech; clips of 1s/5s/30s are cut from it
+//!   (looped if shorter). Without it only synthetic audio is benchmarked.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use memo_stt::audio::to_whisper_input;
+use memo_stt::SttEngine;
+use std::time::Duration;
+
+const CLIP_SECS: [u32; 3] = [1, 5, 30];
+
+/// Deterministic speech-like signal: a few harmonics with a syllable-rate envelope plus noise
+fn synthetic_audio(secs: u32, sample_rate: u32) -> Vec<i16> {
+    let mut seed: u32 = 0x1234_5678;
+    (0..secs * sample_rate)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            let envelope = (0.5 + 0.5 * (t * 2.0 * std::f32::consts::PI * 4.0).sin()).powi(2);
+            let voice: f32 = [140.0f32, 280.0, 420.0, 700.0]
+                .iter()
+                .enumerate()
+                .map(|(k, f)| (t * 2.0 * std::f32::consts::PI * f).sin() / (k + 1) as f32)
+                .sum();
+            seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let noise = (seed >> 16) as f32 / 65536.0 - 0.5;
+            ((voice * envelope * 0.3 + noise * 0.02) * 32767.0) as i16
+        })
+        .collect()
+}
+
+/// Load a 16-bit mono WAV fixture and cut a clip of `secs` (looping short files)
+fn fixture_audio(path: &str, secs: u32) -> Option<(Vec<i16>, u32)> {
+    let mut reader = hound::WavReader::open(path).ok()?;
+    let spec = reader.spec();
+    if spec.channels != 1 || spec.bits_per_sample != 16 {
+        eprintln!("MEMO_BENCH_WAV must be 16-bit mono, skipping fixture benches");
+        return None;
+    }
+    let samples: Vec<i16> = reader.samples::<i16>().filter_map(|s| s.ok()).collect();
+    if samples.is_empty() {
+        return None;
+    }
+    let len = (secs * spec.sample_rate) as usize;
+    Some((samples.iter().copied().cycle().take(len).collect(), spec.sample_rate))
+}
+
+fn bench_resample(c: &mut Criterion) {
+    let mut group = c.benchmark_group("resample");
+    for &rate in &[16000u32, 44100, 48000] {
+        let samples = synthetic_audio(5, rate);
+        let mut out = Vec::new();
+        group.throughput(Throughput::Elements(samples.len() as u64));
+        group.bench_with_input(BenchmarkId::new("5s", rate), &samples, |b, samples| {
+            b.iter(|| to_whisper_input(samples, rate, &mut out))
+        });
+    }
+    group.finish();
+}
+
+fn bench_transcribe(c: &mut Criterion) {
+    let model = std::env::var("MEMO_BENCH_MODEL")
+        .map(std::path::PathBuf::from)
+        .unwrap_or_else(|_| memo_stt::default_model_path());
+
+    let mut clips: Vec<(String, Vec<i16>, u32)> = CLIP_SECS
+        .iter()
+        .map(|&secs| (format!("synthetic/{}s", secs), synthetic_audio(secs, 16000), 16000))
+        .collect();
+    if let Ok(path) = std::env::var("MEMO_BENCH_WAV") {
+        for &secs in &CLIP_SECS {
+            if let Some((samples, rate)) = fixture_audio(&path, secs) {
+                clips.push((format!("fixture/{}s", secs), samples, rate));
+            }
+        }
+    }
+
+    let mut group = c.benchmark_group("transcribe");
+    group.sample_size(10).measurement_time(Duration::from_secs(30));
+    for (name, samples, rate) in &clips {
+        let mut engine = match SttEngine::new_cpu(&model, *rate) {
+            Ok(engine) => engine,
+            Err(e) => {
+                eprintln!("Skipping transcribe benches, failed to load {}: {}", model.display(), e);
+                return;
+            }
+        };
+        engine.warmup().ok();
+        group.bench_function(name.as_str(), |b| b.iter(|| engine.transcribe(samples).unwrap()));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_resample, bench_transcribe);
+criterion_main!(benches);
@@ -1,16 +1,38 @@
 //! Microphone recording example
 //!
-//! This example shows how to record from the microphone and transcribe in real-time.
-//! Run with: `cargo run --example microphone`
+//! Transcribes each utterance spoken into the default microphone, or every utterance of a
+//! WAV file if one is given. The transcription loop only sees an `AudioSource`, so it works
+//! the same for both.
 //!
-//! Note: This requires the binary dependencies (cpal, rdev) which are only
-//! available when running the main binary, not as a library example.
-//!
-//! For library usage, see the basic example.
+//! Run with: `cargo run --example microphone [recording.wav]`
+
+use memo_stt::capture::MicSource;
+use memo_stt::source::{AudioSource, FileSource};
+use memo_stt::SttEngine;
+use std::time::Duration;
+
+fn transcribe_all(engine: &mut SttEngine, source: &mut dyn AudioSource) -> memo_stt::Result<()> {
+    loop {
+        match engine.transcribe_source(source) {
+            Ok(Some(text)) => println!("📝 {}", text),
+            Ok(None) => return Ok(()),
+            Err(memo_stt::Error::AudioTooShort { .. }) => println!("(too short, skipped)"),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut source: Box<dyn AudioSource> = match std::env::args().nth(1) {
+        Some(path) => Box::new(FileSource::open(path)?),
+        None => {
+            println!("Listening; pause for a moment after speaking. Ctrl+C to quit.");
+            Box::new(MicSource::new(None, 600.0, Duration::from_millis(800))?)
+        }
+    };
 
-fn main() {
-    println!("Microphone recording example");
-    println!("For full microphone recording, use the main binary:");
-    println!("  cargo run --bin memo-stt");
-    println!("\nFor library usage, see examples/basic.rs");
+    let mut engine = SttEngine::new_default(source.sample_rate())?;
+    engine.warmup()?;
+    transcribe_all(&mut engine, source.as_mut())?;
+    Ok(())
 }
@@ -0,0 +1,21 @@
+//! Feeds arbitrary bytes (as a BLE packet payload would arrive) to the Opus bundle parser
+//! and frame decoder.
+//!
+//! ```sh
+//! cargo +nightly fuzz run opus_bundle
+//! ```
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/opus_decoder.rs"]
+mod opus_decoder;
+
+fuzz_target!(|data: &[u8]| {
+    let mut decoder = opus_decoder::OpusDecoder::new(16000, 20).unwrap();
+    let _ = decoder.decode_bundle(data);
+    let _ = decoder.decode_bundle_with_fec(data);
+    let _ = decoder.decode_frame(data);
+});
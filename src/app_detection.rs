@@ -66,7 +66,24 @@ pub fn get_application_context() -> (String, String) {
     (app_name, window_title)
 }
 
+/// Bring `app_name` to the front (e.g. the app that was active when recording started).
+#[cfg(target_os = "macos")]
+pub fn activate_application(app_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let script = format!(
+        "tell application \"System Events\" to set frontmost of (first application process whose name is \"{}\") to true",
+        app_name.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let status = Command::new("osascript").arg("-e").arg(script).status()?;
+    if !status.success() {
+        return Err(format!("Failed to activate {}", app_name).into());
+    }
+    // Give the window server a moment to move focus before keystrokes are sent
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    Ok(())
+}
 
-
-
-
+#[cfg(not(target_os = "macos"))]
+pub fn activate_application(_app_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    // TODO: Implement for other platforms
+    Ok(())
+}
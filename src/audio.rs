@@ -0,0 +1,390 @@
+//! Audio preprocessing
+//!
+//! Conversion from the engine's `i16` input to the 16 kHz mono `f32` buffer whisper consumes.
+
+/// Sample rate whisper models expect
+pub const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Normalize `i16` PCM recorded at `input_sample_rate` to 16 kHz `f32` in `[-1.0, 1.0)`.
+///
+/// Replaces the contents of `out` (reusing its allocation). Non-16 kHz input is resampled
+/// with linear interpolation. This is exactly what [`SttEngine::transcribe`](crate::SttEngine::transcribe)
+/// feeds to whisper.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::to_whisper_input;
+///
+/// let mut out = Vec::new();
+/// to_whisper_input(&vec![0i16; 48000], 48000, &mut out);
+/// assert_eq!(out.len(), 16000);
+/// ```
+pub fn to_whisper_input(samples: &[i16], input_sample_rate: u32, out: &mut Vec<f32>) {
+    out.clear();
+    if samples.is_empty() {
+        return;
+    }
+    if input_sample_rate == WHISPER_SAMPLE_RATE {
+        // Direct normalization, no resampling
+        out.reserve(samples.len());
+        for &s in samples {
+            out.push(s as f32 / 32768.0);
+        }
+    } else {
+        interpolate(samples, input_sample_rate, WHISPER_SAMPLE_RATE, |s| out.push(s / 32768.0));
+    }
+}
+
+/// [`to_whisper_input`] for audio that is already `f32` in `[-1.0, 1.0]`, as most capture
+/// APIs and decoders deliver it: only resamples, copying as-is at 16 kHz.
+///
+/// This is what [`SttEngine::transcribe_f32`](crate::SttEngine::transcribe_f32) feeds to
+/// whisper.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::to_whisper_input_f32;
+///
+/// let mut out = Vec::new();
+/// to_whisper_input_f32(&vec![0.25f32; 48000], 48000, &mut out);
+/// assert_eq!(out.len(), 16000);
+/// assert!(out.iter().all(|&s| (s - 0.25).abs() < 1e-6));
+/// ```
+pub fn to_whisper_input_f32(samples: &[f32], input_sample_rate: u32, out: &mut Vec<f32>) {
+    out.clear();
+    if samples.is_empty() {
+        return;
+    }
+    if input_sample_rate == WHISPER_SAMPLE_RATE {
+        out.extend_from_slice(samples);
+    } else {
+        interpolate(samples, input_sample_rate, WHISPER_SAMPLE_RATE, |s| out.push(s));
+    }
+}
+
+/// Resample `i16` PCM from `from` Hz to `to` Hz with linear interpolation.
+///
+/// The same resampler the engine uses for whisper input, for other 16 kHz consumers such as
+/// the Opus encoder or a VAD. Output is rounded to the nearest sample; equal rates return a
+/// copy.
+///
+/// # Panics
+///
+/// If either rate is zero.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::resample_i16;
+///
+/// let resampled = resample_i16(&vec![100i16; 48000], 48000, 16000);
+/// assert_eq!(resampled.len(), 16000);
+/// assert!(resampled.iter().all(|&s| s == 100));
+/// ```
+pub fn resample_i16(samples: &[i16], from: u32, to: u32) -> Vec<i16> {
+    assert!(from > 0 && to > 0, "sample rates must be non-zero ({} -> {})", from, to);
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let mut out = Vec::new();
+    interpolate(samples, from, to, |s| out.push(s.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16));
+    out
+}
+
+/// Linear interpolation from `from` Hz to `to` Hz, passing each output sample (still on the
+/// input's scale) to `emit`. `samples` must not be empty.
+fn interpolate<T: Copy + Into<f32>>(samples: &[T], from: u32, to: u32, mut emit: impl FnMut(f32)) {
+    let ratio = from as f32 / to as f32;
+    let out_len = (samples.len() as f32 / ratio).max(1.0) as usize;
+    let last = samples.len() - 1;
+    for i in 0..out_len {
+        let pos = i as f32 * ratio;
+        let i0 = pos.floor() as usize;
+        let i1 = (i0 + 1).min(last);
+        let t = pos - i0 as f32;
+        emit(samples[i0].into() * (1.0 - t) + samples[i1].into() * t);
+    }
+}
+
+/// Sample encoding of raw PCM bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    /// 16-bit signed integer
+    I16,
+    /// 32-bit signed integer
+    I32,
+    /// 32-bit float in `[-1.0, 1.0]`
+    F32,
+}
+
+impl SampleFormat {
+    /// Size of one sample in bytes
+    pub fn bytes_per_sample(self) -> usize {
+        match self {
+            SampleFormat::I16 => 2,
+            SampleFormat::I32 | SampleFormat::F32 => 4,
+        }
+    }
+}
+
+/// Byte order of raw PCM samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    #[default]
+    Little,
+    Big,
+}
+
+/// Downmix interleaved `i16` samples to mono.
+///
+/// `weights` gives each channel's share of the mix, e.g. `[1.0, 0.0]` to keep only a voice
+/// mic on channel 0 or `[0.7, 0.3]` for a blend; `None` averages the channels equally.
+/// Weights aren't normalized, so they can also apply gain; the result is clamped.
+///
+/// Fails if `channels` is 0, `weights` doesn't have one finite weight per channel, or
+/// `samples` is not a whole number of frames.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::downmix;
+///
+/// let stereo = [1000, -400, 2000, 600];
+/// assert_eq!(downmix(&stereo, 2, None)?, vec![300, 1300]);
+/// assert_eq!(downmix(&stereo, 2, Some(&[1.0, 0.0]))?, vec![1000, 2000]);
+/// # Ok::<(), memo_stt::Error>(())
+/// ```
+pub fn downmix(samples: &[i16], channels: u16, weights: Option<&[f32]>) -> crate::Result<Vec<i16>> {
+    if channels == 0 {
+        return Err(crate::Error::Config("Audio must have at least one channel".into()));
+    }
+    let channels = channels as usize;
+    let equal = vec![1.0 / channels as f32; channels];
+    let weights = weights.unwrap_or(&equal);
+    if weights.len() != channels || weights.iter().any(|w| !w.is_finite()) {
+        return Err(crate::Error::Config(format!(
+            "Expected {} finite channel weights, got {:?}",
+            channels, weights
+        )));
+    }
+    if !samples.len().is_multiple_of(channels) {
+        return Err(crate::Error::Audio(format!(
+            "{} samples is not a whole number of {}-channel frames",
+            samples.len(),
+            channels
+        )));
+    }
+
+    Ok(samples
+        .chunks_exact(channels)
+        .map(|frame| {
+            let mixed: f32 = frame.iter().zip(weights).map(|(&s, &w)| s as f32 * w).sum();
+            mixed.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+        })
+        .collect())
+}
+
+/// Layout of raw interleaved PCM bytes, for [`SttEngine::transcribe_bytes`](crate::SttEngine::transcribe_bytes).
+///
+/// The sample rate is not part of the format; it is the engine's `input_sample_rate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcmFormat {
+    pub sample_format: SampleFormat,
+    /// Number of interleaved channels (downmixed to mono by averaging)
+    pub channels: u16,
+    pub endianness: Endianness,
+}
+
+impl Default for PcmFormat {
+    /// 16-bit little-endian mono
+    fn default() -> Self {
+        Self {
+            sample_format: SampleFormat::I16,
+            channels: 1,
+            endianness: Endianness::Little,
+        }
+    }
+}
+
+impl PcmFormat {
+    /// Size of one frame (one sample per channel) in bytes
+    pub fn bytes_per_frame(&self) -> usize {
+        self.sample_format.bytes_per_sample() * self.channels as usize
+    }
+}
+
+/// Decode raw interleaved PCM bytes to mono `i16` samples.
+///
+/// Fails if `format.channels` is 0 or `bytes` is not a whole number of frames.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::{decode_pcm, PcmFormat};
+///
+/// let bytes = [0x00, 0x40, 0x00, 0xC0]; // 16384, -16384
+/// assert_eq!(decode_pcm(&bytes, PcmFormat::default())?, vec![16384, -16384]);
+/// # Ok::<(), memo_stt::Error>(())
+/// ```
+pub fn decode_pcm(bytes: &[u8], format: PcmFormat) -> crate::Result<Vec<i16>> {
+    if format.channels == 0 {
+        return Err(crate::Error::Config("PCM format must have at least one channel".into()));
+    }
+    let frame_len = format.bytes_per_frame();
+    if !bytes.len().is_multiple_of(frame_len) {
+        return Err(crate::Error::Audio(format!(
+            "PCM data is {} bytes, not a whole number of {}-byte frames ({:?} x {} channels)",
+            bytes.len(),
+            frame_len,
+            format.sample_format,
+            format.channels
+        )));
+    }
+
+    let sample_len = format.sample_format.bytes_per_sample();
+    let decode = |b: &[u8]| -> f32 {
+        match (format.sample_format, format.endianness) {
+            (SampleFormat::I16, Endianness::Little) => i16::from_le_bytes([b[0], b[1]]) as f32,
+            (SampleFormat::I16, Endianness::Big) => i16::from_be_bytes([b[0], b[1]]) as f32,
+            (SampleFormat::I32, Endianness::Little) => (i32::from_le_bytes([b[0], b[1], b[2], b[3]]) >> 16) as f32,
+            (SampleFormat::I32, Endianness::Big) => (i32::from_be_bytes([b[0], b[1], b[2], b[3]]) >> 16) as f32,
+            (SampleFormat::F32, Endianness::Little) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]).clamp(-1.0, 1.0) * 32767.0,
+            (SampleFormat::F32, Endianness::Big) => f32::from_be_bytes([b[0], b[1], b[2], b[3]]).clamp(-1.0, 1.0) * 32767.0,
+        }
+    };
+
+    Ok(bytes
+        .chunks_exact(frame_len)
+        .map(|frame| {
+            let sum: f32 = frame.chunks_exact(sample_len).map(decode).sum();
+            (sum / format.channels as f32) as i16
+        })
+        .collect())
+}
+
+/// Decode a WAV file's contents to mono `i16` samples and its sample rate.
+///
+/// Supports 16- and 32-bit integer and 32-bit float PCM with any channel count (downmixed
+/// like [`decode_pcm`]). A data chunk cut short (e.g. by a recorder that never finalized
+/// the header) is decoded up to its last whole frame.
+///
+/// # Example
+///
+/// ```no_run
+/// use memo_stt::audio::decode_wav;
+///
+/// let (samples, sample_rate) = decode_wav(&std::fs::read("speech.wav")?)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn decode_wav(bytes: &[u8]) -> crate::Result<(Vec<i16>, u32)> {
+    let bad = |why: String| crate::Error::Audio(format!("Unsupported WAV file: {}", why));
+    let u16_at = |b: &[u8], i: usize| u16::from_le_bytes([b[i], b[i + 1]]);
+    let u32_at = |b: &[u8], i: usize| u32::from_le_bytes([b[i], b[i + 1], b[i + 2], b[i + 3]]);
+
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err(bad("missing RIFF/WAVE header".into()));
+    }
+    let mut format = None;
+    let mut rest = &bytes[12..];
+    while rest.len() >= 8 {
+        let size = u32_at(rest, 4) as usize;
+        let body = rest.get(8..8 + size).unwrap_or(&rest[8..]);
+        match &rest[0..4] {
+            b"fmt " if body.len() >= 16 => {
+                // WAVE_FORMAT_EXTENSIBLE keeps the real format tag at the start of its sub-format GUID
+                let tag = match u16_at(body, 0) {
+                    0xFFFE if body.len() >= 26 => u16_at(body, 24),
+                    tag => tag,
+                };
+                let (channels, bits) = (u16_at(body, 2), u16_at(body, 14));
+                let sample_format = match (tag, bits) {
+                    (1, 16) => SampleFormat::I16,
+                    (1, 32) => SampleFormat::I32,
+                    (3, 32) => SampleFormat::F32,
+                    _ => return Err(bad(format!("format {} with {}-bit samples", tag, bits))),
+                };
+                if channels == 0 || u32_at(body, 4) == 0 {
+                    return Err(bad("no channels or zero sample rate".into()));
+                }
+                format = Some((PcmFormat { sample_format, channels, endianness: Endianness::Little }, u32_at(body, 4)));
+            }
+            b"data" => {
+                let (format, sample_rate) = format.ok_or_else(|| bad("data chunk before fmt chunk".into()))?;
+                let whole = body.len() / format.bytes_per_frame() * format.bytes_per_frame();
+                return Ok((decode_pcm(&body[..whole], format)?, sample_rate));
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        rest = rest.get(8 + size + (size & 1)..).unwrap_or_default();
+    }
+    Err(bad("no data chunk".into()))
+}
+
+/// Root mean square of `samples` (i16 scale), the energy measure used for voice activity
+pub(crate) fn rms(samples: &[i16]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_squares: i64 = samples.iter().map(|&s| (s as i64).pow(2)).sum();
+    (sum_squares as f32 / samples.len() as f32).sqrt()
+}
+
+/// Whether the whole of `samples` is below `threshold` RMS (i16 scale, `0.0..=32767.0`):
+/// a cheap check for dead air before running the model. Room tone from a typical laptop
+/// microphone measures 20-100; quiet speech is several hundred. Empty input is silent.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::is_silent;
+///
+/// assert!(is_silent(&[0, 12, -9, 30], 50.0));
+/// assert!(!is_silent(&[4000, -4000, 4000, -4000], 50.0));
+/// ```
+pub fn is_silent(samples: &[i16], threshold: f32) -> bool {
+    rms(samples) < threshold
+}
+
+/// Sample indices in the middle of each pause at least `min_gap_ms` long, in order: good
+/// places to split long audio without cutting a word.
+///
+/// Audio is measured in 10ms windows; a window is silent when its RMS is under a tenth of
+/// the loudest window's (at least 50 on the i16 scale, so near-digital silence always
+/// counts). Silence at the very start or end is not between anything and is not reported.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::audio::find_silence_boundaries;
+///
+/// let tone = |n| (0..n).map(|i| if i % 2 == 0 { 8000 } else { -8000 }).collect::<Vec<i16>>();
+/// let samples = [tone(16000), vec![0; 8000], tone(16000)].concat();
+/// let gaps = find_silence_boundaries(&samples, 16000, 300);
+/// assert_eq!(gaps.len(), 1);
+/// assert!(gaps[0].abs_diff(20000) <= 160);
+/// ```
+pub fn find_silence_boundaries(samples: &[i16], sample_rate: u32, min_gap_ms: u32) -> Vec<usize> {
+    let window = (sample_rate as usize / 100).max(1);
+    let energies: Vec<f32> = samples.chunks(window).map(rms).collect();
+    let threshold = (energies.iter().copied().fold(0.0, f32::max) * 0.1).max(50.0);
+    let min_windows = (min_gap_ms as usize * sample_rate as usize).div_ceil(1000 * window).max(1);
+
+    let mut boundaries = Vec::new();
+    let mut run_start = None;
+    for (i, &energy) in energies.iter().enumerate() {
+        match (energy < threshold, run_start) {
+            (true, None) => run_start = Some(i),
+            (false, Some(start)) => {
+                if start > 0 && i - start >= min_windows {
+                    boundaries.push((start + i) * window / 2);
+                }
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    boundaries
+}
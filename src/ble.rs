@@ -4,54 +4,302 @@
  */
 
 use anyhow::{Context, Result};
-use btleplug::api::{Manager as _, Central as _, Characteristic, Peripheral as _, ScanFilter};
+use btleplug::api::{Manager as _, Central as _, CharPropFlags, Characteristic, Peripheral as _, ScanFilter, WriteType};
 use btleplug::platform::{Manager, Adapter, Peripheral};
 use log::{debug, info, warn, error};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tokio::time::timeout;
 use uuid::Uuid;
 
 const DEVICE_NAME_PATTERN: &str = "memo_";
-const DEVICE_ADDRESS: &str = "64D5A7E1-B149-191F-9B11-96F5CCF590BF"; // From memory
 const SCAN_TIMEOUT: Duration = Duration::from_secs(30);
+/// How long a keep-alive read may take before it counts as failed
+const KEEP_ALIVE_READ_TIMEOUT: Duration = Duration::from_secs(3);
+/// How long to wait for a lost peripheral to acknowledge the disconnect before dropping it
+const RELEASE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Delays between attempts in `connect_with_auto_reconnect`: doubled after each failure
+const RECONNECT_MIN_BACKOFF: Duration = Duration::from_secs(2);
+const RECONNECT_MAX_BACKOFF: Duration = Duration::from_secs(60);
 
 // Service and characteristic UUIDs (from firmware bluetooth.c)
 // Memo Audio Service UUID: 1234A000-1234-5678-1234-56789ABCDEF0
-const MEMO_AUDIO_SERVICE_UUID: &str = "1234A000-1234-5678-1234-56789ABCDEF0";
+const MEMO_AUDIO_SERVICE_UUID: Uuid = Uuid::from_u128(0x1234A000_1234_5678_1234_56789ABCDEF0);
 // Memo Audio Data Characteristic UUID: 1234A001-1234-5678-1234-56789ABCDEF0
-const MEMO_AUDIO_DATA_CHAR_UUID: &str = "1234A001-1234-5678-1234-56789ABCDEF0";
+const MEMO_AUDIO_DATA_CHAR_UUID: Uuid = Uuid::from_u128(0x1234A001_1234_5678_1234_56789ABCDEF0);
+// Control RX Characteristic UUID: 1234A002-1234-5678-1234-56789ABCDEF0
+// Receives commands from the central (see ControlCommand)
+const MEMO_CONTROL_RX_CHAR_UUID: Uuid = Uuid::from_u128(0x1234A002_1234_5678_1234_56789ABCDEF0);
 // Control TX Characteristic UUID: 1234A003-1234-5678-1234-56789ABCDEF0
 // Sends notifications: RESP_SPEECH_START (0x01) and RESP_SPEECH_END (0x02)
-const MEMO_CONTROL_TX_CHAR_UUID: &str = "1234A003-1234-5678-1234-56789ABCDEF0";
+const MEMO_CONTROL_TX_CHAR_UUID: Uuid = Uuid::from_u128(0x1234A003_1234_5678_1234_56789ABCDEF0);
 // Battery Characteristic UUID: 1234A004-1234-5678-1234-56789ABCDEF0
 // Used for low-frequency central polling to confirm the link is still alive while idle.
-const MEMO_BATTERY_CHAR_UUID: &str = "1234A004-1234-5678-1234-56789ABCDEF0";
+const MEMO_BATTERY_CHAR_UUID: Uuid = Uuid::from_u128(0x1234A004_1234_5678_1234_56789ABCDEF0);
 
 // Control response values from firmware
 const RESP_SPEECH_START: u8 = 0x01;  // 1 - Recording started
 const RESP_SPEECH_END: u8 = 0x02;    // 2 - Recording ended
 const RESP_PRESS_ENTER: u8 = 0x03;   // 3 - Second tap shortly after stop (desktop Enter)
 
+// Command values written to Control RX
+const CMD_START_CAPTURE: u8 = 0x01;
+const CMD_STOP_CAPTURE: u8 = 0x02;
+const CMD_FLASH_LED: u8 = 0x10;
+
+/// Known commands for [`BleAudioReceiver::send_command`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Start streaming audio, as if the button was pressed
+    StartCapture,
+    /// Stop streaming audio, as if the button was released
+    StopCapture,
+    /// Blink the device LED (e.g. to acknowledge a transcription)
+    FlashLed,
+}
+
+impl ControlCommand {
+    /// Byte written to the Control RX characteristic
+    pub fn code(self) -> u8 {
+        match self {
+            ControlCommand::StartCapture => CMD_START_CAPTURE,
+            ControlCommand::StopCapture => CMD_STOP_CAPTURE,
+            ControlCommand::FlashLed => CMD_FLASH_LED,
+        }
+    }
+
+    /// Parse `start_capture` / `stop_capture` / `flash_led` (case-insensitive)
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "start_capture" => Some(ControlCommand::StartCapture),
+            "stop_capture" => Some(ControlCommand::StopCapture),
+            "flash_led" => Some(ControlCommand::FlashLed),
+            _ => None,
+        }
+    }
+}
+
+/// Which peripheral to connect to and the GATT layout of its audio service.
+///
+/// Defaults to the memo device; other hardware that streams Opus bundles over a
+/// notification characteristic can be used by changing the name pattern and UUIDs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BleConfig {
+    /// Advertised name prefix, matched case-insensitively (`memo_`)
+    pub name_pattern: String,
+    /// Audio service, also matched in advertising data while scanning
+    pub service_uuid: Uuid,
+    /// Notification characteristic carrying the audio
+    pub audio_data_uuid: Uuid,
+    /// Notification characteristic carrying button/control codes
+    pub control_tx_uuid: Uuid,
+    /// Writable characteristic for commands to the device ([`BleAudioReceiver::send_control`])
+    pub control_rx_uuid: Uuid,
+    /// Characteristic read by [`BleAudioReceiver::poll_link`] and the keep-alive
+    pub battery_uuid: Uuid,
+    /// Connect only to the peripheral with this ID/address (a UUID on macOS, a MAC address
+    /// elsewhere), skipping name and service matching. `None` (the default) scans.
+    pub device_address: Option<String>,
+}
+
+impl Default for BleConfig {
+    fn default() -> Self {
+        Self {
+            name_pattern: DEVICE_NAME_PATTERN.to_string(),
+            service_uuid: MEMO_AUDIO_SERVICE_UUID,
+            audio_data_uuid: MEMO_AUDIO_DATA_CHAR_UUID,
+            control_tx_uuid: MEMO_CONTROL_TX_CHAR_UUID,
+            control_rx_uuid: MEMO_CONTROL_RX_CHAR_UUID,
+            battery_uuid: MEMO_BATTERY_CHAR_UUID,
+            device_address: None,
+        }
+    }
+}
+
+impl BleConfig {
+    /// Whether an advertised name starts with `name_pattern` (case-insensitive)
+    fn matches_name(&self, name: &str) -> bool {
+        name.to_lowercase().starts_with(&self.name_pattern.to_lowercase())
+    }
+
+    /// `Some(matches)` when connecting directly to `device_address`, `None` when scanning
+    fn matches_address(&self, id: &str, address: &str) -> Option<bool> {
+        self.device_address.as_deref().map(|wanted| address_matches(wanted, id, address))
+    }
+}
+
+/// How to write to a characteristic with these properties: confirmed writes when supported
+fn control_write_type(properties: CharPropFlags) -> Option<WriteType> {
+    if properties.contains(CharPropFlags::WRITE) {
+        Some(WriteType::WithResponse)
+    } else if properties.contains(CharPropFlags::WRITE_WITHOUT_RESPONSE) {
+        Some(WriteType::WithoutResponse)
+    } else {
+        None
+    }
+}
+
+/// Strongest signal first; devices without an RSSI last
+fn sort_by_signal(devices: &mut [DiscoveredDevice]) {
+    devices.sort_by_key(|d| std::cmp::Reverse(d.rssi.unwrap_or(i16::MIN)));
+}
+
+/// Whether `wanted` is the peripheral's ID or address (case-insensitive)
+fn address_matches(wanted: &str, id: &str, address: &str) -> bool {
+    wanted.eq_ignore_ascii_case(id) || wanted.eq_ignore_ascii_case(address)
+}
+
+/// Whether `s` looks like a peripheral address: a MAC address (`AA:BB:CC:DD:EE:FF`, as
+/// reported on Linux/Windows) or a peripheral UUID (as reported on macOS)
+pub fn is_device_address(s: &str) -> bool {
+    let octets: Vec<&str> = s.split(':').collect();
+    let is_mac = octets.len() == 6
+        && octets.iter().all(|o| o.len() == 2 && o.chars().all(|c| c.is_ascii_hexdigit()));
+    is_mac || (s.len() == 36 && Uuid::parse_str(s).is_ok())
+}
+
+/// A device seen by [`BleAudioReceiver::scan_devices`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredDevice {
+    /// Advertised name, if any
+    pub name: Option<String>,
+    /// ID/address to pass to [`BleAudioReceiver::connect_by_address`]
+    pub address: String,
+    /// Signal strength in dBm (higher is closer), if reported
+    pub rssi: Option<i16>,
+}
+
+/// Why a device was disconnected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectReason {
+    /// `disconnect()` was called
+    UserRequested,
+    /// The link went silent / health checks failed
+    ConnectionLost,
+}
+
+/// Connection state change, see [`BleAudioReceiver::connection_events`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    Connected { device_name: String },
+    Disconnected(DisconnectReason),
+    /// Dropping the current link to connect again (to the same or another device)
+    Reconnecting,
+    ConnectFailed { reason: String },
+}
+
 pub struct BleAudioReceiver {
     periph: Option<Peripheral>,
     char_audio_data: Option<Characteristic>,
     char_control_tx: Option<Characteristic>,
+    char_control_rx: Option<Characteristic>,
     char_battery: Option<Characteristic>,
     device_name: Option<String>, // Store device name for retrieval
+    /// Device of the last successful connect, preferred by `reconnect`
+    last_device_name: Option<String>,
+    /// Whether the last successful connect was trigger-only
+    trigger_only: bool,
+    /// Address of the last successful `connect_by_address`, preferred by `reconnect`
+    last_address: Option<String>,
+    config: BleConfig,
+    events: broadcast::Sender<ConnectionEvent>,
+    keep_alive_interval: Option<Duration>,
+    keep_alive_task: Option<tokio::task::JoinHandle<()>>,
 }
 
 impl BleAudioReceiver {
-    pub async fn new() -> Result<Self> {
-        info!("Initializing BLE receiver with btleplug");
+    pub async fn new(config: BleConfig) -> Result<Self> {
+        info!("Initializing BLE receiver with btleplug ({:?})", config);
+        let (events, _) = broadcast::channel(16);
         Ok(Self {
             periph: None,
             char_audio_data: None,
             char_control_tx: None,
+            char_control_rx: None,
             char_battery: None,
             device_name: None,
+            last_device_name: None,
+            trigger_only: false,
+            last_address: None,
+            config,
+            events,
+            keep_alive_interval: None,
+            keep_alive_task: None,
         })
     }
 
+    /// Periodically read a characteristic (battery, else the peripheral properties) while
+    /// connected, for BLE stacks that drop a peripheral after a period without GATT activity.
+    /// Trigger-only mode sends nothing between button presses and is the main case.
+    ///
+    /// `None` (the default) disables it. The task starts after each successful connect and
+    /// stops on disconnect; changing the interval while connected restarts it.
+    pub fn set_keep_alive(&mut self, interval: Option<Duration>) {
+        self.keep_alive_interval = interval;
+        if self.periph.is_some() {
+            self.start_keep_alive();
+        }
+    }
+
+    fn start_keep_alive(&mut self) {
+        self.stop_keep_alive();
+        let (Some(interval), Some(periph)) = (self.keep_alive_interval, self.periph.clone()) else {
+            return;
+        };
+        let battery = self.char_battery.clone();
+        debug!("Starting BLE keep-alive every {:?}", interval);
+        self.keep_alive_task = Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            ticker.tick().await; // The first tick completes immediately
+            loop {
+                ticker.tick().await;
+                let ok = match battery {
+                    Some(ref c) => matches!(timeout(KEEP_ALIVE_READ_TIMEOUT, periph.read(c)).await, Ok(Ok(_))),
+                    None => matches!(timeout(KEEP_ALIVE_READ_TIMEOUT, periph.properties()).await, Ok(Ok(_))),
+                };
+                // Link loss is detected and reported by the caller's health checks
+                if !ok {
+                    debug!("BLE keep-alive read failed");
+                }
+            }
+        }));
+    }
+
+    fn stop_keep_alive(&mut self) {
+        if let Some(task) = self.keep_alive_task.take() {
+            task.abort();
+        }
+    }
+
+    /// Subscribe to connection state changes (connected, disconnected, reconnecting, failed).
+    ///
+    /// Each call returns an independent receiver that sees events emitted after subscribing,
+    /// so a UI can reflect link status without polling `is_connected()`.
+    pub fn connection_events(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.events.subscribe()
+    }
+
+    /// Publish a connection state change to subscribers.
+    ///
+    /// The receiver reports connects and disconnects itself; this is for state only the
+    /// caller's health-check loop knows about (e.g. [`ConnectionEvent::Reconnecting`]).
+    pub fn emit_connection_event(&self, event: ConnectionEvent) {
+        debug!("Connection event: {:?}", event);
+        // Err just means nobody is subscribed
+        let _ = self.events.send(event);
+    }
+
+    /// Report the outcome of a connect attempt to subscribers
+    fn report_connect(&self, result: &Result<()>) {
+        match result {
+            Ok(()) => self.emit_connection_event(ConnectionEvent::Connected {
+                device_name: self.device_name.clone().unwrap_or_default(),
+            }),
+            Err(e) => self.emit_connection_event(ConnectionEvent::ConnectFailed { reason: e.to_string() }),
+        }
+    }
+
     /// Scan for memo devices matching a specific UID pattern
     /// Outputs DEVICE_FOUND events to stdout for Electron to consume
     pub async fn scan_for_uid(&self, uid: &str) -> Result<()> {
@@ -68,10 +316,9 @@ impl BleAudioReceiver {
             .context("No BLE adapter found")?;
 
         // Device advertises the service UUID - scan for it
-        let service_uuid = Uuid::parse_str(MEMO_AUDIO_SERVICE_UUID)?;
+        let service_uuid = self.config.service_uuid;
         adapter.start_scan(ScanFilter::default()).await.context("Failed to start scan")?;
 
-        let _target_device_name = format!("memo_{}", uid.to_uppercase());
         let scan_duration = Duration::from_secs(10); // Fixed 10 second scan
         let start = std::time::Instant::now();
 
@@ -84,10 +331,10 @@ impl BleAudioReceiver {
                     // Check for service UUID and matching device name
                     if props.services.contains(&service_uuid) {
                         if let Some(name) = &props.local_name {
-                            if name.to_lowercase().starts_with(DEVICE_NAME_PATTERN) {
+                            if self.config.matches_name(name) {
                                 // Extract UID from device name (memo_XXXXX -> XXXXX)
                                 let device_uid = name
-                                    .strip_prefix(DEVICE_NAME_PATTERN)
+                                    .get(self.config.name_pattern.len()..)
                                     .unwrap_or("")
                                     .to_uppercase();
 
@@ -109,14 +356,214 @@ impl BleAudioReceiver {
         Ok(())
     }
 
+    /// Scan for `scan_time` without connecting and list every device whose name matches the
+    /// name pattern or that advertises the audio service, strongest signal first.
+    ///
+    /// For a device picker: pass the chosen device's `address` to
+    /// [`connect_by_address`](Self::connect_by_address).
+    pub async fn scan_devices(&self, scan_time: Duration) -> Result<Vec<DiscoveredDevice>> {
+        info!("Scanning for BLE devices for {:?}", scan_time);
+        let manager = Manager::new().await
+            .context("Failed to create BLE manager")?;
+        let adapter: Adapter = manager.adapters().await
+            .context("Failed to get adapters")?
+            .into_iter()
+            .next()
+            .context("No BLE adapter found")?;
+
+        adapter.start_scan(ScanFilter::default()).await.context("Failed to start scan")?;
+        tokio::time::sleep(scan_time).await;
+        let peripherals = adapter.peripherals().await;
+        adapter.stop_scan().await.ok();
+
+        let mut devices = Vec::new();
+        for p in peripherals? {
+            let Ok(Some(props)) = p.properties().await else {
+                continue;
+            };
+            let name_matches = props.local_name.as_deref().is_some_and(|name| self.config.matches_name(name));
+            if !name_matches && !props.services.contains(&self.config.service_uuid) {
+                continue;
+            }
+            // macOS hides MAC addresses (all zeros) and identifies peripherals by UUID instead
+            let address = if props.address == btleplug::api::BDAddr::default() {
+                p.id().to_string()
+            } else {
+                props.address.to_string()
+            };
+            debug!("Discovered {:?} at {} (RSSI {:?})", props.local_name, address, props.rssi);
+            devices.push(DiscoveredDevice { name: props.local_name, address, rssi: props.rssi });
+        }
+        sort_by_signal(&mut devices);
+        Ok(devices)
+    }
+
     /// Scan for and connect to the memo device
     /// If preferred_device_name is provided, it will be prioritized during scanning
     pub async fn connect(&mut self, preferred_device_name: Option<&str>) -> Result<()> {
+        let result = self.scan_and_connect(preferred_device_name).await;
+        if result.is_ok() {
+            self.trigger_only = false;
+            self.last_device_name = self.device_name.clone();
+            self.last_address = None;
+            self.start_keep_alive();
+        }
+        self.report_connect(&result);
+        result
+    }
+
+    /// Connect to the peripheral with this ID/address (see [`is_device_address`]) and subscribe
+    /// to its audio, for picking one of several memo devices or returning to the last one.
+    ///
+    /// A peripheral the adapter already knows about (seen or connected before) is connected
+    /// right away; otherwise this scans until that exact device shows up. Names and the
+    /// service UUID are not checked. Fails without scanning if `address` is malformed.
+    pub async fn connect_by_address(&mut self, address: &str) -> Result<()> {
+        self.connect_address(address, false).await
+    }
+
+    /// [`connect_by_address`](Self::connect_by_address), in trigger-only mode if `trigger_only`
+    async fn connect_address(&mut self, address: &str, trigger_only: bool) -> Result<()> {
+        let address = address.trim();
+        let result = if is_device_address(address) {
+            self.find_and_connect_address(address, trigger_only).await
+        } else {
+            Err(anyhow::anyhow!(
+                "Invalid BLE address {:?}: expected a MAC address (AA:BB:CC:DD:EE:FF) or a peripheral UUID",
+                address
+            ))
+        };
+        if result.is_ok() {
+            self.trigger_only = trigger_only;
+            self.last_device_name = self.device_name.clone();
+            self.last_address = Some(address.to_string());
+            self.start_keep_alive();
+        }
+        self.report_connect(&result);
+        result
+    }
+
+    async fn find_and_connect_address(&mut self, address: &str, trigger_only: bool) -> Result<()> {
+        let manager = Manager::new().await
+            .context("Failed to create BLE manager")?;
+        let adapter: Adapter = manager.adapters().await
+            .context("Failed to get adapters")?
+            .into_iter()
+            .next()
+            .context("No BLE adapter found")?;
+
+        let mut found_periph = Self::find_known_address(&adapter, address).await?;
+        if found_periph.is_none() {
+            info!("Scanning for BLE device at {}", address);
+            eprintln!("🔍 Scanning for BLE device at {}...", address);
+            adapter.start_scan(ScanFilter::default()).await.context("Failed to start scan")?;
+            let start = std::time::Instant::now();
+            while found_periph.is_none() && start.elapsed() < SCAN_TIMEOUT {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                found_periph = Self::find_known_address(&adapter, address).await?;
+            }
+            adapter.stop_scan().await.ok();
+        }
+
+        let periph = found_periph.with_context(|| format!("No BLE device found at {}", address))?;
+        eprintln!("✅ Found device at {}", periph.id());
+        if trigger_only {
+            self.connect_peripheral_trigger_only(periph).await
+        } else {
+            self.connect_peripheral(periph).await
+        }
+    }
+
+    /// The adapter's peripheral with this ID/address, if it has seen it
+    async fn find_known_address(adapter: &Adapter, address: &str) -> Result<Option<Peripheral>> {
+        for p in adapter.peripherals().await? {
+            if let Ok(Some(props)) = p.properties().await {
+                if address_matches(address, &p.id().to_string(), &props.address.to_string()) {
+                    return Ok(Some(p));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Drop the current link (if any) and run the scan/connect/subscribe flow again, preferring
+    /// the previously connected device (by address after [`connect_by_address`](Self::connect_by_address)),
+    /// in the same mode (audio or trigger-only) as before.
+    ///
+    /// For a device that went out of range and came back: the old peripheral is disconnected
+    /// (errors ignored, since the link is usually already gone) and released before scanning,
+    /// and both characteristics are subscribed again. Get a new [`notifications`](Self::notifications)
+    /// stream afterwards; the old one has ended.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let preferred = self.device_name.clone().or_else(|| self.last_device_name.clone());
+        self.emit_connection_event(ConnectionEvent::Reconnecting);
+        self.release_link().await;
+        if let Some(address) = self.last_address.clone() {
+            self.connect_address(&address, self.trigger_only).await
+        } else if self.trigger_only {
+            self.connect_trigger_only(preferred.as_deref()).await
+        } else {
+            self.connect(preferred.as_deref()).await
+        }
+    }
+
+    /// Connect, retrying with backoff (2s doubling up to 60s) until it succeeds, for an
+    /// always-on station whose device may be out of range for a while.
+    ///
+    /// With nothing connected before, this connects like [`connect`](Self::connect); after a
+    /// lost link it [`reconnect`](Self::reconnect)s, preferring `preferred_device_name` or
+    /// else the previous device. Each failed attempt is reported as
+    /// [`ConnectionEvent::ConnectFailed`]. Never gives up: wrap it in `tokio::select!` or
+    /// `timeout` to stop waiting.
+    pub async fn connect_with_auto_reconnect(&mut self, preferred_device_name: Option<&str>) -> Result<()> {
+        if let Some(name) = preferred_device_name {
+            self.last_device_name = Some(name.to_string());
+        }
+        let mut backoff = RECONNECT_MIN_BACKOFF;
+        loop {
+            let result = if self.periph.is_some() || self.last_device_name.is_some() {
+                self.reconnect().await
+            } else {
+                self.connect(None).await
+            };
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!("Reconnect failed: {:#}; retrying in {:?}", e, backoff);
+                    eprintln!("🔁 Reconnect failed ({}), retrying in {}s", e, backoff.as_secs());
+                }
+            }
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(RECONNECT_MAX_BACKOFF);
+        }
+    }
+
+    /// Forget the current peripheral after a lost link, disconnecting it first (best effort)
+    /// so the adapter doesn't keep a stale connection around
+    async fn release_link(&mut self) {
+        self.stop_keep_alive();
+        let Some(periph) = self.periph.take() else {
+            return;
+        };
+        match timeout(RELEASE_TIMEOUT, periph.disconnect()).await {
+            Ok(Ok(())) => debug!("Released previous peripheral"),
+            Ok(Err(e)) => debug!("Disconnecting lost peripheral failed (already gone?): {}", e),
+            Err(_) => debug!("Disconnecting lost peripheral timed out"),
+        }
+        self.char_audio_data = None;
+        self.char_control_tx = None;
+        self.char_control_rx = None;
+        self.char_battery = None;
+        self.device_name = None;
+        self.emit_connection_event(ConnectionEvent::Disconnected(DisconnectReason::ConnectionLost));
+    }
+
+    async fn scan_and_connect(&mut self, preferred_device_name: Option<&str>) -> Result<()> {
         if let Some(pref_name) = preferred_device_name {
-            info!("Scanning for memo device (preferred: {}, pattern: {}*)", pref_name, DEVICE_NAME_PATTERN);
+            info!("Scanning for memo device (preferred: {}, pattern: {}*)", pref_name, self.config.name_pattern);
             eprintln!("🔍 Scanning for BLE device (preferred: {})...", pref_name);
         } else {
-            info!("Scanning for memo device (pattern: {}*)", DEVICE_NAME_PATTERN);
+            info!("Scanning for memo device (pattern: {}*)", self.config.name_pattern);
             eprintln!("🔍 Scanning for BLE device...");
         }
 
@@ -130,7 +577,7 @@ impl BleAudioReceiver {
             .context("No BLE adapter found")?;
 
         // Device advertises the service UUID - scan for it
-        let service_uuid = Uuid::parse_str(MEMO_AUDIO_SERVICE_UUID)?;
+        let service_uuid = self.config.service_uuid;
         adapter.start_scan(ScanFilter::default()).await.context("Failed to start scan")?;
 
         let mut found_periph: Option<Peripheral> = None;
@@ -142,6 +589,14 @@ impl BleAudioReceiver {
 
             for p in peripherals {
                 if let Ok(Some(props)) = p.properties().await {
+                    if let Some(matches) = self.config.matches_address(&p.id().to_string(), &props.address.to_string()) {
+                        if matches {
+                            eprintln!("✅ Found device at {}", p.id());
+                            found_periph = Some(p);
+                            break;
+                        }
+                        continue;
+                    }
                     // Check for service UUID in advertising data
                     if props.services.contains(&service_uuid) {
                         eprintln!("✅ Found device with Memo service");
@@ -162,7 +617,7 @@ impl BleAudioReceiver {
                     }
                     // Or check name
                     if let Some(name) = &props.local_name {
-                        if name.to_lowercase().starts_with(DEVICE_NAME_PATTERN) {
+                        if self.config.matches_name(name) {
                             // If preferred device name is specified, only match that device
                             if let Some(pref_name) = preferred_device_name {
                                 // Match if name contains the preferred name
@@ -185,6 +640,12 @@ impl BleAudioReceiver {
         
         adapter.stop_scan().await.ok();
         let periph = found_periph.context("Device not found")?;
+        self.connect_peripheral(periph).await
+    }
+
+    /// Connect to a found peripheral, discover the audio service and subscribe to its
+    /// audio and control characteristics
+    async fn connect_peripheral(&mut self, periph: Peripheral) -> Result<()> {
         eprintln!("🔌 Connecting...");
 
         // Connect
@@ -206,14 +667,7 @@ impl BleAudioReceiver {
             .context("Failed to discover services")?;
 
         // Find Memo Audio Service and characteristics
-        let service_uuid = Uuid::parse_str(MEMO_AUDIO_SERVICE_UUID)
-            .context("Failed to parse service UUID")?;
-        let audio_data_uuid = Uuid::parse_str(MEMO_AUDIO_DATA_CHAR_UUID)
-            .context("Failed to parse audio data characteristic UUID")?;
-        let control_tx_uuid = Uuid::parse_str(MEMO_CONTROL_TX_CHAR_UUID)
-            .context("Failed to parse control TX characteristic UUID")?;
-        let battery_uuid = Uuid::parse_str(MEMO_BATTERY_CHAR_UUID)
-            .context("Failed to parse battery characteristic UUID")?;
+        let BleConfig { service_uuid, audio_data_uuid, control_tx_uuid, control_rx_uuid, battery_uuid, .. } = self.config;
 
         let services = periph.services();
         let mut found_service = false;
@@ -240,6 +694,9 @@ impl BleAudioReceiver {
                     } else if char.uuid == control_tx_uuid {
                         info!("Found Control TX characteristic");
                         self.char_control_tx = Some(char);
+                    } else if char.uuid == control_rx_uuid {
+                        info!("Found Control RX characteristic");
+                        self.char_control_rx = Some(char);
                     } else if char.uuid == battery_uuid {
                         info!("Found Battery characteristic");
                         self.char_battery = Some(char);
@@ -250,7 +707,7 @@ impl BleAudioReceiver {
         }
 
         if !found_service {
-            error!("Memo Audio Service not found. Expected UUID: {}", MEMO_AUDIO_SERVICE_UUID);
+            error!("Memo Audio Service not found. Expected UUID: {}", service_uuid);
             error!("Available services:");
             for service in periph.services() {
                 error!("  - {}", service.uuid);
@@ -321,6 +778,12 @@ impl BleAudioReceiver {
 
     /// Disconnect from the current device
     pub async fn disconnect(&mut self) -> Result<()> {
+        self.disconnect_with_reason(DisconnectReason::UserRequested).await
+    }
+
+    /// Disconnect from the current device, reporting `reason` to connection event subscribers
+    pub async fn disconnect_with_reason(&mut self, reason: DisconnectReason) -> Result<()> {
+        self.stop_keep_alive();
         if let Some(ref periph) = self.periph {
             let device_name = self.device_name.clone().unwrap_or_else(|| "Unknown".to_string());
             info!("Disconnecting from {}", device_name);
@@ -330,11 +793,13 @@ impl BleAudioReceiver {
             self.periph = None;
             self.char_audio_data = None;
             self.char_control_tx = None;
+            self.char_control_rx = None;
             self.char_battery = None;
             self.device_name = None;
 
             println!("DISCONNECTED:user_requested");
             info!("✅ Disconnected from {}", device_name);
+            self.emit_connection_event(ConnectionEvent::Disconnected(reason));
         }
         Ok(())
     }
@@ -353,6 +818,18 @@ impl BleAudioReceiver {
     /// This allows using BLE device as a remote trigger while audio comes from system mic
     /// If preferred_device_name is provided, it will be prioritized during scanning
     pub async fn connect_trigger_only(&mut self, preferred_device_name: Option<&str>) -> Result<()> {
+        let result = self.scan_and_connect_trigger_only(preferred_device_name).await;
+        if result.is_ok() {
+            self.trigger_only = true;
+            self.last_device_name = self.device_name.clone();
+            self.last_address = None;
+            self.start_keep_alive();
+        }
+        self.report_connect(&result);
+        result
+    }
+
+    async fn scan_and_connect_trigger_only(&mut self, preferred_device_name: Option<&str>) -> Result<()> {
         if let Some(pref_name) = preferred_device_name {
             info!("Scanning for memo device (trigger-only mode, preferred: {})...", pref_name);
             eprintln!("🔍 Scanning for BLE device (trigger-only, preferred: {})...", pref_name);
@@ -371,7 +848,7 @@ impl BleAudioReceiver {
             .context("No BLE adapter found")?;
 
         // Device advertises the service UUID - scan for it
-        let service_uuid = Uuid::parse_str(MEMO_AUDIO_SERVICE_UUID)?;
+        let service_uuid = self.config.service_uuid;
         adapter.start_scan(ScanFilter::default()).await.context("Failed to start scan")?;
         
         let mut found_periph: Option<Peripheral> = None;
@@ -383,6 +860,14 @@ impl BleAudioReceiver {
             
             for p in peripherals {
                 if let Ok(Some(props)) = p.properties().await {
+                    if let Some(matches) = self.config.matches_address(&p.id().to_string(), &props.address.to_string()) {
+                        if matches {
+                            eprintln!("✅ Found device at {}", p.id());
+                            found_periph = Some(p);
+                            break;
+                        }
+                        continue;
+                    }
                     // Check for service UUID in advertising data
                     if props.services.contains(&service_uuid) {
                         eprintln!("✅ Found device with Memo service");
@@ -391,7 +876,7 @@ impl BleAudioReceiver {
                     }
                     // Or check name
                     if let Some(name) = &props.local_name {
-                        if name.to_lowercase().starts_with(DEVICE_NAME_PATTERN) {
+                        if self.config.matches_name(name) {
                             eprintln!("✅ Found: {}", name);
                             found_periph = Some(p);
                             break;
@@ -404,6 +889,11 @@ impl BleAudioReceiver {
         
         adapter.stop_scan().await.ok();
         let periph = found_periph.context("Device not found")?;
+        self.connect_peripheral_trigger_only(periph).await
+    }
+
+    /// Connect to `periph` and subscribe to Control TX only (see [`connect_trigger_only`](Self::connect_trigger_only))
+    async fn connect_peripheral_trigger_only(&mut self, periph: Peripheral) -> Result<()> {
         eprintln!("🔌 Connecting...");
         
         timeout(Duration::from_secs(10), periph.connect())
@@ -423,10 +913,7 @@ impl BleAudioReceiver {
             .context("Failed to discover services")?;
 
         // Find Memo Audio Service and Control TX characteristic only
-        let service_uuid = Uuid::parse_str(MEMO_AUDIO_SERVICE_UUID)
-            .context("Failed to parse service UUID")?;
-        let control_tx_uuid = Uuid::parse_str(MEMO_CONTROL_TX_CHAR_UUID)
-            .context("Failed to parse control TX characteristic UUID")?;
+        let BleConfig { service_uuid, control_tx_uuid, control_rx_uuid, .. } = self.config;
 
         let services = periph.services();
         let mut found_service = false;
@@ -436,12 +923,14 @@ impl BleAudioReceiver {
                 found_service = true;
                 info!("Found Memo Audio Service");
                 
-                // Find control TX characteristic only (not audio data)
+                // Find control characteristics only (not audio data)
                 for char in service.characteristics {
                     if char.uuid == control_tx_uuid {
                         info!("Found Control TX characteristic (trigger-only mode)");
                         self.char_control_tx = Some(char);
-                        break;
+                    } else if char.uuid == control_rx_uuid {
+                        info!("Found Control RX characteristic (trigger-only mode)");
+                        self.char_control_rx = Some(char);
                     }
                 }
                 break;
@@ -491,6 +980,26 @@ impl BleAudioReceiver {
         Ok(())
     }
     
+    /// Write a raw command to the device's Control RX characteristic.
+    ///
+    /// Uses a write with response when the characteristic supports it, so a rejected command
+    /// surfaces as an error, and falls back to write-without-response otherwise.
+    pub async fn send_control(&self, command: &[u8]) -> Result<()> {
+        let periph = self.periph.as_ref().context("Not connected to a BLE device")?;
+        let char = self.char_control_rx.as_ref()
+            .context("Device has no Control RX characteristic (firmware does not accept commands)")?;
+        let write_type = control_write_type(char.properties)
+            .with_context(|| format!("Control RX characteristic is not writable ({:?})", char.properties))?;
+        debug!("Sending control command {:02X?} ({:?})", command, write_type);
+        periph.write(char, command, write_type).await
+            .context("Failed to write control command")
+    }
+
+    /// Send a known command, see [`send_control`](Self::send_control)
+    pub async fn send_command(&self, command: ControlCommand) -> Result<()> {
+        self.send_control(&[command.code()]).await
+    }
+
     /// Get the device name if available
     pub fn device_name(&self) -> Option<&String> {
         self.device_name.as_ref()
@@ -575,10 +1084,141 @@ pub enum NotificationResult {
     None,
 }
 
+/// Default window after a RESP_SPEECH_END in which a new RESP_SPEECH_START is treated as link bounce
+pub const DEFAULT_DEBOUNCE_WINDOW: Duration = Duration::from_millis(150);
+/// Recordings shorter than this are most likely noise on the control line and are discarded
+const MIN_RECORDING_DURATION: Duration = Duration::from_millis(200);
+
+/// What to do with a control code after debouncing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlAction {
+    /// Start recording
+    Start,
+    /// Stop recording and transcribe
+    Stop,
+    /// Stop recording and drop the audio (recording was too short to be real)
+    Discard,
+    /// Duplicate or bounced code, do nothing
+    Ignore,
+}
+
+/// Debounces RESP_SPEECH_START / RESP_SPEECH_END from a flaky BLE link.
+///
+/// A START while already started (or END while stopped) is a duplicate and ignored, a START
+/// within `window` of the last END is treated as a bounce, and recordings shorter than
+/// 200ms end in [`ControlAction::Discard`].
+#[derive(Debug)]
+pub struct ControlDebounce {
+    window: Duration,
+    started_at: Option<Instant>,
+    last_end: Option<Instant>,
+}
+
+impl ControlDebounce {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            started_at: None,
+            last_end: None,
+        }
+    }
+
+    /// Feed a control code received from the device
+    pub fn on_control(&mut self, code: u8) -> ControlAction {
+        let now = Instant::now();
+        match code {
+            RESP_SPEECH_START => {
+                if self.started_at.is_some() {
+                    debug!("Ignoring duplicate RESP_SPEECH_START");
+                    return ControlAction::Ignore;
+                }
+                if self.last_end.is_some_and(|t| now.duration_since(t) < self.window) {
+                    debug!("Ignoring RESP_SPEECH_START within debounce window");
+                    return ControlAction::Ignore;
+                }
+                self.started_at = Some(now);
+                ControlAction::Start
+            }
+            RESP_SPEECH_END => {
+                let Some(started_at) = self.started_at.take() else {
+                    debug!("Ignoring duplicate RESP_SPEECH_END");
+                    return ControlAction::Ignore;
+                };
+                self.last_end = Some(now);
+                if now.duration_since(started_at) < MIN_RECORDING_DURATION {
+                    ControlAction::Discard
+                } else {
+                    ControlAction::Stop
+                }
+            }
+            _ => ControlAction::Ignore,
+        }
+    }
+}
+
 impl Drop for BleAudioReceiver {
     fn drop(&mut self) {
+        self.stop_keep_alive();
         if self.periph.is_some() {
             warn!("BleAudioReceiver dropped without explicit disconnect");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_matches_names_and_addresses() {
+        let config = BleConfig::default();
+        assert!(config.matches_name("memo_C9AA6"));
+        assert!(config.matches_name("MEMO_c9aa6"));
+        assert!(!config.matches_name("Zephyr [memo_C9AA6]"));
+        assert_eq!(config.matches_address("64D5A7E1-B149-191F-9B11-96F5CCF590BF", "00:00:00:00:00:00"), None);
+        assert_eq!(config.service_uuid.to_string(), "1234a000-1234-5678-1234-56789abcdef0");
+
+        let custom = BleConfig {
+            name_pattern: "Acme-".to_string(),
+            device_address: Some("AA:BB:CC:DD:EE:FF".to_string()),
+            ..BleConfig::default()
+        };
+        assert!(custom.matches_name("acme-42"));
+        assert!(!custom.matches_name("memo_C9AA6"));
+        assert_eq!(custom.matches_address("hci0/dev_AA_BB", "aa:bb:cc:dd:ee:ff"), Some(true));
+        assert_eq!(custom.matches_address("hci0/dev_11_22", "11:22:33:44:55:66"), Some(false));
+    }
+
+    #[test]
+    fn control_commands_pick_a_write_type() {
+        assert_eq!(ControlCommand::parse(" Flash_LED "), Some(ControlCommand::FlashLed));
+        assert_eq!(ControlCommand::parse("reboot"), None);
+        assert_eq!(ControlCommand::StopCapture.code(), CMD_STOP_CAPTURE);
+
+        let both = CharPropFlags::WRITE | CharPropFlags::WRITE_WITHOUT_RESPONSE;
+        assert_eq!(control_write_type(both), Some(WriteType::WithResponse));
+        assert_eq!(control_write_type(CharPropFlags::WRITE_WITHOUT_RESPONSE), Some(WriteType::WithoutResponse));
+        assert_eq!(control_write_type(CharPropFlags::READ | CharPropFlags::NOTIFY), None);
+    }
+
+    #[test]
+    fn discovered_devices_sort_by_signal() {
+        let device = |address: &str, rssi| DiscoveredDevice { name: None, address: address.to_string(), rssi };
+        let mut devices = vec![device("far", Some(-90)), device("unknown", None), device("near", Some(-40))];
+        sort_by_signal(&mut devices);
+        let order: Vec<&str> = devices.iter().map(|d| d.address.as_str()).collect();
+        assert_eq!(order, ["near", "far", "unknown"]);
+    }
+
+    #[test]
+    fn device_addresses_are_validated() {
+        assert!(is_device_address("AA:BB:CC:DD:EE:FF"));
+        assert!(is_device_address("aa:bb:cc:0d:ee:f1"));
+        assert!(is_device_address("64D5A7E1-B149-191F-9B11-96F5CCF590BF"));
+        assert!(!is_device_address("memo_C9AA6"));
+        assert!(!is_device_address("AA:BB:CC:DD:EE"));
+        assert!(!is_device_address("AA:BB:CC:DD:EE:GG"));
+        assert!(!is_device_address("AABBCCDDEEFF"));
+        assert!(!is_device_address("64d5a7e1b149191f9b1196f5ccf590bf"));
+    }
+}
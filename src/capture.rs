@@ -0,0 +1,340 @@
+//! Microphone capture helpers (requires the `capture` feature)
+//!
+//! Device selection and sample conversion shared by the `memo-stt` binary and library
+//! users that record with [cpal](https://docs.rs/cpal).
+
+use cpal::traits::{DeviceTrait, HostTrait};
+use std::time::Duration;
+use crate::{Error, Result};
+
+/// Names of all input devices on the default host, in enumeration order.
+///
+/// The position in this list is the index accepted by [`find_input_device_by_spec`].
+///
+/// # Example
+///
+/// ```no_run
+/// for (i, name) in memo_stt::capture::list_input_devices()?.iter().enumerate() {
+///     println!("{}: {}", i, name);
+/// }
+/// # Ok::<(), memo_stt::Error>(())
+/// ```
+pub fn list_input_devices() -> Result<Vec<String>> {
+    let devices = cpal::default_host()
+        .input_devices()
+        .map_err(|e| Error::Audio(format!("Failed to enumerate input devices: {}", e)))?;
+    Ok(devices
+        .map(|d| d.name().unwrap_or_else(|_| "?".to_string()))
+        .collect())
+}
+
+/// Select the input device whose name contains `name` (case-insensitive).
+///
+/// Falls back to the default input device when `name` is `None` or no device matches.
+pub fn select_input_device(name: Option<&str>) -> Result<cpal::Device> {
+    let host = cpal::default_host();
+    let matched = name.and_then(|name| {
+        let name_lower = name.trim().to_lowercase();
+        host.input_devices()
+            .ok()?
+            .find(|d| d.name().is_ok_and(|n| n.to_lowercase().contains(&name_lower)))
+    });
+    if let (Some(name), None) = (name, &matched) {
+        eprintln!("No input device matching {:?}, using default", name);
+    }
+    matched
+        .or_else(|| host.default_input_device())
+        .ok_or_else(|| Error::Audio("No input device found".into()))
+}
+
+/// Resolve input device: "default", numeric index, or substring name match (e.g. "AirPods", "External Microphone").
+pub fn find_input_device_by_spec(host: &cpal::Host, spec: &str) -> Option<cpal::Device> {
+    let spec = spec.trim();
+    if spec.is_empty() || spec.eq_ignore_ascii_case("default") {
+        return host.default_input_device();
+    }
+    let devices: Vec<cpal::Device> = match host.input_devices() {
+        Ok(iter) => iter.collect(),
+        Err(_) => return host.default_input_device(),
+    };
+    if let Ok(idx) = spec.parse::<usize>() {
+        if idx < devices.len() {
+            return Some(devices[idx].clone());
+        }
+        return host.default_input_device();
+    }
+    let spec_lower = spec.to_lowercase();
+    for dev in &devices {
+        if let Ok(name) = dev.name() {
+            if name.to_lowercase().contains(&spec_lower) {
+                return Some(dev.clone());
+            }
+        }
+    }
+    host.default_input_device()
+}
+
+/// Prefer mono input and the highest sample rate up to 48 kHz (better quality when the device allows it).
+pub fn best_input_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig> {
+    use cpal::SampleRate;
+    let default = device
+        .default_input_config()
+        .map_err(|e| Error::Audio(format!("Failed to get input config: {}", e)))?;
+    let Ok(configs) = device.supported_input_configs() else {
+        return Ok(default);
+    };
+    let candidates: Vec<cpal::SupportedStreamConfigRange> = configs.collect();
+    if candidates.is_empty() {
+        return Ok(default);
+    }
+    let pick_mono = candidates
+        .iter()
+        .filter(|c| c.channels() == 1)
+        .max_by_key(|c| c.max_sample_rate().0);
+    let pick_any = candidates
+        .iter()
+        .max_by_key(|c| c.max_sample_rate().0);
+    let range = pick_mono.or(pick_any).unwrap_or(&candidates[0]);
+    let max_sr = range.max_sample_rate().0;
+    let min_sr = range.min_sample_rate().0;
+    let target = max_sr.min(48_000).max(min_sr);
+    Ok(range.with_sample_rate(SampleRate(target)))
+}
+
+/// Append interleaved `i16` samples to `buf`, averaging channels down to mono.
+pub fn extend_buffer_mono_i16(buf: &mut Vec<i16>, data: &[i16], channels: usize) {
+    match channels {
+        1 => buf.extend_from_slice(data),
+        n if n > 1 => {
+            for frame in data.chunks_exact(n) {
+                let sum: i32 = frame.iter().map(|&s| s as i32).sum();
+                buf.push((sum / n as i32) as i16);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Append interleaved `f32` samples (-1.0..=1.0) to `buf` as mono `i16`.
+pub fn extend_buffer_mono_f32(buf: &mut Vec<i16>, data: &[f32], channels: usize) {
+    match channels {
+        1 => {
+            for &s in data {
+                buf.push((s.clamp(-1.0, 1.0) * 32767.0) as i16);
+            }
+        }
+        n if n > 1 => {
+            for frame in data.chunks_exact(n) {
+                let mut acc = 0.0f32;
+                for &s in frame {
+                    acc += s.clamp(-1.0, 1.0);
+                }
+                buf.push(((acc / n as f32) * 32767.0) as i16);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Append interleaved `u16` samples to `buf` as mono `i16`.
+pub fn extend_buffer_mono_u16(buf: &mut Vec<i16>, data: &[u16], channels: usize) {
+    match channels {
+        1 => {
+            for &s in data {
+                buf.push(((s as i32) - 32768) as i16);
+            }
+        }
+        n if n > 1 => {
+            for frame in data.chunks_exact(n) {
+                let sum: i32 = frame.iter().map(|&s| (s as i32) - 32768).sum();
+                buf.push((sum / n as i32) as i16);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Compute RMS (root mean square) of i16 samples for VAD.
+pub fn compute_rms(samples: &[i16]) -> f32 {
+    crate::audio::rms(samples)
+}
+
+/// Energy-gated end-of-utterance detector for hands-free recording.
+///
+/// Feed it mono audio as it arrives; [`push`](Self::push) returns `true` once speech has been
+/// heard and the RMS has then stayed below `threshold` for `silence`. Leading silence
+/// (before the user starts talking) never ends the utterance.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::capture::AutoStop;
+/// use std::time::Duration;
+///
+/// let mut auto_stop = AutoStop::new(16000, 600.0, Duration::from_millis(800));
+/// assert!(!auto_stop.push(&vec![4000i16; 8000])); // speech
+/// assert!(auto_stop.push(&vec![0i16; 16000]));    // 1s of silence
+/// ```
+#[derive(Debug, Clone)]
+pub struct AutoStop {
+    threshold: f32,
+    window_samples: usize,
+    silence_samples: usize,
+    silent_run: usize,
+    heard_speech: bool,
+}
+
+impl AutoStop {
+    /// # Arguments
+    ///
+    /// * `sample_rate` - Sample rate of the audio that will be pushed
+    /// * `threshold` - RMS below which a 50ms window counts as silence (i16 scale, e.g. 600.0)
+    /// * `silence` - Trailing silence that ends the utterance (e.g. 800ms)
+    pub fn new(sample_rate: u32, threshold: f32, silence: Duration) -> Self {
+        Self {
+            threshold,
+            window_samples: (sample_rate as usize / 20).max(1),
+            silence_samples: (sample_rate as f64 * silence.as_secs_f64()) as usize,
+            silent_run: 0,
+            heard_speech: false,
+        }
+    }
+
+    /// Feed new audio; returns `true` when the utterance should end.
+    pub fn push(&mut self, samples: &[i16]) -> bool {
+        for window in samples.chunks(self.window_samples) {
+            if compute_rms(window) >= self.threshold {
+                self.heard_speech = true;
+                self.silent_run = 0;
+            } else if self.heard_speech {
+                self.silent_run += window.len();
+            }
+        }
+        self.heard_speech && self.silent_run >= self.silence_samples
+    }
+
+    /// Forget all audio seen so far (e.g. when a new recording starts).
+    pub fn reset(&mut self) {
+        self.silent_run = 0;
+        self.heard_speech = false;
+    }
+}
+
+/// Microphone [`AudioSource`](crate::source::AudioSource): records continuously and yields
+/// one utterance per stretch of speech, ended by trailing silence like [`AutoStop`].
+///
+/// Audio before speech starts is dropped, except for a short pre-roll so the first word
+/// isn't clipped. Utterances are cut at 28s even without a pause (whisper's window is 30s).
+///
+/// # Example
+///
+/// ```no_run
+/// use memo_stt::capture::MicSource;
+/// use memo_stt::source::AudioSource;
+/// use std::time::Duration;
+///
+/// let mut mic = MicSource::new(None, 600.0, Duration::from_millis(800))?;
+/// let mut engine = memo_stt::SttEngine::new_default(mic.sample_rate())?;
+/// while let Some(text) = engine.transcribe_source(&mut mic)? {
+///     println!("{}", text);
+/// }
+/// # Ok::<(), memo_stt::Error>(())
+/// ```
+pub struct MicSource {
+    _stream: cpal::Stream,
+    chunks: std::sync::mpsc::Receiver<Vec<i16>>,
+    sample_rate: u32,
+    threshold: f32,
+    auto_stop: AutoStop,
+}
+
+impl MicSource {
+    /// Open the input device named `device` (see [`select_input_device`]; `None` is the
+    /// default device) and start recording.
+    ///
+    /// `threshold` and `silence` are as for [`AutoStop::new`].
+    pub fn new(device: Option<&str>, threshold: f32, silence: Duration) -> Result<Self> {
+        use cpal::traits::StreamTrait;
+
+        let device = select_input_device(device)?;
+        let config = best_input_config(&device)?;
+        let sample_rate = config.sample_rate().0;
+        let channels = config.channels() as usize;
+        // Callbacks come every ~10ms; audio beyond ~10s that nobody has asked for is dropped
+        let (tx, chunks) = std::sync::mpsc::sync_channel(1024);
+        let on_error = |e| log::warn!("Microphone stream error: {}", e);
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            cpal::SampleFormat::I16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &cpal::InputCallbackInfo| {
+                    let mut mono = Vec::with_capacity(data.len() / channels.max(1));
+                    extend_buffer_mono_i16(&mut mono, data, channels);
+                    let _ = tx.try_send(mono);
+                },
+                on_error,
+                None,
+            ),
+            cpal::SampleFormat::F32 => device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                    let mut mono = Vec::with_capacity(data.len() / channels.max(1));
+                    extend_buffer_mono_f32(&mut mono, data, channels);
+                    let _ = tx.try_send(mono);
+                },
+                on_error,
+                None,
+            ),
+            cpal::SampleFormat::U16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[u16], _: &cpal::InputCallbackInfo| {
+                    let mut mono = Vec::with_capacity(data.len() / channels.max(1));
+                    extend_buffer_mono_u16(&mut mono, data, channels);
+                    let _ = tx.try_send(mono);
+                },
+                on_error,
+                None,
+            ),
+            other => return Err(Error::Audio(format!("Unsupported sample format {:?}", other))),
+        }
+        .map_err(|e| Error::Audio(format!("Failed to open input stream: {}", e)))?;
+        stream.play().map_err(|e| Error::Audio(format!("Failed to start input stream: {}", e)))?;
+
+        Ok(Self {
+            _stream: stream,
+            chunks,
+            sample_rate,
+            threshold,
+            auto_stop: AutoStop::new(sample_rate, threshold, silence),
+        })
+    }
+}
+
+impl crate::source::AudioSource for MicSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_utterance(&mut self) -> Result<Option<Vec<i16>>> {
+        let pre_roll = self.sample_rate as usize * 3 / 10;
+        let max_len = self.sample_rate as usize * 28;
+        let mut utterance = Vec::new();
+        let mut speaking = false;
+        self.auto_stop.reset();
+        // Speech that started while the previous utterance was being transcribed is still queued
+        while let Ok(chunk) = self.chunks.recv() {
+            utterance.extend_from_slice(&chunk);
+            speaking |= compute_rms(&chunk) >= self.threshold;
+            if !speaking {
+                let excess = utterance.len().saturating_sub(pre_roll);
+                utterance.drain(..excess);
+                continue;
+            }
+            if self.auto_stop.push(&chunk) || utterance.len() >= max_len {
+                return Ok(Some(utterance));
+            }
+        }
+        // The stream stopped delivering audio (device removed)
+        Ok(speaking.then_some(utterance))
+    }
+}
@@ -0,0 +1,147 @@
+//! Setup self-check
+//!
+//! [`diagnostics`] checks each thing zero-config transcription depends on (model, GPU,
+//! microphone, network) and reports them together, instead of the first missing piece
+//! surfacing as an error somewhere later. The `memo-stt --doctor` command prints this
+//! report plus the binary's own checks.
+//!
+//! # Example
+//!
+//! ```no_run
+//! for check in memo_stt::diagnostics() {
+//!     println!("{}", check);
+//! }
+//! ```
+
+use std::time::{Duration, Instant};
+use crate::engine::{Backend, GpuOptions};
+use crate::SttEngine;
+
+/// How long the network check waits for the model host
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of one [`Check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    /// Works
+    Pass,
+    /// Works, but not as well as it could (e.g. CPU-only), or will be fixed automatically
+    Warn,
+    /// Transcription won't work until this is fixed
+    Fail,
+}
+
+/// One line of the [`diagnostics`] report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked ("model", "gpu", "microphone", "network", ...)
+    pub name: &'static str,
+    /// Pass, warn or fail
+    pub status: CheckStatus,
+    /// What was found, and for failures what to do about it
+    pub detail: String,
+}
+
+impl Check {
+    /// A check result, e.g. for adding app-specific checks to the report
+    pub fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self { name, status, detail: detail.into() }
+    }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mark = match self.status {
+            CheckStatus::Pass => "✅",
+            CheckStatus::Warn => "⚠️ ",
+            CheckStatus::Fail => "❌",
+        };
+        write!(f, "{} {}: {}", mark, self.name, self.detail)
+    }
+}
+
+/// Check the default model (present, valid and loadable), the GPU backend, the default
+/// microphone (feature `capture`) and whether the model host is reachable.
+///
+/// Loads the model, so this takes about as long as [`SttEngine::new_default`]; it never
+/// downloads anything.
+pub fn diagnostics() -> Vec<Check> {
+    let (model, backend) = check_model();
+    let model_missing = model.status != CheckStatus::Pass;
+    let mut checks = vec![model, check_gpu(backend)];
+    #[cfg(feature = "capture")]
+    checks.push(check_microphone());
+    checks.push(check_network(model_missing));
+    checks
+}
+
+/// The model check, and the backend the model was loaded on if it loaded
+fn check_model() -> (Check, Option<Backend>) {
+    let path = match crate::model::locate_model(crate::DEFAULT_MODEL) {
+        Ok(path) => path,
+        Err(_) => {
+            let detail = format!(
+                "{} not downloaded yet; it is fetched on first use (~500MB) to {}",
+                crate::DEFAULT_MODEL,
+                crate::default_model_path().display()
+            );
+            return (Check::new("model", CheckStatus::Warn, detail), None);
+        }
+    };
+    if let Err(e) = crate::validate_model(&path) {
+        return (Check::new("model", CheckStatus::Fail, e.to_string()), None);
+    }
+    let start = Instant::now();
+    match SttEngine::new_with_gpu(&path, 16000, GpuOptions::default()) {
+        Ok(engine) => {
+            let detail = format!("{} (loaded in {:.1}s)", path.display(), start.elapsed().as_secs_f32());
+            (Check::new("model", CheckStatus::Pass, detail), Some(engine.backend()))
+        }
+        Err(e) => (Check::new("model", CheckStatus::Fail, format!("{} failed to load: {}", path.display(), e)), None),
+    }
+}
+
+fn check_gpu(loaded: Option<Backend>) -> Check {
+    match loaded.unwrap_or_else(|| Backend::select(GpuOptions::default())) {
+        Backend::Gpu { name, device_index } => {
+            Check::new("gpu", CheckStatus::Pass, format!("{} (device {})", name, device_index))
+        }
+        Backend::Cpu => Check::new(
+            "gpu",
+            CheckStatus::Warn,
+            "no GPU backend compiled in; transcription runs on the CPU (slower)",
+        ),
+    }
+}
+
+#[cfg(feature = "capture")]
+fn check_microphone() -> Check {
+    use cpal::traits::DeviceTrait;
+
+    let result = crate::capture::select_input_device(None).and_then(|device| {
+        let name = device.name().unwrap_or_else(|_| "?".to_string());
+        let config = crate::capture::best_input_config(&device)?;
+        Ok(format!(
+            "{} ({} Hz, {} ch, {:?})",
+            name,
+            config.sample_rate().0,
+            config.channels(),
+            config.sample_format()
+        ))
+    });
+    match result {
+        Ok(detail) => Check::new("microphone", CheckStatus::Pass, detail),
+        Err(e) => Check::new("microphone", CheckStatus::Fail, format!("{} (check the device is connected and allowed to record)", e)),
+    }
+}
+
+/// Only a failure if the model still has to be downloaded
+fn check_network(model_missing: bool) -> Check {
+    match crate::model::check_model_host(NETWORK_TIMEOUT) {
+        Ok(()) => Check::new("network", CheckStatus::Pass, "model host reachable"),
+        Err(e) if model_missing => {
+            Check::new("network", CheckStatus::Fail, format!("{}; the model can't be downloaded (set HTTPS_PROXY if needed)", e))
+        }
+        Err(e) => Check::new("network", CheckStatus::Warn, format!("{} (not needed, the model is already downloaded)", e)),
+    }
+}
@@ -1,9 +1,426 @@
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState};
-use std::path::Path;
-use std::sync::{Arc, Mutex};
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters, WhisperState, WhisperToken, WhisperTokenData};
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_int;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use log::{debug, info, warn};
+use crate::model::CancelToken;
 use crate::Result;
+use crate::postprocess::{self, Locale, NoopProcessor, OutputStyle, TranscriptProcessor};
 use num_cpus;
 
+/// Window length for [`SttEngine::transcribe_robust`] (whisper's context)
+const ROBUST_WINDOW_SECS: usize = 30;
+/// How far [`SttEngine::transcribe_robust`] may move a window edge back to land in a pause
+const ROBUST_SNAP_SECS: usize = 3;
+/// Shortest pause a window edge is snapped to
+const ROBUST_MIN_GAP_MS: u32 = 300;
+/// Overlap between consecutive [`SttEngine::transcribe_stream`] chunks (at most a quarter
+/// of the chunk)
+const STREAM_OVERLAP_SECS: f32 = 2.0;
+/// Words of earlier chunks [`SttEngine::transcribe_stream`] passes on as the prompt
+const STREAM_CONTEXT_WORDS: usize = 32;
+/// Encoder frames per second of audio (whisper's 1500 cover 30 seconds)
+const AUDIO_CTX_PER_SEC: usize = 50;
+/// Frames [`SttEngine::set_auto_audio_ctx`] adds beyond the clip, so its last words aren't
+/// squeezed against the edge of the context
+const AUTO_AUDIO_CTX_MARGIN: usize = 64;
+/// Longest the idle-unload thread sleeps between checks
+const IDLE_POLL: Duration = Duration::from_secs(1);
+/// Capacity of the conversion buffer on creation and after [`SttEngine::shrink_buffers`]
+/// (3s of 16 kHz audio)
+const RETAINED_BUFFER_SAMPLES: usize = 48000;
+/// RMS (i16 scale) below which [`SttEngine::set_trim_silence`] treats audio as silence
+const TRIM_SILENCE_RMS: f32 = 100.0;
+/// Audio kept on either side of the speech when trimming, so soft word edges survive
+const TRIM_GUARD_MS: usize = 100;
+
+/// Timing of a single transcription.
+///
+/// See [`SttEngine::last_transcription_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TranscriptionStats {
+    /// Length of the input audio in seconds
+    pub audio_duration_secs: f32,
+    /// Time spent in whisper inference in seconds
+    pub inference_secs: f32,
+    /// `audio_duration_secs / inference_secs` (higher is faster; 0 if nothing was transcribed)
+    pub realtime_factor: f32,
+    /// Seconds from the start of inference until whisper finalized its first segment: how
+    /// soon a UI using [`SttEngine::transcribe_with_callback`] could show text. `None` if no
+    /// segment was produced.
+    pub time_to_first_segment_secs: Option<f32>,
+}
+
+/// How long one `state.full` run took, and whether it was cut short
+#[derive(Debug, Clone, Copy, Default)]
+struct Timing {
+    inference_secs: f32,
+    first_segment_secs: Option<f32>,
+    cancelled: bool,
+}
+
+/// Audio handed to the engine, in the caller's sample format
+#[derive(Clone, Copy)]
+enum Pcm<'a> {
+    I16(&'a [i16]),
+    F32(&'a [f32]),
+}
+
+impl Pcm<'_> {
+    fn len(&self) -> usize {
+        match self {
+            Pcm::I16(samples) => samples.len(),
+            Pcm::F32(samples) => samples.len(),
+        }
+    }
+
+    fn slice(self, range: std::ops::Range<usize>) -> Self {
+        match self {
+            Pcm::I16(samples) => Pcm::I16(&samples[range]),
+            Pcm::F32(samples) => Pcm::F32(&samples[range]),
+        }
+    }
+
+    /// RMS on the i16 scale, as silence thresholds are given
+    fn rms(&self) -> f32 {
+        match self {
+            Pcm::I16(samples) => crate::audio::rms(samples),
+            Pcm::F32([]) => 0.0,
+            Pcm::F32(samples) => {
+                let sum_squares: f64 = samples.iter().map(|&s| (s as f64).powi(2)).sum();
+                (sum_squares / samples.len() as f64).sqrt() as f32 * 32768.0
+            }
+        }
+    }
+
+    /// Convert to whisper input in `out`, averaging interleaved frames of `channels` to mono
+    fn to_whisper_input(self, channels: u16, input_sample_rate: u32, out: &mut Vec<f32>) -> Result<()> {
+        match self {
+            Pcm::I16(samples) if channels == 1 => crate::audio::to_whisper_input(samples, input_sample_rate, out),
+            Pcm::I16(samples) => {
+                let mono = crate::audio::downmix(samples, channels, None)?;
+                crate::audio::to_whisper_input(&mono, input_sample_rate, out);
+            }
+            Pcm::F32(samples) if channels == 1 => crate::audio::to_whisper_input_f32(samples, input_sample_rate, out),
+            Pcm::F32(samples) => {
+                let channels = channels as usize;
+                if !samples.len().is_multiple_of(channels) {
+                    return Err(crate::Error::Audio(format!(
+                        "{} samples is not a whole number of {}-channel frames",
+                        samples.len(),
+                        channels
+                    )));
+                }
+                let mono: Vec<f32> = samples
+                    .chunks_exact(channels)
+                    .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                    .collect();
+                crate::audio::to_whisper_input_f32(&mono, input_sample_rate, out);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Which device the model is loaded on; see [`SttEngine::new_with_gpu`].
+///
+/// The default uses GPU 0 if a GPU backend is compiled in, like [`SttEngine::new`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuOptions {
+    /// Use a GPU backend if one is compiled in; `false` forces the CPU
+    pub use_gpu: bool,
+    /// Index of the GPU to load the model on (CUDA/Vulkan/SYCL device order; ignored on the CPU)
+    pub device_index: u32,
+}
+
+impl Default for GpuOptions {
+    fn default() -> Self {
+        Self { use_gpu: true, device_index: 0 }
+    }
+}
+
+impl GpuOptions {
+    /// CPU only, even if a GPU is available
+    pub fn cpu() -> Self {
+        Self { use_gpu: false, device_index: 0 }
+    }
+
+    /// The GPU with index `device_index`
+    pub fn device(device_index: u32) -> Self {
+        Self { use_gpu: true, device_index }
+    }
+}
+
+/// Where an engine runs inference; see [`SttEngine::backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// CPU, because it was requested or no GPU backend is compiled in
+    Cpu,
+    /// A GPU backend ("Metal", "CUDA", "Vulkan", "SYCL", "Kompute" or "OpenCL") on device `device_index`
+    Gpu { name: &'static str, device_index: u32 },
+}
+
+impl Backend {
+    /// Backend whisper.cpp will pick for `gpu`, given the GPU backends compiled into this build
+    pub(crate) fn select(gpu: GpuOptions) -> Self {
+        if !gpu.use_gpu {
+            return Backend::Cpu;
+        }
+        use whisper_rs::whisper_rs_sys as sys;
+        // SAFETY: these only read compile-time flags
+        let compiled: [(bool, &'static str); 6] = unsafe {
+            [
+                (sys::ggml_cpu_has_metal() != 0, "Metal"),
+                (sys::ggml_cpu_has_cuda() != 0, "CUDA"),
+                (sys::ggml_cpu_has_vulkan() != 0, "Vulkan"),
+                (sys::ggml_cpu_has_sycl() != 0, "SYCL"),
+                (sys::ggml_cpu_has_kompute() != 0, "Kompute"),
+                (sys::ggml_cpu_has_clblast() != 0, "OpenCL"),
+            ]
+        };
+        match compiled.iter().find(|(has, _)| *has) {
+            Some(&(_, name)) => Backend::Gpu { name, device_index: gpu.device_index },
+            None => Backend::Cpu,
+        }
+    }
+}
+
+/// Properties of the loaded whisper model.
+///
+/// See [`SttEngine::model_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelInfo {
+    /// Model size as reported by whisper.cpp (e.g. "small", "large")
+    pub model_type: String,
+    /// Whether the model supports languages other than English
+    pub is_multilingual: bool,
+    /// Vocabulary size
+    pub n_vocab: usize,
+    /// Text context size in tokens; prompts set with [`SttEngine::set_prompt`] are limited
+    /// to half of this
+    pub n_text_ctx: usize,
+    /// Audio context size
+    pub n_audio_ctx: usize,
+}
+
+/// Raw and post-processed text of a transcription.
+///
+/// See [`SttEngine::transcribe_detailed`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    /// Verbatim whisper output
+    pub raw: String,
+    /// Output of the engine's [`TranscriptProcessor`] and disfluency removal, if enabled
+    /// (equal to `raw` by default)
+    pub processed: String,
+    /// Timing of this transcription
+    pub stats: TranscriptionStats,
+}
+
+impl Transcript {
+    /// Whether whisper heard no speech in the clip (as opposed to the clip being too short,
+    /// which is reported as [`Error::AudioTooShort`](crate::Error::AudioTooShort)).
+    pub fn is_silent(&self) -> bool {
+        self.raw.trim().is_empty()
+    }
+}
+
+/// How a [`SttEngine::transcribe_cancellable`] call ended, with the processed text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscribeOutcome {
+    /// The whole clip was transcribed
+    Completed(String),
+    /// The token was cancelled first; the text of the segments finished by then
+    Cancelled(String),
+}
+
+impl TranscribeOutcome {
+    /// The text, complete or not
+    pub fn text(&self) -> &str {
+        match self {
+            TranscribeOutcome::Completed(text) | TranscribeOutcome::Cancelled(text) => text,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, TranscribeOutcome::Cancelled(_))
+    }
+}
+
+/// A timed piece of a transcription.
+///
+/// See [`SttEngine::transcribe_segments`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// Start time in seconds from the beginning of the clip
+    pub start_secs: f32,
+    /// End time in seconds from the beginning of the clip
+    pub end_secs: f32,
+    /// Verbatim whisper output for this segment (trimmed, never empty)
+    pub text: String,
+}
+
+impl Segment {
+    /// [`start_secs`](Self::start_secs) in milliseconds (whisper's timestamps have 10ms
+    /// resolution), e.g. for subtitle formats
+    pub fn start_ms(&self) -> u64 {
+        (self.start_secs * 1000.0).round() as u64
+    }
+
+    /// [`end_secs`](Self::end_secs) in milliseconds
+    pub fn end_ms(&self) -> u64 {
+        (self.end_secs * 1000.0).round() as u64
+    }
+}
+
+/// Live progress from [`SttEngine::transcribe_with_callback`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TranscriptUpdate {
+    /// Text decoded so far in the segment whisper is working on (raw, trimmed). Each partial
+    /// replaces the previous one; it may still change before the segment is finalized.
+    Partial(String),
+    /// A finalized segment; the partial text it grew from is superseded
+    Segment(Segment),
+}
+
+/// A timed word with whisper's confidence in it.
+///
+/// See [`SttEngine::transcribe_words`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    /// Start time in seconds from the beginning of the clip
+    pub start_secs: f32,
+    /// End time in seconds from the beginning of the clip
+    pub end_secs: f32,
+    /// Verbatim whisper output, including attached punctuation (trimmed)
+    pub text: String,
+    /// Lowest probability among the word's tokens (`0.0..=1.0`)
+    pub probability: f32,
+}
+
+impl Word {
+    /// [`start_secs`](Self::start_secs) in milliseconds (whisper's timestamps have 10ms
+    /// resolution)
+    pub fn start_ms(&self) -> u64 {
+        (self.start_secs * 1000.0).round() as u64
+    }
+
+    /// [`end_secs`](Self::end_secs) in milliseconds
+    pub fn end_ms(&self) -> u64 {
+        (self.end_secs * 1000.0).round() as u64
+    }
+}
+
+/// How readily whisper treats audio as speech.
+///
+/// Maps to tuned combinations of whisper's no-speech, log-probability and entropy
+/// thresholds. See [`SttEngine::set_sensitivity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Sensitivity {
+    /// Drop low-confidence output; fewer hallucinations on noise, but quiet speech may be missed
+    Conservative,
+    /// Whisper's recommended thresholds (the default)
+    #[default]
+    Balanced,
+    /// Keep low-confidence output; picks up quiet speech, but may hallucinate on noise
+    Aggressive,
+}
+
+impl Sensitivity {
+    /// `(no_speech_thold, logprob_thold, entropy_thold)`
+    fn thresholds(self) -> (f32, f32, f32) {
+        match self {
+            Sensitivity::Conservative => (0.4, -0.5, 2.2),
+            Sensitivity::Balanced => (0.6, -1.0, 2.4),
+            Sensitivity::Aggressive => (0.8, -1.5, 2.8),
+        }
+    }
+}
+
+/// Speed/accuracy trade-off for decoding. See [`SttEngine::set_preset`].
+///
+/// | Preset | Sampling | Threads | Temperature fallback |
+/// |--------|----------|---------|----------------------|
+/// | `Fast` | greedy, best of 1 | 2 | off (`temperature_inc` 0.0) |
+/// | `Balanced` | greedy, best of 1 | all cores, at most 8 | on (`temperature_inc` 0.2) |
+/// | `Accurate` | beam search, beam size 5 | all cores | on (`temperature_inc` 0.2) |
+///
+/// Temperature fallback re-decodes a segment at higher temperatures when the output looks
+/// like a failure (repetitive or low-confidence, see [`Sensitivity`]). Thread counts are
+/// capped at the number of available cores; [`SttEngine::set_n_threads`] overrides them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Preset {
+    /// Lowest latency and CPU use; fine for short dictation on a clean microphone
+    Fast,
+    /// The default
+    #[default]
+    Balanced,
+    /// Best transcript for the model, at several times the decoding time of `Balanced`
+    Accurate,
+}
+
+impl Preset {
+    /// `(sampling strategy, threads, temperature_inc)`
+    fn decoding(self) -> (SamplingStrategy, usize, f32) {
+        let cores = num_cpus::get();
+        match self {
+            Preset::Fast => (SamplingStrategy::Greedy { best_of: 1 }, cores.min(2), 0.0),
+            // For Raspberry Pi, 4-6 threads is optimal
+            Preset::Balanced => (SamplingStrategy::Greedy { best_of: 1 }, cores.min(8), 0.2),
+            Preset::Accurate => (SamplingStrategy::BeamSearch { beam_size: 5, patience: -1.0 }, cores, 0.2),
+        }
+    }
+}
+
+/// Per-call overrides for [`SttEngine::transcribe_with`]. `None` fields keep the engine's
+/// setting; nothing is stored on the engine.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscribeOptions {
+    /// Spoken language as a whisper code (`"en"`, `"de"`, `"ja"`), or `"auto"` to detect it.
+    /// English-only models accept only `"en"`.
+    pub language: Option<String>,
+    /// Prompt used instead of the engine's [`set_prompt`](SttEngine::set_prompt);
+    /// `Some(String::new())` for no prompt
+    pub prompt: Option<String>,
+    /// Translate the speech into English instead of transcribing it (multilingual models)
+    pub translate: Option<bool>,
+    /// Sampling strategy, threads and temperature fallback
+    pub preset: Option<Preset>,
+}
+
+/// Groups of tokens that can be resolved with [`SttEngine::token_ids`] and passed to
+/// [`SttEngine::set_suppress_tokens`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenCategory {
+    /// Tokens containing an ASCII digit ("5", " 20", "3rd"); suppress to get spelled-out numbers
+    Digits,
+    /// Number words (" twenty", "five"); suppress to get numerals
+    NumberWords,
+    /// Tokens made up only of punctuation
+    Punctuation,
+}
+
+/// Number words matched by [`TokenCategory::NumberWords`]
+const NUMBER_WORDS: &[&str] = &[
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen",
+    "nineteen", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+    "hundred", "thousand", "million", "billion",
+];
+
+impl TokenCategory {
+    fn matches(self, text: &str) -> bool {
+        let word = text.trim();
+        match self {
+            TokenCategory::Digits => word.chars().any(|c| c.is_ascii_digit()),
+            TokenCategory::NumberWords => NUMBER_WORDS.contains(&word.to_lowercase().as_str()),
+            TokenCategory::Punctuation => !word.is_empty() && word.chars().all(|c| c.is_ascii_punctuation()),
+        }
+    }
+}
+
 /// Speech-to-text engine optimized for speed and ease of use.
 ///
 /// This is the main entry point for transcription. Create an engine, warm it up,
@@ -35,10 +452,281 @@ use num_cpus;
 /// - Subsequent transcriptions: ~200-500ms
 /// - GPU acceleration is automatic on supported platforms
 pub struct SttEngine {
-    state: Arc<Mutex<WhisperState>>,
-    initial_prompt: Option<String>, // Cache prompt, recreate params each time
+    model: Arc<ModelSlot>,
+    model_info: ModelInfo,
+    backend: Backend,
     input_sample_rate: u32,
+    channels: u16, // Interleaved channels per frame of input
     f32_buffer: Vec<f32>, // Reusable buffer
+    decode: DecodeSettings,
+    post: Postprocessing,
+    silence_threshold: Option<f32>, // None = always run the model
+    trim_silence: bool,
+    last_stats: Option<TranscriptionStats>,
+}
+
+/// The whisper model, which [`SttEngine::unload`] drops and the next use loads again.
+///
+/// Shared with `transcribe_async` tasks and the idle-unload thread; the lock also
+/// serializes inference, as whisper runs one transcription per state at a time.
+struct ModelSlot {
+    path: PathBuf,
+    gpu: GpuOptions,
+    loaded: Mutex<Option<LoadedModel>>,
+    last_used: Mutex<Instant>,
+    idle: Mutex<IdleUnload>,
+}
+
+struct LoadedModel {
+    ctx: Arc<WhisperContext>, // Kept for tokenizing prompts; shared by pooled engines
+    state: WhisperState,
+}
+
+/// Settings of the idle-unload thread, under one lock so it can't exit while being re-armed
+struct IdleUnload {
+    timeout: Option<Duration>,
+    watching: bool,
+}
+
+impl LoadedModel {
+    fn load(path: &Path, gpu: GpuOptions) -> Result<Self> {
+        let gpu_device = c_int::try_from(gpu.device_index)
+            .map_err(|_| crate::Error::Config(format!("GPU device index {} is out of range", gpu.device_index)))?;
+        let path_str = path.to_str().ok_or_else(|| crate::Error::Model("Invalid model path".into()))?;
+        info!("Loading model {}", path.display());
+        let load_start = Instant::now();
+        
+        // Enable GPU/ACCEL auto-detection (will use CPU if no GPU/ACCEL available)
+        // This allows whisper.cpp to automatically detect and use:
+        // - GPU backends (Metal, CUDA, Vulkan, OpenCL)
+        // - ACCEL backends (like Hailo AI Hat on Raspberry Pi)
+        let mut params = WhisperContextParameters::default();
+        params.use_gpu = gpu.use_gpu; // Enable GPU/ACCEL auto-detection unless CPU-only was requested
+        params.gpu_device = gpu_device;
+        
+        let ctx = WhisperContext::new_with_params(path_str, params)
+            .map_err(|e| crate::Error::Model(format!("Failed to load model {}: {}", path.display(), e)))?;
+        
+        let state = ctx.create_state()
+            .map_err(|e| crate::Error::Model(format!("Failed to create state: {}", e)))?;
+        debug!("Model loaded in {:.2}s on {:?}", load_start.elapsed().as_secs_f32(), Backend::select(gpu));
+        Ok(Self { ctx: Arc::new(ctx), state })
+    }
+}
+
+impl ModelSlot {
+    fn new(path: PathBuf, gpu: GpuOptions, loaded: LoadedModel) -> Self {
+        Self {
+            path,
+            gpu,
+            loaded: Mutex::new(Some(loaded)),
+            last_used: Mutex::new(Instant::now()),
+            idle: Mutex::new(IdleUnload { timeout: None, watching: false }),
+        }
+    }
+
+    /// Run `f` on the model, loading it first if it was unloaded
+    fn with<T>(&self, f: impl FnOnce(&mut LoadedModel) -> Result<T>) -> Result<T> {
+        let mut loaded = self.loaded.lock().map_err(|e| crate::Error::Inference(format!("State lock failed: {}", e)))?;
+        if loaded.is_none() {
+            *loaded = Some(LoadedModel::load(&self.path, self.gpu)?);
+        }
+        let result = loaded.as_mut().map(f).expect("model was just loaded");
+        self.touch();
+        result
+    }
+
+    fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// Run `f` on the model if it is loaded, without loading it
+    fn if_loaded<T>(&self, f: impl FnOnce(&LoadedModel) -> T) -> Option<T> {
+        self.loaded.lock().ok()?.as_ref().map(f)
+    }
+
+    fn is_loaded(&self) -> bool {
+        self.loaded.lock().unwrap_or_else(|e| e.into_inner()).is_some()
+    }
+
+    /// Drop the model; waits for a running transcription to finish
+    fn unload(&self) {
+        let mut loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+        if loaded.take().is_some() {
+            info!("Unloaded model {}", self.path.display());
+        }
+        // The model is gone, so a panic that poisoned the lock left nothing behind
+        drop(loaded);
+        self.loaded.clear_poison();
+    }
+
+    /// Replace the decoder state with a fresh one (nothing to do while unloaded)
+    fn reset_state(&self) -> Result<()> {
+        // A panic during a previous transcription poisons the lock; the new state is clean
+        let mut loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(model) = loaded.as_mut() {
+            model.state = model.ctx.create_state()
+                .map_err(|e| crate::Error::Model(format!("Failed to create state: {}", e)))?;
+        }
+        drop(loaded);
+        self.loaded.clear_poison();
+        Ok(())
+    }
+
+    fn set_idle_timeout(self: &Arc<Self>, timeout: Option<Duration>) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        idle.timeout = timeout;
+        if timeout.is_some() && !idle.watching {
+            idle.watching = true;
+            let slot = Arc::downgrade(self);
+            std::thread::Builder::new()
+                .name("memo-stt-idle-unload".into())
+                .spawn(move || watch_idle(slot))
+                .expect("failed to spawn idle-unload thread");
+        }
+    }
+}
+
+/// Unload the model once it has been idle for the configured timeout. Exits when the
+/// timeout is cleared or the engine is dropped.
+fn watch_idle(slot: Weak<ModelSlot>) {
+    loop {
+        let Some(slot) = slot.upgrade() else { return };
+        let timeout = {
+            let mut idle = slot.idle.lock().unwrap_or_else(|e| e.into_inner());
+            match idle.timeout {
+                Some(timeout) => timeout,
+                None => {
+                    idle.watching = false;
+                    return;
+                }
+            }
+        };
+        let idle_for = slot.last_used.lock().unwrap_or_else(|e| e.into_inner()).elapsed();
+        if idle_for >= timeout {
+            // A held lock means a transcription is running, which isn't idle
+            if let Ok(mut loaded) = slot.loaded.try_lock() {
+                if loaded.take().is_some() {
+                    info!("Unloaded model after {:.0}s idle", idle_for.as_secs_f32());
+                }
+            }
+        }
+        let wait = timeout.saturating_sub(idle_for).clamp(Duration::from_millis(10), IDLE_POLL);
+        drop(slot);
+        std::thread::sleep(wait);
+    }
+}
+
+/// Everything the whisper params are built from, owned so a copy can move to another thread
+#[derive(Clone)]
+struct DecodeSettings {
+    initial_prompt: Option<String>, // Cache prompt, recreate params each time
+    prompt_tokens: Option<Vec<WhisperToken>>, // Tokenized prompt, truncated to the context budget
+    suppress_tokens: Vec<WhisperToken>, // Logits forced to -inf during decoding
+    max_segment_len: usize, // 0 = unlimited
+    audio_ctx: usize, // Encoder frames, 0 = the model's full context
+    auto_audio_ctx: bool, // Size audio_ctx to each clip instead
+    sensitivity: Sensitivity,
+    preset: Preset,
+    n_threads: Option<usize>, // None = the preset's
+    language: Option<&'static str>, // None = auto-detect (multilingual models)
+    translate: bool,
+    logits_filter: Option<Arc<LogitsFilter>>,
+    progress: Option<Arc<Mutex<Box<InferenceProgress>>>>, // Shared by clones, called one at a time
+    sampling: Option<(SamplingStrategy, f32)>, // Strategy and temperature overriding the preset (n-best)
+    cancel: Option<CancelToken>, // Aborts inference when cancelled
+}
+
+/// Callback for [`SttEngine::set_logits_filter`]: the token IDs decoded so far in the
+/// current segment, and the logits for the next token (one per vocabulary entry)
+pub type LogitsFilter = dyn Fn(&[WhisperToken], &mut [f32]) + Send + Sync;
+
+/// Callback for [`SttEngine::set_progress_callback`]: inference progress in percent (0-100)
+pub type InferenceProgress = dyn FnMut(i32) + Send;
+
+/// Cleanup turning the raw transcript into the processed text
+#[derive(Clone)]
+struct Postprocessing {
+    processor: Arc<dyn TranscriptProcessor>,
+    remove_disfluencies: bool,
+    filler_words: Vec<String>,
+    locale: Option<Locale>,
+    output_style: OutputStyle,
+}
+
+impl Postprocessing {
+    fn apply(&self, raw: &str) -> String {
+        let mut processed = self.processor.process(raw);
+        if self.remove_disfluencies {
+            processed = postprocess::remove_disfluencies_with(&processed, &self.filler_words);
+        }
+        if let Some(locale) = self.locale {
+            processed = locale.apply(&processed);
+        }
+        self.output_style.apply(&processed)
+    }
+}
+
+impl DecodeSettings {
+    /// Encoder context for `n_input` samples: the fixed setting, or with auto-sizing enough
+    /// for the clip plus a margin (the full context, 0, once that reaches 30 seconds)
+    fn audio_ctx(&self, n_input: usize) -> usize {
+        if !self.auto_audio_ctx {
+            return self.audio_ctx;
+        }
+        let frames = (n_input * AUDIO_CTX_PER_SEC).div_ceil(crate::audio::WHISPER_SAMPLE_RATE as usize) + AUTO_AUDIO_CTX_MARGIN;
+        if frames >= 30 * AUDIO_CTX_PER_SEC { 0 } else { frames }
+    }
+
+    /// Threads whisper decodes with: [`SttEngine::set_n_threads`], else the preset's
+    fn n_threads(&self) -> usize {
+        self.n_threads.unwrap_or_else(|| self.preset.decoding().1)
+    }
+
+    /// Params for decoding `n_input` samples of 16 kHz audio
+    fn params(&self, word_timestamps: bool, n_input: usize) -> FullParams<'_, '_> {
+        // Create params (reuse configuration pattern)
+        let (strategy, _, temperature_inc) = self.preset.decoding();
+        let n_threads = self.n_threads();
+        let (strategy, temperature, temperature_inc) = match &self.sampling {
+            Some((strategy, temperature)) => (strategy.clone(), *temperature, 0.0),
+            None => (strategy, 0.0, temperature_inc),
+        };
+        let mut params = FullParams::new(strategy);
+        // Thread count is set per-transcription
+        params.set_n_threads(n_threads as i32);
+        params.set_translate(self.translate);
+        params.set_language(self.language);
+        params.set_print_progress(false);
+        params.set_print_special(false);
+        params.set_print_realtime(false);
+        params.set_print_timestamps(false);
+        params.set_suppress_blank(true);
+        params.set_suppress_non_speech_tokens(true);
+        params.set_max_len(self.max_segment_len as i32);
+        // whisper.cpp only splits segments at max_len using token timestamps
+        params.set_token_timestamps(self.max_segment_len > 0 || word_timestamps);
+        params.set_split_on_word(self.max_segment_len > 0);
+        params.set_speed_up(false);
+        params.set_audio_ctx(self.audio_ctx(n_input) as i32);
+        params.set_temperature(temperature);
+        params.set_max_initial_ts(1.0);
+        params.set_length_penalty(-1.0);
+        params.set_temperature_inc(temperature_inc);
+        let (no_speech_thold, logprob_thold, entropy_thold) = self.sensitivity.thresholds();
+        params.set_entropy_thold(entropy_thold);
+        params.set_logprob_thold(logprob_thold);
+        params.set_no_speech_thold(no_speech_thold);
+        if let Some(ref tokens) = self.prompt_tokens {
+            params.set_tokens(tokens);
+        } else if let Some(ref prompt) = self.initial_prompt {
+            if !prompt.trim().is_empty() {
+                params.set_initial_prompt(prompt);
+            }
+        }
+
+        params
+    }
 }
 
 impl SttEngine {
@@ -60,7 +748,7 @@ impl SttEngine {
     /// ```
     pub fn new_default(input_sample_rate: u32) -> Result<Self> {
         // Ensure default model is available (downloads if needed)
-        let model_path = crate::ensure_model(crate::default_model_path())?;
+        let model_path = crate::ensure_model_kind(crate::ModelKind::default())?;
         Self::new(model_path, input_sample_rate)
     }
 
@@ -92,32 +780,119 @@ impl SttEngine {
     /// - `ggml-small.en-q5_1.bin` (~500MB) - Best balance of speed and accuracy
     /// - `ggml-distil-large-v3-q5_1.bin` (~500MB) - Higher accuracy
     /// - `ggml-distil-large-v3-q8_0.bin` (~800MB) - Highest accuracy
+    /// - `ggml-large-v3-turbo.bin` (~1.6GB) - Multilingual
     ///
-    /// Models are downloaded from: https://huggingface.co/ggerganov/whisper.cpp
+    /// These and the other [`KNOWN_MODELS`](crate::model::KNOWN_MODELS) are downloaded into
+    /// the cache directory on first use when passed by file name.
+    ///
+    /// English-only (`.en`) models always transcribe as English. Multilingual models
+    /// auto-detect the spoken language (see [`ModelInfo::is_multilingual`]).
     pub fn new(model_path: impl AsRef<Path>, input_sample_rate: u32) -> Result<Self> {
-        // Ensure model exists (may download if it's the default model)
-        let path = crate::ensure_model(model_path)?;
+        Self::load(model_path, input_sample_rate, GpuOptions::default(), true)
+    }
 
-        let path_str = path.to_str().ok_or_else(|| crate::Error("Invalid model path".into()))?;
-        
-        // Enable GPU/ACCEL auto-detection (will use CPU if no GPU/ACCEL available)
-        // This allows whisper.cpp to automatically detect and use:
-        // - GPU backends (Metal, CUDA, Vulkan, OpenCL)
-        // - ACCEL backends (like Hailo AI Hat on Raspberry Pi)
-        let mut params = WhisperContextParameters::default();
-        params.use_gpu = true; // Enable GPU/ACCEL auto-detection
-        
-        let ctx = WhisperContext::new_with_params(path_str, params)
-            .map_err(|e| crate::Error(format!("Failed to load model: {}", e)))?;
-        
-        let state = ctx.create_state()
-            .map_err(|e| crate::Error(format!("Failed to create state: {}", e)))?;
+    /// Create a new engine from a model that must already be on disk, never downloading it.
+    ///
+    /// A missing model fails at once with [`Error::ModelNotFound`](crate::Error::ModelNotFound)
+    /// instead of trying the network. Same as the builder with
+    /// [`allow_download(false)`](SttEngineBuilder::allow_download); to disable downloads
+    /// process-wide see [`set_offline`](crate::set_offline).
+    pub fn new_offline(model_path: impl AsRef<Path>, input_sample_rate: u32) -> Result<Self> {
+        Self::load(model_path, input_sample_rate, GpuOptions::default(), false)
+    }
+
+    /// Create a new engine that runs on the CPU only, even if a GPU is available.
+    ///
+    /// Useful for reproducible timings (benchmarks, CI) and machines where the GPU
+    /// backend misbehaves. Otherwise identical to [`new`](Self::new).
+    pub fn new_cpu(model_path: impl AsRef<Path>, input_sample_rate: u32) -> Result<Self> {
+        Self::load(model_path, input_sample_rate, GpuOptions::cpu(), true)
+    }
+
+    /// Create a new engine on a specific GPU, or on the CPU with [`GpuOptions::cpu`].
+    ///
+    /// [`new`](Self::new) always uses GPU 0; on machines with several GPUs this pins the
+    /// model to another one. Check where it ended up with [`backend`](Self::backend).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if `device_index` doesn't fit a C `int`,
+    /// and [`Error::Model`](crate::Error::Model) if whisper.cpp can't load the model on the device.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::{GpuOptions, SttEngine};
+    ///
+    /// let engine = SttEngine::new_with_gpu("ggml-small.en-q5_1.bin", 16000, GpuOptions::device(1))?;
+    /// println!("Running on {:?}", engine.backend());
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn new_with_gpu(model_path: impl AsRef<Path>, input_sample_rate: u32, gpu: GpuOptions) -> Result<Self> {
+        Self::load(model_path, input_sample_rate, gpu, true)
+    }
+
+    /// Start configuring an engine; see [`SttEngineBuilder`].
+    pub fn builder() -> SttEngineBuilder {
+        SttEngineBuilder::default()
+    }
+
+    fn load(model_path: impl AsRef<Path>, input_sample_rate: u32, gpu: GpuOptions, allow_download: bool) -> Result<Self> {
+        // Ensure model exists and isn't truncated (may download if it's the default model)
+        let path = crate::model::resolve_valid_model(model_path, allow_download)?;
+        let loaded = LoadedModel::load(&path, gpu)?;
+        let ctx = &loaded.ctx;
+
+        let model_info = ModelInfo {
+            model_type: ctx.model_type_readable().unwrap_or_default(),
+            is_multilingual: ctx.is_multilingual(),
+            n_vocab: ctx.n_vocab().max(0) as usize,
+            n_text_ctx: ctx.n_text_ctx().max(0) as usize,
+            n_audio_ctx: ctx.n_audio_ctx().max(0) as usize,
+        };
+        debug!("Model info: {:?}", model_info);
+        // English-only models must be told "en"; multilingual ones detect the language
+        let language = if model_info.is_multilingual {
+            info!("Multilingual model loaded, spoken language will be auto-detected");
+            None
+        } else {
+            Some("en")
+        };
 
         Ok(Self {
-            state: Arc::new(Mutex::new(state)),
-            initial_prompt: None,
+            model: Arc::new(ModelSlot::new(path, gpu, loaded)),
+            model_info,
+            backend: Backend::select(gpu),
             input_sample_rate,
-            f32_buffer: Vec::with_capacity(48000), // Pre-allocate for common sizes
+            channels: 1,
+            f32_buffer: Vec::with_capacity(RETAINED_BUFFER_SAMPLES), // Pre-allocate for common sizes
+            decode: DecodeSettings {
+                initial_prompt: None,
+                prompt_tokens: None,
+                suppress_tokens: Vec::new(),
+                max_segment_len: 0,
+                audio_ctx: 0,
+                auto_audio_ctx: false,
+                sensitivity: Sensitivity::default(),
+                preset: Preset::default(),
+                n_threads: None,
+                language,
+                translate: false,
+                logits_filter: None,
+                progress: None,
+                sampling: None,
+                cancel: None,
+            },
+            post: Postprocessing {
+                processor: Arc::new(NoopProcessor),
+                remove_disfluencies: false,
+                filler_words: postprocess::DEFAULT_DISFLUENCIES.iter().map(|w| w.to_string()).collect(),
+                locale: None,
+                output_style: OutputStyle::default(),
+            },
+            silence_threshold: None,
+            trim_silence: false,
+            last_stats: None,
         })
     }
 
@@ -131,7 +906,14 @@ impl SttEngine {
     ///
     /// # Returns
     ///
-    /// Transcribed text as a `String`. Returns empty string if no speech detected.
+    /// Transcribed text as a `String`, after the engine's [`TranscriptProcessor`]
+    /// (see [`set_processor`](Self::set_processor)). Returns an empty string if the clip is
+    /// long enough but contains no speech.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AudioTooShort`](crate::Error::AudioTooShort) (with the sample count
+    /// received) for clips, including empty ones, shorter than the 1 second minimum.
     ///
     /// # Example
     ///
@@ -155,95 +937,1340 @@ impl SttEngine {
     /// - Format: 16-bit signed integer PCM (`i16`)
     /// - Channels: Mono
     /// - Sample rate: Must match the `input_sample_rate` provided to `new()` or `new_default()`
-    /// - Minimum length: 1 second (16000 samples at 16kHz)
+    /// - Minimum length: 1 second (`input_sample_rate` samples, e.g. 16000 at 16kHz)
     pub fn transcribe(&mut self, samples: &[i16]) -> Result<String> {
-        if samples.is_empty() {
-            return Ok(String::new());
-        }
-
-        // Normalize and resample inline
-        self.f32_buffer.clear();
-        if self.input_sample_rate == 16000 {
-            // Direct normalization, no resampling
-            self.f32_buffer.reserve(samples.len());
-            for &s in samples {
-                self.f32_buffer.push(s as f32 / 32768.0);
-            }
-        } else {
-            // Resample directly without intermediate Vec
-            let ratio = self.input_sample_rate as f32 / 16000.0;
-            let out_len = (samples.len() as f32 / ratio).max(1.0) as usize;
-            self.f32_buffer.reserve(out_len);
-            for i in 0..out_len {
-                let pos = i as f32 * ratio;
-                let i0 = pos.floor() as usize;
-                let i1 = (i0 + 1).min(samples.len().saturating_sub(1));
-                let t = pos - i0 as f32;
-                let s0 = samples[i0] as f32 / 32768.0;
-                let s1 = samples[i1] as f32 / 32768.0;
-                self.f32_buffer.push(s0 * (1.0 - t) + s1 * t);
-            }
-        }
+        Ok(self.transcribe_detailed(samples)?.processed)
+    }
 
-        if self.f32_buffer.len() < 16000 {
-            return Err(crate::Error(format!("Audio too short: {} samples", self.f32_buffer.len())));
-        }
+    /// [`transcribe`](Self::transcribe) for `f32` samples in `[-1.0, 1.0]`, as cpal and most
+    /// decoders produce them, without a round trip through `i16`.
+    ///
+    /// Samples are mono at the engine's `input_sample_rate` and still resampled when that
+    /// isn't 16kHz. The minimum length, silence threshold and post-processing are the same
+    /// as for `i16` input.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(48000)?;
+    /// let samples: Vec<f32> = vec![]; // e.g. from a cpal F32 input stream
+    /// let text = engine.transcribe_f32(&samples)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_f32(&mut self, samples: &[f32]) -> Result<String> {
+        let raw = self.transcribe_raw(Pcm::F32(samples))?;
+        Ok(self.post.apply(&raw))
+    }
 
-        // Create params (reuse configuration pattern)
-        let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        // Use all available CPU cores for transcription (thread count is set per-transcription)
-        // For Raspberry Pi, 4-6 threads is optimal
-        params.set_n_threads(num_cpus::get().min(8) as i32);
-        params.set_translate(false);
-        params.set_language(Some("en"));
-        params.set_print_progress(false);
-        params.set_print_special(false);
-        params.set_print_realtime(false);
-        params.set_print_timestamps(false);
-        params.set_suppress_blank(true);
-        params.set_suppress_non_speech_tokens(true);
-        params.set_max_len(0);
-        params.set_token_timestamps(false);
-        params.set_speed_up(false);
-        params.set_audio_ctx(0);
-        params.set_temperature(0.0);
-        params.set_max_initial_ts(1.0);
-        params.set_length_penalty(-1.0);
-        params.set_temperature_inc(0.2);
-        params.set_entropy_thold(2.4);
-        params.set_logprob_thold(-1.0);
-        params.set_no_speech_thold(0.6);
-        if let Some(ref prompt) = self.initial_prompt {
-            if !prompt.trim().is_empty() {
-                params.set_initial_prompt(prompt);
+    /// [`transcribe`](Self::transcribe) with some settings overridden for this call only.
+    ///
+    /// For an engine shared between requests that need different languages or prompts:
+    /// nothing is written back, so there is no window in which another caller sees the
+    /// other request's settings and no need to restore them afterwards.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) for an unknown language, a language
+    /// other than `"en"` or translation on an English-only model, and otherwise the same
+    /// errors as [`transcribe`](Self::transcribe).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::{SttEngine, TranscribeOptions};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// let options = TranscribeOptions {
+    ///     prompt: Some("Kubernetes, kubectl, Helm".to_string()),
+    ///     ..TranscribeOptions::default()
+    /// };
+    /// let text = engine.transcribe_with(&samples, &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_with(&mut self, samples: &[i16], options: &TranscribeOptions) -> Result<String> {
+        let decode = self.decode_with(options)?;
+        let defaults = std::mem::replace(&mut self.decode, decode);
+        let result = self.transcribe(samples);
+        self.decode = defaults;
+        result
+    }
+
+    /// The engine's decode settings with `options` applied
+    fn decode_with(&self, options: &TranscribeOptions) -> Result<DecodeSettings> {
+        let mut decode = self.decode.clone();
+        if let Some(language) = options.language.as_deref() {
+            decode.language = self.resolve_language(language)?;
+        }
+        if let Some(prompt) = &options.prompt {
+            decode.prompt_tokens = Some(prompt.as_str())
+                .filter(|p| !p.trim().is_empty())
+                .and_then(|p| self.tokenize_prompt(p));
+            decode.initial_prompt = Some(prompt.clone());
+        }
+        if let Some(translate) = options.translate {
+            if translate && !self.model_info.is_multilingual {
+                return Err(crate::Error::Config("Translation needs a multilingual model".into()));
+            }
+            decode.translate = translate;
+        }
+        if let Some(preset) = options.preset {
+            decode.preset = preset;
+        }
+        Ok(decode)
+    }
+
+    /// Whisper's static code for `language`, or `None` for `"auto"`
+    fn resolve_language(&self, language: &str) -> Result<Option<&'static str>> {
+        let multilingual = self.model_info.is_multilingual;
+        if language.eq_ignore_ascii_case("auto") {
+            if !multilingual {
+                return Err(crate::Error::Config("English-only models can't detect the language".into()));
+            }
+            return Ok(None);
+        }
+        let code = whisper_rs::get_lang_id(&language.to_lowercase())
+            .and_then(whisper_rs::get_lang_str)
+            .ok_or_else(|| crate::Error::Config(format!("Unknown language: {:?}", language)))?;
+        if !multilingual && code != "en" {
+            return Err(crate::Error::Config(format!("English-only model can't transcribe {:?}", language)));
+        }
+        Ok(Some(code))
+    }
+
+    /// Transcribe on tokio's blocking pool without tying up the async runtime.
+    ///
+    /// Equivalent to [`transcribe`](Self::transcribe) (same settings, processor and errors),
+    /// but takes `&self`: the samples and current settings are copied, and inference runs
+    /// via `spawn_blocking` on the engine's shared whisper state. Concurrent calls queue on
+    /// that state and run one at a time. Does not update
+    /// [`last_transcription_stats`](Self::last_transcription_stats).
+    ///
+    /// Requires the `async` feature and a tokio runtime.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = SttEngine::new_default(16000)?;
+    ///
+    /// let samples: Vec<i16> = vec![]; // e.g. decoded from the BLE receiver
+    /// let text = engine.transcribe_async(&samples).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn transcribe_async(
+        &self,
+        samples: &[i16],
+    ) -> impl std::future::Future<Output = Result<String>> + Send + 'static {
+        let model = Arc::clone(&self.model);
+        let decode = self.decode.clone();
+        let post = self.post.clone();
+        let input_sample_rate = self.input_sample_rate;
+        let channels = self.channels;
+        // Silence gate and trimming as in `transcribe`, before copying the samples
+        let samples = self.speech_range(Pcm::I16(samples)).map(|speech| samples[speech].to_vec());
+        async move {
+            let Some(samples) = samples else { return Ok(String::new()) };
+            tokio::task::spawn_blocking(move || {
+                let min_samples = input_sample_rate as usize;
+                let frames = samples.len() / channels as usize;
+                if frames < min_samples {
+                    return Err(crate::Error::AudioTooShort { samples: frames, min_samples });
+                }
+                let mut input = Vec::new();
+                Pcm::I16(&samples).to_whisper_input(channels, input_sample_rate, &mut input)?;
+                let (segments, _) = run_full(&model, &decode, &input, false, None, read_segments)?;
+                Ok(post.apply(&join_segments(&segments)))
+            })
+            .await
+            .map_err(|e| crate::Error::Inference(format!("Transcription task failed: {}", e)))?
+        }
+    }
+
+    /// The exact 16 kHz mono `f32` buffer whisper would see for `samples`, without running
+    /// inference.
+    ///
+    /// Resampling uses the engine's `input_sample_rate`; see
+    /// [`audio::to_whisper_input`](crate::audio::to_whisper_input) to reuse an allocation.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = SttEngine::new_default(48000)?;
+    /// let buffer = engine.preprocess(&vec![0i16; 48000]);
+    /// assert_eq!(buffer.len(), 16000);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn preprocess(&self, samples: &[i16]) -> Vec<f32> {
+        let mut out = Vec::new();
+        if Pcm::I16(samples).to_whisper_input(self.channels, self.input_sample_rate, &mut out).is_err() {
+            out.clear(); // Not a whole number of frames
+        }
+        out
+    }
+
+    /// Transcribe the next utterance from `source`, resampled to the engine's input rate.
+    /// Returns `Ok(None)` once the source is exhausted.
+    ///
+    /// See [`source`](crate::source) for an example.
+    pub fn transcribe_source(&mut self, source: &mut dyn crate::source::AudioSource) -> Result<Option<String>> {
+        let Some(samples) = source.next_utterance()? else {
+            return Ok(None);
+        };
+        let samples = match source.sample_rate() {
+            rate if rate == self.input_sample_rate => samples,
+            rate => crate::audio::resample_i16(&samples, rate, self.input_sample_rate),
+        };
+        self.with_mono(|engine| engine.transcribe(&samples)).map(Some)
+    }
+
+    /// Transcribe interleaved multi-channel samples, downmixed to mono with
+    /// [`audio::downmix`](crate::audio::downmix).
+    ///
+    /// `weights` sets each channel's share, for capture setups where the channels differ
+    /// (e.g. a headset with the voice mic on one channel and noise on the other); `None`
+    /// averages them.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let stereo: Vec<i16> = vec![]; // L/R interleaved, voice on the left
+    /// let text = engine.transcribe_interleaved(&stereo, 2, Some(&[0.7, 0.3]))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_interleaved(&mut self, samples: &[i16], channels: u16, weights: Option<&[f32]>) -> Result<String> {
+        let mono = crate::audio::downmix(samples, channels, weights)?;
+        self.with_mono(|engine| engine.transcribe(&mono))
+    }
+
+    /// Transcribe raw interleaved PCM bytes (e.g. from a socket or FFI buffer).
+    ///
+    /// The bytes are decoded according to `format` and downmixed to mono; the sample rate
+    /// must match the engine's `input_sample_rate`. Fails if the byte length is not a whole
+    /// number of frames.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    /// use memo_stt::audio::{Endianness, PcmFormat, SampleFormat};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(48000)?;
+    /// let bytes: Vec<u8> = vec![]; // f32 stereo little-endian from the network
+    /// let format = PcmFormat { sample_format: SampleFormat::F32, channels: 2, endianness: Endianness::Little };
+    /// let text = engine.transcribe_bytes(&bytes, format)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_bytes(&mut self, bytes: &[u8], format: crate::audio::PcmFormat) -> Result<String> {
+        let samples = crate::audio::decode_pcm(bytes, format)?;
+        self.with_mono(|engine| engine.transcribe(&samples))
+    }
+
+    /// Transcribe audio samples and return both the raw and the post-processed text.
+    ///
+    /// Like [`transcribe`](Self::transcribe), a clip under 1 second fails with
+    /// [`Error::AudioTooShort`](crate::Error::AudioTooShort), while a clip without speech
+    /// succeeds with [`Transcript::is_silent`] set.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    /// use memo_stt::postprocess::RuleBasedProcessor;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// engine.set_processor(Box::new(RuleBasedProcessor::default()));
+    ///
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// let transcript = engine.transcribe_detailed(&samples)?;
+    /// println!("raw: {}\ncleaned: {}", transcript.raw, transcript.processed);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_detailed(&mut self, samples: &[i16]) -> Result<Transcript> {
+        let raw = self.transcribe_raw(Pcm::I16(samples))?;
+        let processed = self.post.apply(&raw);
+        Ok(Transcript {
+            raw,
+            processed,
+            stats: self.last_stats.unwrap_or_default(),
+        })
+    }
+
+    /// Transcribe audio samples into timed segments.
+    ///
+    /// Segment text is the raw whisper output; the [`TranscriptProcessor`] is not applied.
+    /// Combine with [`set_max_segment_len`](Self::set_max_segment_len) to get
+    /// subtitle-sized chunks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// engine.set_max_segment_len(42);
+    ///
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// for seg in engine.transcribe_segments(&samples)? {
+    ///     println!("[{:.2} -> {:.2}] {}", seg.start_secs, seg.end_secs, seg.text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_segments(&mut self, samples: &[i16]) -> Result<Vec<Segment>> {
+        self.run_inference(Pcm::I16(samples), false, None, read_segments)
+    }
+
+    /// Transcribe audio samples into timed words with whisper's confidence for each.
+    ///
+    /// Word text is the raw whisper output; the [`TranscriptProcessor`] is not applied.
+    /// Punctuation stays attached to the word it follows (`"country,"`).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    ///
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// for word in engine.transcribe_words(&samples)? {
+    ///     if word.probability < 0.5 {
+    ///         println!("unsure about {:?} at {:.2}s", word.text, word.start_secs);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_words(&mut self, samples: &[i16]) -> Result<Vec<Word>> {
+        let tokens = self.run_inference(Pcm::I16(samples), true, None, read_tokens)?;
+        self.model.with(|model| Ok(words_from_tokens(&model.ctx, &tokens)))
+    }
+
+    /// Transcribe into up to `n` alternative transcripts, each with a score: the mean
+    /// log-probability of its tokens (`<= 0.0`, higher is more confident). Sorted by score,
+    /// highest first.
+    ///
+    /// For showing alternatives or reranking them yourself (e.g. against a domain language
+    /// model). The result of beam search (beam size `n`, at least 5) is always included,
+    /// and is usually at least as good as [`transcribe`](Self::transcribe) with the current
+    /// preset. whisper.cpp only keeps the winning beam, so the others come from
+    /// re-decoding at rising temperatures; identical texts are merged, so fewer than `n`
+    /// come back when whisper is sure. Expect up to `2n - 1` decodes, i.e. several times
+    /// the cost of `transcribe`.
+    ///
+    /// Texts are processed like [`transcribe`](Self::transcribe)'s.
+    /// [`last_transcription_stats`](Self::last_transcription_stats) covers all decodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if `n` is 0, and otherwise the same
+    /// errors as [`transcribe`](Self::transcribe).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// for (text, score) in engine.transcribe_nbest(&samples, 3)? {
+    ///     println!("{:6.2}  {}", score, text);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_nbest(&mut self, samples: &[i16], n: usize) -> Result<Vec<(String, f32)>> {
+        if n == 0 {
+            return Err(crate::Error::Config("n must be at least 1".into()));
+        }
+        self.prepare_input(Pcm::I16(samples))?;
+        let eot = self.model.with(|model| Ok(model.ctx.token_eot()))?;
+
+        let mut hypotheses: Vec<(String, f32)> = Vec::with_capacity(n);
+        let mut timing = Timing::default();
+        let beam = (SamplingStrategy::BeamSearch { beam_size: n.max(5) as c_int, patience: -1.0 }, 0.0);
+        let sampled = (1..2 * n).map(|i| {
+            let temperature = (0.1 + 0.1 * i as f32).min(1.0);
+            (SamplingStrategy::Greedy { best_of: 1 }, temperature)
+        });
+        for sampling in std::iter::once(beam).chain(sampled) {
+            if hypotheses.len() == n {
+                break;
+            }
+            let mut decode = self.decode.clone();
+            decode.sampling = Some(sampling);
+            let ((segments, tokens), pass) = run_full(&self.model, &decode, &self.f32_buffer, false, None, |state| {
+                Ok((read_segments(state)?, read_tokens(state)?))
+            })?;
+            timing.first_segment_secs = timing.first_segment_secs.or(pass.first_segment_secs.map(|t| timing.inference_secs + t));
+            timing.inference_secs += pass.inference_secs;
+            let text = self.post.apply(&join_segments(&segments));
+            if hypotheses.iter().all(|(seen, _)| *seen != text) {
+                hypotheses.push((text, mean_logprob(&tokens, eot)));
+            }
+        }
+        self.record_stats(self.frames(samples.len()), timing);
+
+        hypotheses.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(hypotheses)
+    }
+
+    /// Transcribe long audio with overlapping windows, keeping the more confident reading
+    /// wherever two windows disagree.
+    ///
+    /// The clip is cut into windows of up to 30 seconds (whisper's context) that overlap by
+    /// about `overlap_ratio` (`0.0..0.9`). Window edges are moved back by up to 3 seconds to
+    /// land in a pause (see [`find_silence_boundaries`](crate::audio::find_silence_boundaries))
+    /// where there is one, so fewer words are cut in half. Words in each overlap are aligned by time and text, and
+    /// the word with the higher [`Word::probability`] wins; unmatched words are kept only from
+    /// the window whose centre they are closer to, which drops words half-heard at a window
+    /// edge. The result is post-processed like [`transcribe`](Self::transcribe).
+    ///
+    /// Clips of 30 seconds or less fit in one window and are transcribed once.
+    ///
+    /// # Latency
+    ///
+    /// Every sample is decoded about `1 / (1 - overlap_ratio)` times, so inference takes
+    /// roughly that much longer than [`transcribe`](Self::transcribe): 2x at `0.5`, 4x at
+    /// `0.75`. Meant for recordings, not live dictation.
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Config`](crate::Error::Config) if `overlap_ratio` is out of range, and
+    /// [`Error::AudioTooShort`](crate::Error::AudioTooShort) like `transcribe`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    ///
+    /// let samples: Vec<i16> = vec![]; // Replace with a long recording
+    /// let text = engine.transcribe_robust(&samples, 0.5)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_robust(&mut self, samples: &[i16], overlap_ratio: f32) -> Result<String> {
+        if !(0.0..0.9).contains(&overlap_ratio) {
+            return Err(crate::Error::Config(format!(
+                "overlap_ratio must be in 0.0..0.9, got {}",
+                overlap_ratio
+            )));
+        }
+        if self.channels > 1 {
+            // Windows are planned in frames, so mix down once up front
+            let mono = crate::audio::downmix(samples, self.channels, None)?;
+            return self.with_mono(|engine| engine.transcribe_robust(&mono, overlap_ratio));
+        }
+        let rate = self.input_sample_rate as usize;
+        let window = ROBUST_WINDOW_SECS * rate;
+        if samples.len() <= window {
+            return self.transcribe(samples);
+        }
+        let hop = ((window as f32 * (1.0 - overlap_ratio)) as usize).max(rate);
+        let pauses = crate::audio::find_silence_boundaries(samples, self.input_sample_rate, ROBUST_MIN_GAP_MS);
+
+        let mut words: Vec<Word> = Vec::new();
+        let mut prev_end_secs = 0.0;
+        let mut timing = Timing::default();
+        for (start, end) in plan_windows(samples.len(), window, hop, &pauses, ROBUST_SNAP_SECS * rate, rate) {
+            let offset_secs = start as f32 / rate as f32;
+            let mut pass = self.transcribe_words(&samples[start..end])?;
+            if let Some(stats) = self.last_stats {
+                timing.first_segment_secs = timing.first_segment_secs.or(stats.time_to_first_segment_secs.map(|t| timing.inference_secs + t));
+                timing.inference_secs += stats.inference_secs;
             }
+            for word in &mut pass {
+                word.start_secs += offset_secs;
+                word.end_secs += offset_secs;
+            }
+            words = merge_overlap(words, pass, offset_secs, prev_end_secs);
+            prev_end_secs = end as f32 / rate as f32;
         }
 
-        // Lock state and run inference
-        let mut state = self.state.lock().map_err(|e| crate::Error(format!("State lock failed: {}", e)))?;
-        state.full(params, &self.f32_buffer)
-            .map_err(|e| crate::Error(format!("Inference failed: {}", e)))?;
+        self.record_stats(samples.len(), timing);
+
+        let raw = words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+        Ok(self.post.apply(&raw))
+    }
+
+    /// Transcribe long audio chunk by chunk, reporting each chunk's text as it completes.
+    ///
+    /// For recordings too long to wait for (meetings, lectures): the clip is cut into chunks
+    /// of `chunk_secs` (1 to 30 seconds) that overlap by up to 2 seconds, with edges moved
+    /// into pauses where possible. The last words of each chunk become the prompt for the
+    /// next, so names and spelling carry over, and words heard by both chunks in an overlap
+    /// are reported once. `on_segment` gets one [`Segment`] per chunk, with times from the
+    /// start of `samples` and raw whisper text; chunks without speech report nothing.
+    ///
+    /// Returns the whole text, processed like [`transcribe`](Self::transcribe).
+    ///
+    /// # Errors
+    ///
+    /// [`Error::Config`](crate::Error::Config) if `chunk_secs` is out of range, and
+    /// [`Error::AudioTooShort`](crate::Error::AudioTooShort) like `transcribe`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    ///
+    /// let meeting: Vec<i16> = vec![]; // Replace with a long recording
+    /// engine.transcribe_stream(&meeting, 20.0, |seg| {
+    ///     println!("[{:.0}s] {}", seg.start_secs, seg.text);
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_stream(
+        &mut self,
+        samples: &[i16],
+        chunk_secs: f32,
+        mut on_segment: impl FnMut(Segment),
+    ) -> Result<String> {
+        if !(1.0..=30.0).contains(&chunk_secs) {
+            return Err(crate::Error::Config(format!("chunk_secs must be 1-30, got {}", chunk_secs)));
+        }
+        if self.channels > 1 {
+            // Chunks are planned in frames, so mix down once up front
+            let mono = crate::audio::downmix(samples, self.channels, None)?;
+            return self.with_mono(|engine| engine.transcribe_stream(&mono, chunk_secs, on_segment));
+        }
+        let rate = self.input_sample_rate as usize;
+        let window = (chunk_secs * rate as f32) as usize;
+        let overlap = (STREAM_OVERLAP_SECS.min(chunk_secs / 4.0) * rate as f32) as usize;
+        let pauses = crate::audio::find_silence_boundaries(samples, self.input_sample_rate, ROBUST_MIN_GAP_MS);
+        let windows = plan_windows(samples.len(), window, window - overlap, &pauses, overlap, rate);
+
+        let user_prompt = self.decode.initial_prompt.clone().filter(|p| !p.trim().is_empty());
+        let mut raw = String::new();
+        let mut pending: Vec<Word> = Vec::new(); // Words in the overlap with the next chunk
+        let mut prev_end_secs = 0.0;
+        let mut timing = Timing::default();
+        for (i, &(start, end)) in windows.iter().enumerate() {
+            let offset_secs = start as f32 / rate as f32;
+            let options = TranscribeOptions {
+                prompt: stream_prompt(user_prompt.as_deref(), &raw, &pending),
+                ..TranscribeOptions::default()
+            };
+            let decode = self.decode_with(&options)?;
+            let defaults = std::mem::replace(&mut self.decode, decode);
+            let pass = self.transcribe_words(&samples[start..end]);
+            self.decode = defaults;
+            let mut pass = pass?;
+            if let Some(stats) = self.last_stats {
+                timing.first_segment_secs = timing.first_segment_secs.or(stats.time_to_first_segment_secs.map(|t| timing.inference_secs + t));
+                timing.inference_secs += stats.inference_secs;
+            }
+            for word in &mut pass {
+                word.start_secs += offset_secs;
+                word.end_secs += offset_secs;
+            }
+            let merged = merge_overlap(std::mem::take(&mut pending), pass, offset_secs, prev_end_secs);
+            prev_end_secs = end as f32 / rate as f32;
+
+            // Words the next chunk will hear again wait for it
+            let ready = match windows.get(i + 1) {
+                Some(&(next_start, _)) => {
+                    let next_start_secs = next_start as f32 / rate as f32;
+                    let (ready, rest) = merged.into_iter().partition(|w| (w.start_secs + w.end_secs) / 2.0 < next_start_secs);
+                    pending = rest;
+                    ready
+                }
+                None => merged,
+            };
+            if let (Some(first), Some(last)) = (ready.first(), ready.last()) {
+                let text = ready.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+                if !raw.is_empty() {
+                    raw.push(' ');
+                }
+                raw.push_str(&text);
+                on_segment(Segment { start_secs: first.start_secs, end_secs: last.end_secs, text });
+            }
+        }
+
+        self.record_stats(samples.len(), timing);
+        Ok(self.post.apply(&raw))
+    }
+
+    /// Transcribe audio samples, reporting progress while whisper decodes.
+    ///
+    /// `on_update` receives a [`TranscriptUpdate::Partial`] each time the text of the segment
+    /// in progress grows, and a [`TranscriptUpdate::Segment`] as each segment is finalized, so
+    /// a live caption can show words as they are decoded instead of in multi-second chunks.
+    /// Updates carry raw whisper text; the return value is processed like
+    /// [`transcribe`](Self::transcribe).
+    ///
+    /// The callback runs on the calling thread inside inference, so keep it cheap.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::{SttEngine, TranscriptUpdate};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    ///
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// let mut done = String::new();
+    /// let text = engine.transcribe_with_callback(&samples, |update| match update {
+    ///     TranscriptUpdate::Partial(partial) => print!("\r{}{}", done, partial),
+    ///     TranscriptUpdate::Segment(seg) => {
+    ///         done.push_str(&seg.text);
+    ///         done.push(' ');
+    ///         print!("\r{}", done);
+    ///     }
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_with_callback(
+        &mut self,
+        samples: &[i16],
+        mut on_update: impl FnMut(TranscriptUpdate),
+    ) -> Result<String> {
+        let segments = self.run_inference(Pcm::I16(samples), false, Some(&mut on_update), read_segments)?;
+        Ok(self.post.apply(&join_segments(&segments)))
+    }
+
+    /// Transcribe audio samples, stopping early if `cancel` is cancelled.
+    ///
+    /// For a UI where the user can abort a long transcription: call
+    /// [`cancel`](CancelToken::cancel) on a clone of the token from another thread and
+    /// whisper stops within a fraction of a second. The text of the segments finished by
+    /// then comes back as [`TranscribeOutcome::Cancelled`]; a run that finishes first is
+    /// [`TranscribeOutcome::Completed`]. The text is processed like
+    /// [`transcribe`](Self::transcribe).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::{CancelToken, SttEngine, TranscribeOutcome};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let cancel = CancelToken::new();
+    ///
+    /// let escape = cancel.clone(); // e.g. moved into the Escape key handler
+    /// std::thread::spawn(move || escape.cancel());
+    ///
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// match engine.transcribe_cancellable(&samples, &cancel)? {
+    ///     TranscribeOutcome::Completed(text) => println!("{}", text),
+    ///     TranscribeOutcome::Cancelled(partial) => println!("(aborted) {}", partial),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn transcribe_cancellable(&mut self, samples: &[i16], cancel: &CancelToken) -> Result<TranscribeOutcome> {
+        self.prepare_input(Pcm::I16(samples))?;
+        let mut decode = self.decode.clone();
+        decode.cancel = Some(cancel.clone());
+        let (segments, timing) = run_full(&self.model, &decode, &self.f32_buffer, false, None, read_segments)?;
+        self.record_stats(self.frames(samples.len()), timing);
+        let text = self.post.apply(&join_segments(&segments));
+        Ok(if timing.cancelled { TranscribeOutcome::Cancelled(text) } else { TranscribeOutcome::Completed(text) })
+    }
+
+    fn transcribe_raw(&mut self, samples: Pcm) -> Result<String> {
+        let Some(speech) = self.speech_range(samples) else {
+            self.record_stats(self.frames(samples.len()), Timing::default());
+            return Ok(String::new());
+        };
+        let segments = self.run_inference(samples.slice(speech), false, None, read_segments)?;
+        Ok(join_segments(&segments))
+    }
+
+    /// The part of `samples` to transcribe: `None` for a clip the silence threshold skips,
+    /// otherwise all of it, or with silent edges trimmed when `trim_silence` is on
+    fn speech_range(&self, samples: Pcm) -> Option<std::ops::Range<usize>> {
+        if let Some(threshold) = self.silence_threshold {
+            if self.frames(samples.len()) >= self.input_sample_rate as usize && samples.rms() < threshold {
+                debug!("Skipping inference: clip is below {} RMS", threshold);
+                return None;
+            }
+        }
+        Some(if self.trim_silence { self.trim_edges(samples) } else { 0..samples.len() })
+    }
+
+    /// The range of `samples` without leading and trailing silence, keeping
+    /// [`TRIM_GUARD_MS`] around the speech and at least 1 second overall. All of `samples` if
+    /// none of it is loud enough.
+    fn trim_edges(&self, samples: Pcm) -> std::ops::Range<usize> {
+        let channels = self.channels as usize;
+        let rate = self.input_sample_rate as usize;
+        // 10ms windows of whole frames
+        let window = (rate / 100).max(1) * channels;
+        let len = samples.len();
+        let n_windows = len.div_ceil(window);
+        let loud = |i: usize| samples.slice(i * window..((i + 1) * window).min(len)).rms() >= TRIM_SILENCE_RMS;
+        let Some(first) = (0..n_windows).find(|&i| loud(i)) else {
+            debug!("Not trimming: no audio above {} RMS", TRIM_SILENCE_RMS);
+            return 0..len;
+        };
+        let last = (first..n_windows).rev().find(|&i| loud(i)).unwrap_or(first);
+
+        let guard = TRIM_GUARD_MS * rate / 1000 * channels;
+        let mut start = (first * window).saturating_sub(guard);
+        let mut end = ((last + 1) * window + guard).min(len);
+        // Grow a short utterance back to whisper's minimum, at the end first
+        let min_len = rate * channels;
+        if end - start < min_len {
+            end = (start + min_len).min(len);
+            start = start.min(end.saturating_sub(min_len));
+        }
+        if start > 0 || end < len {
+            debug!("Trimmed {} leading and {} trailing samples of silence", start, len - end);
+        }
+        start..end
+    }
+
+    /// Run whisper over `samples` and read the result out of the state with `extract`.
+    fn run_inference<T>(
+        &mut self,
+        samples: Pcm,
+        word_timestamps: bool,
+        on_update: Option<&mut dyn FnMut(TranscriptUpdate)>,
+        extract: impl FnOnce(&WhisperState) -> Result<T>,
+    ) -> Result<T> {
+        self.prepare_input(samples)?;
+        let (result, timing) =
+            run_full(&self.model, &self.decode, &self.f32_buffer, word_timestamps, on_update, extract)?;
+        self.record_stats(self.frames(samples.len()), timing);
+        Ok(result)
+    }
+
+    /// Check the length of `samples` and convert them into `f32_buffer` (downmixed to mono),
+    /// reloading an unloaded model.
+    fn prepare_input(&mut self, samples: Pcm) -> Result<()> {
+        self.last_stats = None;
+        let min_samples = self.input_sample_rate as usize;
+        let frames = self.frames(samples.len());
+        if frames < min_samples {
+            return Err(crate::Error::AudioTooShort { samples: frames, min_samples });
+        }
+
+        // Normalize and resample into the reusable buffer
+        if self.input_sample_rate != crate::audio::WHISPER_SAMPLE_RATE {
+            debug!("Resampling {} Hz -> 16000 Hz (ratio {:.3})", self.input_sample_rate, self.input_sample_rate as f32 / 16000.0);
+        }
+        samples.to_whisper_input(self.channels, self.input_sample_rate, &mut self.f32_buffer)?;
+        if !self.model.is_loaded() {
+            self.reload()?;
+        }
+        Ok(())
+    }
+
+    /// Frames (samples per channel) in `n_samples` interleaved samples
+    fn frames(&self, n_samples: usize) -> usize {
+        n_samples / self.channels as usize
+    }
+
+    fn record_stats(&mut self, n_frames: usize, timing: Timing) {
+        let audio_duration_secs = n_frames as f32 / self.input_sample_rate as f32;
+        let inference_secs = timing.inference_secs;
+        self.last_stats = Some(TranscriptionStats {
+            audio_duration_secs,
+            inference_secs,
+            realtime_factor: if inference_secs > 0.0 { audio_duration_secs / inference_secs } else { 0.0 },
+            time_to_first_segment_secs: timing.first_segment_secs,
+        });
+    }
+
+    /// Set initial prompt for custom vocabulary or context.
+    ///
+    /// Useful for improving accuracy with domain-specific terms, names, or technical vocabulary.
+    ///
+    /// Whisper only attends to half of its text context as prompt
+    /// ([`ModelInfo::n_text_ctx`] / 2 tokens). Longer prompts are truncated to their most
+    /// recent (last) tokens, with a warning logged.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// engine.set_prompt(Some("Rust programming language, cargo, crates.io".to_string()));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_prompt(&mut self, prompt: Option<String>) {
+        self.decode.prompt_tokens = prompt
+            .as_deref()
+            .filter(|p| !p.trim().is_empty())
+            .and_then(|p| self.tokenize_prompt(p));
+        self.decode.initial_prompt = prompt;
+    }
+
+    /// Tokenize a prompt and keep only the last tokens that fit the model's prompt budget.
+    /// Returns `None` if tokenization fails (the raw text is passed to whisper instead) or
+    /// the model is unloaded.
+    fn tokenize_prompt(&self, prompt: &str) -> Option<Vec<WhisperToken>> {
+        // A BPE token covers at least one byte, so this buffer is always large enough
+        // While unloaded this waits for the next transcription, which reloads the model
+        let tokenized = self.model.if_loaded(|model| model.ctx.tokenize(prompt, prompt.len() + 1))?;
+        let mut tokens = match tokenized {
+            Ok(tokens) => tokens,
+            Err(e) => {
+                warn!("Failed to tokenize prompt, passing it to whisper as text: {}", e);
+                return None;
+            }
+        };
+        let budget = (self.model_info.n_text_ctx / 2).saturating_sub(1);
+        if tokens.len() > budget {
+            warn!(
+                "Prompt is {} tokens, truncating to the last {} (model text context is {})",
+                tokens.len(),
+                budget,
+                self.model_info.n_text_ctx
+            );
+            tokens.drain(..tokens.len() - budget);
+        }
+        Some(tokens)
+    }
+
+    /// Another engine with the same settings and model weights but its own decoder state,
+    /// so the two can transcribe at the same time. See [`SttEnginePool`](crate::pool::SttEnginePool).
+    pub(crate) fn sibling(&self) -> Result<SttEngine> {
+        let loaded = self.model.with(|model| {
+            let state = model.ctx.create_state()
+                .map_err(|e| crate::Error::Model(format!("Failed to create state: {}", e)))?;
+            Ok(LoadedModel { ctx: Arc::clone(&model.ctx), state })
+        })?;
+        Ok(SttEngine {
+            model: Arc::new(ModelSlot::new(self.model.path.clone(), self.model.gpu, loaded)),
+            model_info: self.model_info.clone(),
+            backend: self.backend,
+            input_sample_rate: self.input_sample_rate,
+            channels: self.channels,
+            f32_buffer: Vec::with_capacity(RETAINED_BUFFER_SAMPLES),
+            decode: self.decode.clone(),
+            post: self.post.clone(),
+            silence_threshold: self.silence_threshold,
+            trim_silence: self.trim_silence,
+            last_stats: None,
+        })
+    }
+
+    /// Sample rate the engine expects its input at, as passed to [`new`](Self::new).
+    pub fn input_sample_rate(&self) -> u32 {
+        self.input_sample_rate
+    }
+
+    /// Accept interleaved input with `channels` channels, e.g. 2 for an interface that only
+    /// records stereo. Defaults to 1 (mono).
+    ///
+    /// Frames are averaged to mono before resampling, so durations, the 1 second minimum and
+    /// [`TranscriptionStats`] count frames, not samples. Input must be a whole number of
+    /// frames. Applies to everything that takes raw samples (`transcribe`, `transcribe_f32`,
+    /// `transcribe_segments`, ...); [`transcribe_interleaved`](Self::transcribe_interleaved),
+    /// [`transcribe_bytes`](Self::transcribe_bytes) and
+    /// [`transcribe_source`](Self::transcribe_source) describe their own layout and ignore it.
+    /// A [`StreamingSession`](crate::streaming::StreamingSession) needs a mono engine.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if `channels` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(48000)?;
+    /// engine.set_channels(2)?;
+    /// let stereo: Vec<i16> = vec![]; // L/R interleaved at 48kHz
+    /// let text = engine.transcribe(&stereo)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_channels(&mut self, channels: u16) -> Result<()> {
+        if channels == 0 {
+            return Err(crate::Error::Config("Audio must have at least one channel".into()));
+        }
+        self.channels = channels;
+        Ok(())
+    }
+
+    /// Interleaved channels per frame of input (see [`set_channels`](Self::set_channels))
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Run `f` with mono input, for audio this engine has already mixed down
+    fn with_mono<T>(&mut self, f: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let channels = std::mem::replace(&mut self.channels, 1);
+        let result = f(self);
+        self.channels = channels;
+        result
+    }
+
+    /// Properties of the loaded model (context sizes, multilingual support, etc.).
+    pub fn model_info(&self) -> &ModelInfo {
+        &self.model_info
+    }
+
+    /// Device the model was loaded on.
+    ///
+    /// `Gpu` names the GPU backend compiled into this build and the requested device;
+    /// whisper.cpp logs a warning and runs on the CPU if that device fails to initialize.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    /// Suppress specific tokens so whisper never emits them (e.g. digits, to force numbers
+    /// to be spelled out). Pass an empty list to clear.
+    ///
+    /// Token IDs are model-specific. Resolve them with [`token_ids`](Self::token_ids) for
+    /// common categories, [`token_ids_matching`](Self::token_ids_matching) for anything
+    /// else, or [`tokenize`](Self::tokenize) for the tokens of a given word. Valid IDs are
+    /// `0..`[`ModelInfo::n_vocab`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if an ID is outside the vocabulary.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::{SttEngine, TokenCategory};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// // "twenty five" instead of "25"
+    /// engine.set_suppress_tokens(engine.token_ids(TokenCategory::Digits))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_suppress_tokens(&mut self, tokens: Vec<i32>) -> Result<()> {
+        let n_vocab = self.model_info.n_vocab;
+        if let Some(bad) = tokens.iter().find(|&&t| t < 0 || t as usize >= n_vocab) {
+            return Err(crate::Error::Config(format!(
+                "Token ID {} is outside the model vocabulary (0..{})",
+                bad, n_vocab
+            )));
+        }
+        debug!("Suppressing {} tokens", tokens.len());
+        self.decode.suppress_tokens = tokens;
+        Ok(())
+    }
+
+    /// Advanced: adjust the logits before every token is sampled, for biasing that a static
+    /// prompt or suppress list can't express (boosting domain terms on the fly, forbidding
+    /// tokens in some contexts, grammar-constrained decoding). `None` removes the filter.
+    ///
+    /// The filter gets the token IDs decoded so far in the current segment (including
+    /// special tokens) and one logit per vocabulary entry, after
+    /// [suppressed tokens](Self::set_suppress_tokens) are set to `-inf`. Set a logit to
+    /// `f32::NEG_INFINITY` to forbid that token.
+    ///
+    /// It runs inside inference, once per sampled token, so keep it fast, and it must not
+    /// panic: the panic can't unwind through whisper.cpp and aborts the process.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let boosted = engine.tokenize(" Kubernetes")?;
+    /// engine.set_logits_filter(Some(Box::new(move |_tokens, logits| {
+    ///     for &id in &boosted {
+    ///         logits[id as usize] += 2.0;
+    ///     }
+    /// })));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_logits_filter(&mut self, filter: Option<Box<LogitsFilter>>) {
+        self.decode.logits_filter = filter.map(Arc::from);
+    }
+
+    /// Report inference progress (0-100 percent) to `callback` while transcribing, e.g. to
+    /// drive a progress bar for long clips. `None` removes it.
+    ///
+    /// Whisper reports progress as it moves through the audio in 30 second windows, so a
+    /// short clip may only see the final 100, or nothing at all.
+    ///
+    /// The callback runs on the thread calling `transcribe`, in the middle of inference,
+    /// while the engine is still busy: it must not call back into this engine (or lock a
+    /// mutex the engine is behind, which deadlocks) and must not panic. Send the value to
+    /// the UI thread instead. It also runs during
+    /// [`transcribe_async`](Self::transcribe_async), on the blocking thread.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    /// use std::sync::mpsc;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let (tx, progress) = mpsc::channel();
+    /// engine.set_progress_callback(Some(Box::new(move |percent| {
+    ///     let _ = tx.send(percent);
+    /// })));
+    /// // The UI thread reads `progress` while `transcribe` runs
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_progress_callback(&mut self, callback: Option<Box<InferenceProgress>>) {
+        self.decode.progress = callback.map(|callback| Arc::new(Mutex::new(callback)));
+    }
+
+    /// IDs of all text tokens in a [`TokenCategory`], for use with
+    /// [`set_suppress_tokens`](Self::set_suppress_tokens).
+    pub fn token_ids(&self, category: TokenCategory) -> Vec<i32> {
+        self.token_ids_matching(|text| category.matches(text))
+    }
+
+    /// IDs of all text tokens whose text satisfies `pred`.
+    ///
+    /// Token text includes the leading space BPE uses for word starts (" twenty"). Special
+    /// tokens (end of text, timestamps, language tags) are never returned.
+    pub fn token_ids_matching(&self, mut pred: impl FnMut(&str) -> bool) -> Vec<i32> {
+        let ids = self.model.with(|model| {
+            Ok((0..model.ctx.token_eot())
+                .filter(|&id| {
+                    model.ctx
+                        .token_to_cstr(id)
+                        .is_ok_and(|text| pred(&text.to_string_lossy()))
+                })
+                .collect())
+        });
+        ids.unwrap_or_else(|e| {
+            warn!("Failed to read the vocabulary: {}", e);
+            Vec::new()
+        })
+    }
+
+    /// Token IDs whisper uses for `text`, e.g. to look up which tokens to suppress.
+    pub fn tokenize(&self, text: &str) -> Result<Vec<i32>> {
+        self.model.with(|model| {
+            model.ctx
+                .tokenize(text, text.len() + 1)
+                .map_err(|e| crate::Error::Inference(format!("Failed to tokenize: {}", e)))
+        })
+    }
+
+    /// Set the post-processor applied to every transcript.
+    ///
+    /// Defaults to [`NoopProcessor`]. Use [`transcribe_detailed`](Self::transcribe_detailed)
+    /// to get the raw text alongside the processed text.
+    pub fn set_processor(&mut self, processor: Box<dyn TranscriptProcessor>) {
+        self.post.processor = Arc::from(processor);
+    }
+
+    /// Enable or disable removing filler words ("um", "you know") and stutter repeats
+    /// ("the the meeting") from the processed text. Off by default.
+    ///
+    /// Runs after the [`TranscriptProcessor`]; see [`postprocess::remove_disfluencies`].
+    pub fn set_remove_disfluencies(&mut self, enabled: bool) {
+        self.post.remove_disfluencies = enabled;
+    }
+
+    /// Set the filler words/phrases removed when disfluency removal is enabled.
+    ///
+    /// Defaults to [`postprocess::DEFAULT_DISFLUENCIES`].
+    pub fn set_filler_words(&mut self, words: Vec<String>) {
+        self.post.filler_words = words;
+    }
+
+    /// Set casing/punctuation touch-ups for the processed text, applied after everything
+    /// else. Off by default; the raw transcript is never affected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    /// use memo_stt::postprocess::OutputStyle;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// // Dictating into the middle of a sentence
+    /// engine.set_output_style(OutputStyle { lowercase_first: true, strip_trailing_period: true, trim: true });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_output_style(&mut self, style: OutputStyle) {
+        self.post.output_style = style;
+    }
+
+    /// Write spoken dates and amounts of money the way `locale` does ("March third twenty
+    /// twenty four" → "3/3/2024" for [`Locale::EnUs`]), after disfluency removal and before
+    /// the output style. `None` (the default) leaves them as transcribed.
+    pub fn set_locale(&mut self, locale: Option<Locale>) {
+        self.post.locale = locale;
+    }
+
+    /// Limit segments to roughly `max_len` characters, breaking at word boundaries
+    /// (e.g. 42 for subtitles). `0` (the default) means no limit.
+    ///
+    /// Only affects segment boundaries, so it matters for
+    /// [`transcribe_segments`](Self::transcribe_segments); `transcribe` joins segments anyway.
+    pub fn set_max_segment_len(&mut self, max_len: usize) {
+        self.decode.max_segment_len = max_len;
+    }
+
+    /// Run whisper's encoder on `n` frames of audio context (50 per second) instead of the
+    /// full 30 seconds (1500, the model's [`n_audio_ctx`](ModelInfo::n_audio_ctx)). `0`
+    /// restores the full context, the default.
+    ///
+    /// The encoder always processes the whole context, padded with silence, so a 2 second
+    /// voice command costs as much as 30 seconds of audio. A smaller context cuts that time
+    /// roughly in proportion, which matters most on the CPU. The tradeoff is accuracy: the
+    /// models were trained on full 30 second windows, so a reduced context makes mistakes
+    /// and repetitions more likely, and audio beyond `n / 50` seconds of a clip is not heard
+    /// at all. Check the results on your own audio before shipping a small value; prefer
+    /// [`set_auto_audio_ctx`](Self::set_auto_audio_ctx), which sizes it to each clip.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if `n` exceeds the model's context.
+    pub fn set_audio_ctx(&mut self, n: usize) -> Result<()> {
+        if n > self.model_info.n_audio_ctx {
+            return Err(crate::Error::Config(format!(
+                "audio_ctx {} is larger than the model's context of {}",
+                n, self.model_info.n_audio_ctx
+            )));
+        }
+        self.decode.audio_ctx = n;
+        Ok(())
+    }
+
+    /// Size the audio context (see [`set_audio_ctx`](Self::set_audio_ctx)) to each clip: its
+    /// length at 50 frames per second plus about 1.3 seconds of margin, and the full context
+    /// for clips of 30 seconds or more. Off by default; overrides `set_audio_ctx` while on.
+    ///
+    /// Speeds up short clips such as voice commands severalfold on the CPU, with the same
+    /// (usually small) loss of accuracy a fixed reduced context has.
+    pub fn set_auto_audio_ctx(&mut self, auto: bool) {
+        self.decode.auto_audio_ctx = auto;
+    }
+
+    /// Set how readily audio is treated as speech. Defaults to [`Sensitivity::Balanced`].
+    ///
+    /// Use [`Sensitivity::Conservative`] in noisy rooms where whisper invents text from
+    /// background noise, and [`Sensitivity::Aggressive`] for soft-spoken users or distant
+    /// microphones where quiet speech gets dropped.
+    pub fn set_sensitivity(&mut self, sensitivity: Sensitivity) {
+        self.decode.sensitivity = sensitivity;
+    }
+
+    /// Skip the model for clips whose RMS is below `threshold` (see
+    /// [`audio::is_silent`](crate::audio::is_silent)), returning an empty transcript at once.
+    /// Off (`None`) by default.
+    ///
+    /// Saves the inference time for accidental taps that recorded only dead air, and avoids
+    /// the text whisper sometimes hallucinates from silence. Applies to
+    /// [`transcribe`](Self::transcribe) and [`transcribe_detailed`](Self::transcribe_detailed);
+    /// clips under 1 second still fail with [`Error::AudioTooShort`](crate::Error::AudioTooShort).
+    pub fn set_silence_threshold(&mut self, threshold: Option<f32>) {
+        self.silence_threshold = threshold;
+    }
+
+    /// Cut leading and trailing silence off each clip before it is resampled and decoded.
+    /// Off by default.
+    ///
+    /// For push-to-talk, where the key is often pressed before speaking and released after:
+    /// the dead air costs inference time and can make whisper invent text such as "Thank
+    /// you." Audio is measured in 10ms windows and everything before the first and after the
+    /// last window above 100 RMS (i16 scale) is dropped, keeping 100ms either side so quiet
+    /// word edges aren't clipped. A clip with nothing above the threshold is transcribed
+    /// whole, and a trimmed clip is never shorter than the 1 second whisper needs.
+    ///
+    /// Applies to [`transcribe`](Self::transcribe), [`transcribe_f32`](Self::transcribe_f32)
+    /// and [`transcribe_detailed`](Self::transcribe_detailed), whose
+    /// [stats](TranscriptionStats) then describe the trimmed clip. Methods that return
+    /// timestamps always see the whole clip, so times stay relative to its start.
+    pub fn set_trim_silence(&mut self, trim: bool) {
+        self.trim_silence = trim;
+    }
+
+    /// Trade speed for accuracy with one setting. Defaults to [`Preset::Balanced`].
+    ///
+    /// Sets the sampling strategy, thread count and temperature fallback (see [`Preset`]
+    /// for the exact values); prompt, language, sensitivity and the other settings are
+    /// unaffected.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::{Preset, SttEngine};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// engine.set_preset(Preset::Accurate); // e.g. for transcribing recordings offline
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_preset(&mut self, preset: Preset) {
+        self.decode.preset = preset;
+    }
+
+    /// Set the spoken language as a whisper code (`"es"`, `"de"`, `"ja"`; full names like
+    /// `"spanish"` also work), or `None` to let whisper detect it. Multilingual models
+    /// auto-detect by default; English-only models always use `"en"`.
+    ///
+    /// Setting the language skips detection, which saves a little time and avoids
+    /// misdetection on short or accented clips.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) for an unknown language, and on an
+    /// English-only (`.en`) model for any language other than English or for `None`,
+    /// instead of producing garbage. The previous language is kept.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new("ggml-large-v3-turbo-q5_0.bin", 16000)?; // multilingual
+    /// engine.set_language(Some("es"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_language(&mut self, language: Option<&str>) -> Result<()> {
+        self.decode.language = self.resolve_language(language.unwrap_or("auto"))?;
+        Ok(())
+    }
+
+    /// Language code transcriptions use, or `None` if whisper detects it.
+    pub fn language(&self) -> Option<&'static str> {
+        self.decode.language
+    }
 
-        // Extract text
-        let n = state.full_n_segments()
-            .map_err(|e| crate::Error(format!("Failed to get segments: {}", e)))?;
-        
-        let mut text = String::new();
-        for i in 0..n {
-            if let Ok(seg) = state.full_get_segment_text(i) {
-                if !text.is_empty() {
-                    text.push(' ');
-                }
-                text.push_str(seg.trim());
-            }
+    /// Identify the spoken language without transcribing: the most likely language code
+    /// and its probability (`0.0..=1.0`), from whisper's language-id pass over the first
+    /// 30 seconds.
+    ///
+    /// Much cheaper than a transcription. Pass the code to
+    /// [`set_language`](Self::set_language) (or [`TranscribeOptions::language`]) to
+    /// transcribe in it, e.g. after checking the probability is high enough.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AudioTooShort`](crate::Error::AudioTooShort) for clips under 1
+    /// second, where a guess would be meaningless, and
+    /// [`Error::Config`](crate::Error::Config) on an English-only model.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new("ggml-large-v3-turbo-q5_0.bin", 16000)?;
+    /// let voicemail: Vec<i16> = vec![]; // Replace with actual audio
+    /// let (language, probability) = engine.detect_language(&voicemail)?;
+    /// if probability > 0.5 {
+    ///     engine.set_language(Some(&language))?;
+    /// }
+    /// let text = engine.transcribe(&voicemail)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn detect_language(&mut self, samples: &[i16]) -> Result<(String, f32)> {
+        if !self.model_info.is_multilingual {
+            return Err(crate::Error::Config("English-only models can't detect the language".into()));
         }
+        self.prepare_input(Pcm::I16(samples))?;
+        let n_threads = self.decode.n_threads();
+        let input = &self.f32_buffer;
+        let (id, probabilities) = self.model.with(|model| {
+            model.state.pcm_to_mel(input, n_threads)
+                .map_err(|e| crate::Error::Inference(format!("Failed to compute spectrogram: {}", e)))?;
+            model.state.lang_detect(0, n_threads)
+                .map_err(|e| crate::Error::Inference(format!("Language detection failed: {}", e)))
+        })?;
+        let code = whisper_rs::get_lang_str(id)
+            .ok_or_else(|| crate::Error::Inference(format!("Unknown language id {}", id)))?;
+        let probability = probabilities.get(id as usize).copied().unwrap_or(0.0);
+        debug!("Detected language {} (p = {:.2})", code, probability);
+        Ok((code.to_string(), probability))
+    }
 
-        Ok(text)
+    /// Decode with `n` threads instead of the [`Preset`]'s count (all cores up to 8 by
+    /// default). `0` goes back to the preset's count.
+    ///
+    /// Raise it on many-core machines that batch long recordings; lower it to leave cores
+    /// for the rest of an app. Also used by [`warmup`](Self::warmup).
+    pub fn set_n_threads(&mut self, n: usize) {
+        self.decode.n_threads = (n > 0).then_some(n);
     }
 
-    /// Set initial prompt for custom vocabulary or context.
+    /// Threads transcriptions currently decode with (see [`set_n_threads`](Self::set_n_threads)).
+    pub fn n_threads(&self) -> usize {
+        self.decode.n_threads()
+    }
+
+    /// Timing of the most recent successful transcription, or `None` if there hasn't been one
+    /// (or the last call received no audio).
     ///
-    /// Useful for improving accuracy with domain-specific terms, names, or technical vocabulary.
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let samples: Vec<i16> = vec![]; // Replace with actual audio
+    /// engine.transcribe(&samples)?;
+    /// if let Some(stats) = engine.last_transcription_stats() {
+    ///     println!("{:.1}x realtime", stats.realtime_factor);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_transcription_stats(&self) -> Option<TranscriptionStats> {
+        self.last_stats
+    }
+
+    /// Discard whisper's decoder state and start from a clean slate.
+    ///
+    /// Each transcription is decoded independently, but the underlying `WhisperState` keeps
+    /// buffers (KV cache, decoder history) between calls. Call this between unrelated
+    /// sessions or speakers, or after a failed transcription, if you suspect one utterance
+    /// is influencing the next. Recreating the state is cheap compared to loading the model;
+    /// the prompt, processor and other settings are kept.
     ///
     /// # Example
     ///
@@ -252,12 +2279,17 @@ impl SttEngine {
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let mut engine = SttEngine::new_default(16000)?;
-    /// engine.set_prompt(Some("Rust programming language, cargo, crates.io".to_string()));
+    /// # let samples: Vec<i16> = vec![];
+    /// let _ = engine.transcribe(&samples)?;
+    /// engine.reset()?; // next speaker
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_prompt(&mut self, prompt: Option<String>) {
-        self.initial_prompt = prompt;
+    pub fn reset(&mut self) -> Result<()> {
+        self.model.reset_state()?;
+        self.last_stats = None;
+        debug!("Engine state reset");
+        Ok(())
     }
 
     /// Warm up the GPU to reduce first-transcription latency.
@@ -279,13 +2311,730 @@ impl SttEngine {
     /// ```
     pub fn warmup(&self) -> Result<()> {
         let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-        params.set_n_threads(2);
-        params.set_language(Some("en"));
+        // Same thread count as transcriptions, so the warmed-up timings are representative
+        params.set_n_threads(self.decode.n_threads() as i32);
+        params.set_language(self.decode.language);
         params.set_print_progress(false);
         params.set_print_special(false);
         params.set_print_realtime(false);
-        let mut state = self.state.lock().map_err(|e| crate::Error(format!("State lock failed: {}", e)))?;
-        let _ = state.full(params, &vec![0.0f32; 1600]);
+        self.model.with(|model| {
+            let _ = model.state.full(params, &vec![0.0f32; 1600]);
+            Ok(())
+        })
+    }
+
+    /// Free the model's memory (RAM and VRAM) until the engine is used again.
+    ///
+    /// Settings are kept, and the next transcription (or anything else that needs the
+    /// model, like [`tokenize`](Self::tokenize)) loads it from disk again, so it pays the
+    /// load time plus the cost a [`warmup`](Self::warmup) would have absorbed. Call
+    /// [`reload`](Self::reload) to pay that up front instead. Waits for a running
+    /// [`transcribe_async`](Self::transcribe_async) to finish.
+    ///
+    /// Useful for apps that idle in the background for hours; see also
+    /// [`set_idle_unload`](Self::set_idle_unload).
+    pub fn unload(&mut self) {
+        self.model.unload();
+        self.f32_buffer = Vec::new();
+    }
+
+    /// Load the model again after [`unload`](Self::unload) (does nothing if it is loaded).
+    ///
+    /// Transcribing reloads it automatically; call this, followed by
+    /// [`warmup`](Self::warmup), to take the load time before the user starts talking.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Model`](crate::Error::Model) if the model file can no longer be loaded.
+    pub fn reload(&mut self) -> Result<()> {
+        self.model.with(|_| Ok(()))?;
+        if self.decode.prompt_tokens.is_none() {
+            if let Some(prompt) = self.decode.initial_prompt.clone().filter(|p| !p.trim().is_empty()) {
+                self.decode.prompt_tokens = self.tokenize_prompt(&prompt);
+            }
+        }
         Ok(())
     }
+
+    /// Whether the model is in memory, i.e. not [unloaded](Self::unload).
+    pub fn is_loaded(&self) -> bool {
+        self.model.is_loaded()
+    }
+
+    /// Bytes held by the engine's reusable audio buffer.
+    ///
+    /// The buffer grows to fit the longest clip transcribed so far (4 bytes per sample at
+    /// 16 kHz, so about 38 MB for 10 minutes) and keeps that size; see
+    /// [`shrink_buffers`](Self::shrink_buffers).
+    pub fn buffer_bytes(&self) -> usize {
+        self.f32_buffer.capacity() * std::mem::size_of::<f32>()
+    }
+
+    /// Release the reusable audio buffer's memory beyond what a few seconds of audio need.
+    ///
+    /// Call after an unusually long clip in a long-lived engine so one big transcription
+    /// doesn't pin its memory for good. The next long clip grows the buffer again.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// let meeting: Vec<i16> = vec![]; // Replace with an hour-long recording
+    /// let text = engine.transcribe_robust(&meeting, 0.1)?;
+    /// engine.shrink_buffers();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shrink_buffers(&mut self) {
+        self.f32_buffer.clear();
+        self.f32_buffer.shrink_to(RETAINED_BUFFER_SAMPLES);
+    }
+
+    /// Unload the model automatically after it has gone unused for `timeout`; `None` (the
+    /// default) keeps it loaded.
+    ///
+    /// A background thread checks about once a second and never unloads during a
+    /// transcription. The next use after an unload reloads the model, with the extra
+    /// latency described in [`unload`](Self::unload).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::SttEngine;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut engine = SttEngine::new_default(16000)?;
+    /// // Tray app: give the memory back after ten quiet minutes
+    /// engine.set_idle_unload(Some(Duration::from_secs(600)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_idle_unload(&mut self, timeout: Option<Duration>) {
+        self.model.touch();
+        self.model.set_idle_timeout(timeout);
+    }
+}
+
+/// Builder for [`SttEngine`].
+///
+/// # Example
+///
+/// ```no_run
+/// use memo_stt::SttEngine;
+///
+/// // Never download: fails with Error::ModelNotFound if the model isn't on disk
+/// let engine = SttEngine::builder()
+///     .model_path("models/ggml-small.en-q5_1.bin")
+///     .input_sample_rate(48000)
+///     .allow_download(false)
+///     .build()?;
+///
+/// // Decoding settings are applied (and validated) before the engine is returned
+/// let engine = SttEngine::builder()
+///     .model_path("ggml-large-v3-turbo-q5_0.bin")
+///     .language("de")
+///     .translate(true)
+///     .n_threads(4)
+///     .initial_prompt("Bundestag, Bundesrat")
+///     .build()?;
+/// # Ok::<(), memo_stt::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SttEngineBuilder {
+    model_path: Option<PathBuf>,
+    input_sample_rate: u32,
+    allow_download: bool,
+    gpu: GpuOptions,
+    language: Option<String>,
+    n_threads: usize, // 0 = the preset's count
+    initial_prompt: Option<String>,
+    translate: bool,
+}
+
+impl Default for SttEngineBuilder {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            input_sample_rate: crate::audio::WHISPER_SAMPLE_RATE,
+            allow_download: true,
+            gpu: GpuOptions::default(),
+            language: None,
+            n_threads: 0,
+            initial_prompt: None,
+            translate: false,
+        }
+    }
+}
+
+impl SttEngineBuilder {
+    /// Model file or name (defaults to [`default_model_path`](crate::default_model_path))
+    pub fn model_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.model_path = Some(path.into());
+        self
+    }
+
+    /// Sample rate of the audio that will be transcribed (defaults to 16000)
+    pub fn input_sample_rate(mut self, rate: u32) -> Self {
+        self.input_sample_rate = rate;
+        self
+    }
+
+    /// Whether a missing default model may be downloaded (defaults to `true`).
+    ///
+    /// With `false` the engine never writes to disk or touches the network: a missing model
+    /// fails with [`Error::ModelNotFound`](crate::Error::ModelNotFound) and a corrupt one with
+    /// [`Error::CorruptModel`](crate::Error::CorruptModel). Fetch models explicitly with
+    /// [`download_model`](crate::download_model).
+    pub fn allow_download(mut self, allow: bool) -> Self {
+        self.allow_download = allow;
+        self
+    }
+
+    /// Device to load the model on (defaults to GPU 0 if available); see [`SttEngine::new_with_gpu`]
+    pub fn gpu(mut self, gpu: GpuOptions) -> Self {
+        self.gpu = gpu;
+        self
+    }
+
+    /// Spoken language code, or `"auto"` to detect it (defaults to `"en"` on English-only
+    /// models and auto-detection otherwise); see [`SttEngine::set_language`]
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// CPU threads for inference, 0 for the preset's count (the default); see
+    /// [`SttEngine::set_n_threads`]
+    pub fn n_threads(mut self, n: usize) -> Self {
+        self.n_threads = n;
+        self
+    }
+
+    /// Custom vocabulary or context; see [`SttEngine::set_prompt`]
+    pub fn initial_prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.initial_prompt = Some(prompt.into());
+        self
+    }
+
+    /// Translate speech into English instead of transcribing it (defaults to `false`).
+    /// Needs a multilingual model.
+    pub fn translate(mut self, translate: bool) -> Self {
+        self.translate = translate;
+        self
+    }
+
+    /// Load the model and create the engine with every setting applied.
+    ///
+    /// # Errors
+    ///
+    /// Besides the errors of [`SttEngine::new`], returns [`Error::Config`](crate::Error::Config)
+    /// for an unknown language, or a language other than `"en"` or translation on an
+    /// English-only model.
+    pub fn build(self) -> Result<SttEngine> {
+        let model_path = self.model_path.unwrap_or_else(crate::default_model_path);
+        let mut engine = SttEngine::load(model_path, self.input_sample_rate, self.gpu, self.allow_download)?;
+        let options = TranscribeOptions {
+            language: self.language,
+            prompt: self.initial_prompt,
+            translate: Some(self.translate),
+            preset: None,
+        };
+        engine.decode = engine.decode_with(&options)?;
+        engine.set_n_threads(self.n_threads);
+        Ok(engine)
+    }
+}
+
+/// Run whisper over 16 kHz `input` and read the result out of the state with `extract`.
+///
+/// Returns the result and the inference time in seconds.
+fn run_full<T>(
+    model: &ModelSlot,
+    settings: &DecodeSettings,
+    input: &[f32],
+    word_timestamps: bool,
+    on_update: Option<&mut dyn FnMut(TranscriptUpdate)>,
+    extract: impl FnOnce(&WhisperState) -> Result<T>,
+) -> Result<(T, Timing)> {
+    let mut params = settings.params(word_timestamps, input.len());
+    let reports_updates = on_update.is_some();
+    let mut hooks = Hooks {
+        suppress_tokens: &settings.suppress_tokens,
+        logits_filter: settings.logits_filter.as_deref(),
+        progress: settings.progress.as_deref(),
+        on_update,
+        partial: String::new(),
+        segment_tail: Vec::new(),
+        first_segment_at: None,
+    };
+    let hooks_ptr = &mut hooks as *mut Hooks as *mut c_void;
+    // SAFETY: `hooks` outlives `state.full` below and is only touched by these callbacks,
+    // which whisper.cpp runs one at a time on this thread. Suppressed IDs were checked
+    // against n_vocab.
+    unsafe {
+        if !settings.suppress_tokens.is_empty() || settings.logits_filter.is_some() || reports_updates {
+            params.set_filter_logits_callback(Some(logits_filter_hook));
+            params.set_filter_logits_callback_user_data(hooks_ptr);
+        }
+        // Always installed, to time the first segment
+        params.set_new_segment_callback(Some(new_segment_hook));
+        params.set_new_segment_callback_user_data(hooks_ptr);
+        if settings.progress.is_some() {
+            params.set_progress_callback(Some(progress_hook));
+            params.set_progress_callback_user_data(hooks_ptr);
+        }
+        // The token outlives `state.full`; the hook only reads its atomic flag, so it's fine
+        // for ggml to call it from its worker threads
+        if let Some(cancel) = &settings.cancel {
+            params.set_abort_callback(Some(abort_hook));
+            params.set_abort_callback_user_data(cancel as *const CancelToken as *mut c_void);
+        }
+    }
+
+    // Lock the model (loading it if it was unloaded) and run inference
+    let (result, inference_start, inference_secs, cancelled) = model.with(|model| {
+        debug!("Starting inference on {} samples ({:.2}s)", input.len(), input.len() as f32 / 16000.0);
+        let inference_start = Instant::now();
+        let mut cancelled = false;
+        if let Err(e) = model.state.full(params, input) {
+            // An aborted run fails, but the segments finished before it are still there
+            cancelled = settings.cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+            if !cancelled {
+                return Err(crate::Error::Inference(format!("Inference failed: {}", e)));
+            }
+            debug!("Inference cancelled");
+        }
+        let inference_secs = inference_start.elapsed().as_secs_f32();
+        debug!("Inference took {:.3}s", inference_secs);
+
+        Ok((extract(&model.state)?, inference_start, inference_secs, cancelled))
+    })?;
+    let first_segment_secs = hooks
+        .first_segment_at
+        .map(|at| at.saturating_duration_since(inference_start).as_secs_f32());
+    Ok((result, Timing { inference_secs, first_segment_secs, cancelled }))
+}
+
+fn join_segments(segments: &[Segment]) -> String {
+    let mut text = String::new();
+    for seg in segments {
+        if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(&seg.text);
+    }
+    text
+}
+
+fn read_segments(state: &WhisperState) -> Result<Vec<Segment>> {
+    let n = state.full_n_segments()
+        .map_err(|e| crate::Error::Inference(format!("Failed to get segments: {}", e)))?;
+
+    let mut segments: Vec<Segment> = Vec::with_capacity(n.max(0) as usize);
+    // Segments split at max_len can end mid-character; the rest of it starts the next one
+    let mut pending = Vec::new();
+    for i in 0..n {
+        if let Ok(bytes) = state.full_get_segment_bytes(i) {
+            // Timestamps are in centiseconds
+            let t0 = state.full_get_segment_t0(i).unwrap_or(0);
+            let t1 = state.full_get_segment_t1(i).unwrap_or(t0);
+            segments.push(Segment {
+                start_secs: t0 as f32 / 100.0,
+                end_secs: t1 as f32 / 100.0,
+                text: take_complete_utf8(&mut pending, &bytes).trim().to_string(),
+            });
+        }
+    }
+    if let Some(last) = segments.last_mut() {
+        last.text.push_str(String::from_utf8_lossy(&pending).trim_end());
+    }
+    segments.retain(|seg| !seg.text.is_empty());
+
+    Ok(segments)
+}
+
+/// Split `bytes`, appended to `pending`, into complete UTF-8 text and a trailing partial
+/// character, which stays in `pending` for the next call. Invalid bytes become U+FFFD.
+///
+/// Whisper's units (tokens, and segments split at `max_len`) are byte-based, so a multi-byte
+/// character from a multilingual model can straddle two of them.
+fn take_complete_utf8(pending: &mut Vec<u8>, bytes: &[u8]) -> String {
+    pending.extend_from_slice(bytes);
+    let mut split = pending.len();
+    // Look for a lead byte in the last 3 bytes whose sequence runs past the end
+    for back in 1..=pending.len().min(3) {
+        let b = pending[pending.len() - back];
+        if b & 0xC0 == 0x80 {
+            continue; // continuation byte
+        }
+        let needed = match b {
+            0xF0..=0xF7 => 4,
+            0xE0.. => 3,
+            0xC0.. => 2,
+            _ => 1,
+        };
+        if needed > back {
+            split = pending.len() - back;
+        }
+        break;
+    }
+    let text = String::from_utf8_lossy(&pending[..split]).into_owned();
+    pending.drain(..split);
+    text
+}
+
+/// Text tokens (no special or timestamp tokens) of each segment
+fn read_tokens(state: &WhisperState) -> Result<Vec<Vec<WhisperTokenData>>> {
+    let n = state.full_n_segments()
+        .map_err(|e| crate::Error::Inference(format!("Failed to get segments: {}", e)))?;
+
+    let mut segments = Vec::with_capacity(n.max(0) as usize);
+    for seg in 0..n {
+        let n_tokens = state.full_n_tokens(seg).unwrap_or(0);
+        segments.push((0..n_tokens).filter_map(|tok| state.full_get_token_data(seg, tok).ok()).collect());
+    }
+    Ok(segments)
+}
+
+/// Mean log-probability of the text tokens (IDs below `eot`), or 0 without any
+fn mean_logprob(segments: &[Vec<WhisperTokenData>], eot: WhisperToken) -> f32 {
+    let plogs: Vec<f32> = segments.iter().flatten().filter(|t| t.id < eot).map(|t| t.plog).collect();
+    if plogs.is_empty() {
+        0.0
+    } else {
+        plogs.iter().sum::<f32>() / plogs.len() as f32
+    }
+}
+
+/// Group text tokens into words: a token starting with a space opens a new word, anything
+/// else (word pieces, punctuation, the rest of a split character) extends the current one.
+fn words_from_tokens(ctx: &WhisperContext, segments: &[Vec<WhisperTokenData>]) -> Vec<Word> {
+    let eot = ctx.token_eot();
+    let mut words: Vec<Word> = Vec::new();
+    let mut word_bytes: Vec<u8> = Vec::new();
+    let mut token_probs: Vec<f32> = Vec::new();
+    for tokens in segments {
+        // Special and timestamp tokens sort after end-of-text
+        for data in tokens.iter().filter(|t| t.id < eot) {
+            let Ok(piece) = ctx.token_to_cstr(data.id) else { continue };
+            let piece = piece.to_bytes();
+            let (t0, t1) = (data.t0 as f32 / 100.0, data.t1 as f32 / 100.0);
+            match words.last_mut() {
+                Some(word) if !piece.starts_with(b" ") && !token_probs.is_empty() => {
+                    word.end_secs = word.end_secs.max(t1);
+                }
+                _ => {
+                    finish_word(&mut words, &mut word_bytes, &mut token_probs);
+                    words.push(Word { start_secs: t0, end_secs: t1, text: String::new(), probability: 0.0 });
+                }
+            }
+            word_bytes.extend_from_slice(piece);
+            token_probs.push(data.p);
+        }
+        // Segments always end on a word boundary
+        finish_word(&mut words, &mut word_bytes, &mut token_probs);
+    }
+    words.retain(|w| !w.text.is_empty());
+    words
+}
+
+/// Decode the current word's text; a word is only as certain as its least certain token
+fn finish_word(words: &mut [Word], word_bytes: &mut Vec<u8>, token_probs: &mut Vec<f32>) {
+    if let Some(word) = words.last_mut() {
+        if !token_probs.is_empty() {
+            word.text = String::from_utf8_lossy(word_bytes).trim().to_string();
+            word.probability = token_probs.iter().copied().fold(1.0, f32::min);
+        }
+    }
+    word_bytes.clear();
+    token_probs.clear();
+}
+
+/// Windows (`start..end` sample ranges) covering `len` samples for
+/// [`SttEngine::transcribe_robust`]: at most `window` long, each starting about `hop` after
+/// the last and never after its end. Edges move back by up to `tolerance` to the nearest of
+/// the (sorted) `pauses`, but never so far that a window gets shorter than `min_len` (the 1
+/// second whisper needs); the last window is aligned to the end so it is full length too.
+fn plan_windows(len: usize, window: usize, hop: usize, pauses: &[usize], tolerance: usize, min_len: usize) -> Vec<(usize, usize)> {
+    // Latest pause in `lo..=hi`
+    let pause_in = |lo: usize, hi: usize| {
+        let from = pauses.partition_point(|&p| p < lo);
+        let to = pauses.partition_point(|&p| p <= hi);
+        pauses[from..to.max(from)].last().copied()
+    };
+
+    let mut windows = Vec::new();
+    let mut start = 0;
+    while len - start > window {
+        let full = start + window;
+        let end = pause_in((full - tolerance).max(start + min_len), full).unwrap_or(full);
+        windows.push((start, end));
+        let next = (start + hop).min(end);
+        start = pause_in(next.saturating_sub(tolerance).max(start + 1), next).unwrap_or(next);
+    }
+    // The last window runs to the end: full length, unless it can start in a pause without
+    // leaving a gap after the previous one
+    let first = len.saturating_sub(window);
+    let latest = (first + tolerance).min(start).min(len.saturating_sub(min_len));
+    windows.push((pause_in(first, latest).unwrap_or(first), len));
+    windows
+}
+
+/// Prompt for the next [`SttEngine::transcribe_stream`] chunk: the user's prompt followed by
+/// the last [`STREAM_CONTEXT_WORDS`] words so far, or `None` before there are any.
+fn stream_prompt(user_prompt: Option<&str>, emitted: &str, pending: &[Word]) -> Option<String> {
+    let words: Vec<&str> = emitted.split_whitespace().chain(pending.iter().map(|w| w.text.as_str())).collect();
+    if words.is_empty() {
+        return None;
+    }
+    let context = words[words.len().saturating_sub(STREAM_CONTEXT_WORDS)..].join(" ");
+    Some(match user_prompt {
+        Some(prompt) => format!("{} {}", prompt.trim(), context),
+        None => context,
+    })
+}
+
+/// Merge the next window's words into the running transcript.
+///
+/// `overlap_start..overlap_end` (seconds) is heard by both. Matching words (same normalized
+/// text or overlapping in time) are resolved by probability; a word only one window heard is
+/// kept if it lies in that window's half of the overlap.
+fn merge_overlap(prev: Vec<Word>, next: Vec<Word>, overlap_start: f32, overlap_end: f32) -> Vec<Word> {
+    let mid = |w: &Word| (w.start_secs + w.end_secs) / 2.0;
+    let centre = (overlap_start + overlap_end) / 2.0;
+    let normalized = |w: &Word| -> String {
+        w.text.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect()
+    };
+
+    let (mut merged, prev_overlap): (Vec<Word>, Vec<Word>) =
+        prev.into_iter().partition(|w| mid(w) < overlap_start);
+    let (next_overlap, next_rest): (Vec<Word>, Vec<Word>) =
+        next.into_iter().partition(|w| mid(w) <= overlap_end);
+
+    let mut a = prev_overlap.into_iter().peekable();
+    let mut b = next_overlap.into_iter().peekable();
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(x), Some(y)) => {
+                let same_text = normalized(x) == normalized(y) && (mid(x) - mid(y)).abs() < 1.0;
+                let same_time = x.start_secs < y.end_secs && y.start_secs < x.end_secs;
+                if same_text || same_time {
+                    let (x, y) = (a.next().unwrap(), b.next().unwrap());
+                    merged.push(if x.probability >= y.probability { x } else { y });
+                } else if mid(x) <= mid(y) {
+                    let x = a.next().unwrap();
+                    if mid(&x) < centre {
+                        merged.push(x);
+                    }
+                } else {
+                    let y = b.next().unwrap();
+                    if mid(&y) >= centre {
+                        merged.push(y);
+                    }
+                }
+            }
+            (Some(_), None) => merged.extend(a.by_ref().filter(|x| mid(x) < centre)),
+            (None, Some(_)) => merged.extend(b.by_ref().filter(|y| mid(y) >= centre)),
+            (None, None) => break,
+        }
+    }
+
+    merged.extend(next_rest);
+    merged
+}
+
+/// State shared by the whisper callbacks during one `state.full` run
+struct Hooks<'a, 'f> {
+    suppress_tokens: &'a [WhisperToken],
+    logits_filter: Option<&'a LogitsFilter>,
+    progress: Option<&'a Mutex<Box<InferenceProgress>>>,
+    on_update: Option<&'f mut dyn FnMut(TranscriptUpdate)>,
+    /// Last partial text reported, to skip duplicates
+    partial: String,
+    /// Start of a character split across finalized segments
+    segment_tail: Vec<u8>,
+    /// When whisper finalized its first segment
+    first_segment_at: Option<Instant>,
+}
+
+/// Runs before each token is sampled, with the tokens decoded so far in the current segment:
+/// forces suppressed tokens to -inf, runs the user's logits filter and reports the partial
+/// text.
+unsafe extern "C" fn logits_filter_hook(
+    ctx: *mut whisper_rs::WhisperSysContext,
+    _state: *mut whisper_rs::WhisperSysState,
+    tokens: *const whisper_rs::WhisperTokenData,
+    n_tokens: c_int,
+    logits: *mut f32,
+    user_data: *mut c_void,
+) {
+    if user_data.is_null() {
+        return;
+    }
+    // SAFETY: user_data is the `Hooks` set up in `run_full`; logits has n_vocab entries and
+    // tokens has n_tokens entries, as documented by whisper.cpp.
+    let hooks = unsafe { &mut *(user_data as *mut Hooks) };
+    if !logits.is_null() {
+        for &id in hooks.suppress_tokens {
+            unsafe { *logits.add(id as usize) = f32::NEG_INFINITY };
+        }
+    }
+    let tokens = match (tokens.is_null(), n_tokens) {
+        (false, n) if n > 0 => unsafe { std::slice::from_raw_parts(tokens, n as usize) },
+        _ => &[],
+    };
+    if let Some(filter) = hooks.logits_filter.filter(|_| !logits.is_null()) {
+        let n_vocab = unsafe { whisper_rs::whisper_rs_sys::whisper_n_vocab(ctx) }.max(0) as usize;
+        let logits = unsafe { std::slice::from_raw_parts_mut(logits, n_vocab) };
+        let ids: Vec<WhisperToken> = tokens.iter().map(|t| t.id).collect();
+        filter(&ids, logits);
+    }
+
+    let Some(on_update) = hooks.on_update.as_mut() else { return };
+    if tokens.is_empty() {
+        return;
+    }
+    let eot = unsafe { whisper_rs::whisper_rs_sys::whisper_token_eot(ctx) };
+    let mut bytes = Vec::new();
+    for token in tokens.iter().filter(|t| t.id < eot) {
+        let piece = unsafe { whisper_rs::whisper_rs_sys::whisper_token_to_str(ctx, token.id) };
+        if !piece.is_null() {
+            bytes.extend_from_slice(unsafe { CStr::from_ptr(piece) }.to_bytes());
+        }
+    }
+    // A multi-byte character may be split across tokens; hold back the incomplete tail
+    let text = take_complete_utf8(&mut Vec::new(), &bytes);
+    let text = text.trim();
+    if !text.is_empty() && text != hooks.partial {
+        hooks.partial = text.to_string();
+        on_update(TranscriptUpdate::Partial(hooks.partial.clone()));
+    }
+}
+
+/// Runs as whisper works through the audio, with the percentage done
+unsafe extern "C" fn progress_hook(
+    _ctx: *mut whisper_rs::WhisperSysContext,
+    _state: *mut whisper_rs::WhisperSysState,
+    progress: c_int,
+    user_data: *mut c_void,
+) {
+    if user_data.is_null() {
+        return;
+    }
+    // SAFETY: see `logits_filter_hook`
+    let hooks = unsafe { &mut *(user_data as *mut Hooks) };
+    if let Some(progress_fn) = hooks.progress {
+        // A callback that panicked earlier poisoned the lock; keep reporting anyway
+        let mut progress_fn = progress_fn.lock().unwrap_or_else(|e| e.into_inner());
+        progress_fn(progress);
+    }
+}
+
+/// Polled by whisper.cpp during inference; returning `true` stops it.
+unsafe extern "C" fn abort_hook(user_data: *mut c_void) -> bool {
+    if user_data.is_null() {
+        return false;
+    }
+    // SAFETY: set to the `CancelToken` in the decode settings, which outlive `state.full`
+    let cancel = unsafe { &*(user_data as *const CancelToken) };
+    cancel.is_cancelled()
+}
+
+/// Runs after whisper finalizes `n_new` segments
+unsafe extern "C" fn new_segment_hook(
+    _ctx: *mut whisper_rs::WhisperSysContext,
+    state: *mut whisper_rs::WhisperSysState,
+    n_new: c_int,
+    user_data: *mut c_void,
+) {
+    if user_data.is_null() || state.is_null() {
+        return;
+    }
+    // SAFETY: see `logits_filter_hook`
+    let hooks = unsafe { &mut *(user_data as *mut Hooks) };
+    if n_new > 0 && hooks.first_segment_at.is_none() {
+        hooks.first_segment_at = Some(Instant::now());
+    }
+    let Some(on_update) = hooks.on_update.as_mut() else { return };
+    hooks.partial.clear();
+    let n_segments = unsafe { whisper_rs::whisper_rs_sys::whisper_full_n_segments_from_state(state) };
+    for i in (n_segments - n_new).max(0)..n_segments {
+        let text = unsafe { whisper_rs::whisper_rs_sys::whisper_full_get_segment_text_from_state(state, i) };
+        if text.is_null() {
+            continue;
+        }
+        let bytes = unsafe { CStr::from_ptr(text) }.to_bytes();
+        let text = take_complete_utf8(&mut hooks.segment_tail, bytes);
+        if text.trim().is_empty() {
+            continue;
+        }
+        // Timestamps are in centiseconds
+        let t0 = unsafe { whisper_rs::whisper_rs_sys::whisper_full_get_segment_t0_from_state(state, i) };
+        let t1 = unsafe { whisper_rs::whisper_rs_sys::whisper_full_get_segment_t1_from_state(state, i) };
+        on_update(TranscriptUpdate::Segment(Segment {
+            start_secs: t0 as f32 / 100.0,
+            end_secs: t1 as f32 / 100.0,
+            text: text.trim().to_string(),
+        }));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{plan_windows, take_complete_utf8};
+
+    #[test]
+    fn holds_back_characters_split_across_chunks() {
+        let text = "café 日本語".as_bytes();
+        let mut pending = Vec::new();
+        let mut out = String::new();
+        for chunk in text.chunks(2) {
+            out.push_str(&take_complete_utf8(&mut pending, chunk));
+        }
+        assert!(pending.is_empty());
+        assert_eq!(out, "café 日本語");
+
+        let mut pending = Vec::new();
+        assert_eq!(take_complete_utf8(&mut pending, &"日".as_bytes()[..2]), "");
+        assert_eq!(take_complete_utf8(&mut pending, &"日".as_bytes()[2..]), "日");
+    }
+
+    #[test]
+    fn invalid_bytes_are_replaced_not_held() {
+        let mut pending = Vec::new();
+        assert_eq!(take_complete_utf8(&mut pending, b"a\xFFb"), "a\u{FFFD}b");
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn robust_windows_end_in_nearby_pauses() {
+        // 100 units of audio, windows of 30 with a hop of 15, edges may move back by 3
+        let plain = plan_windows(100, 30, 15, &[], 3, 10);
+        assert_eq!(plain, vec![(0, 30), (15, 45), (30, 60), (45, 75), (60, 90), (70, 100)]);
+
+        let snapped = plan_windows(100, 30, 15, &[13, 28, 44, 72], 3, 10);
+        assert_eq!(snapped, vec![(0, 28), (13, 43), (28, 58), (43, 72), (58, 88), (72, 100)]);
+        // Every window is within bounds and overlaps or abuts the previous one
+        for pair in snapped.windows(2) {
+            assert!(pair[1].0 <= pair[0].1 && pair[1].1 - pair[1].0 <= 30);
+        }
+
+        // Without overlap, each window starts where the last ended
+        assert_eq!(plan_windows(70, 30, 30, &[28], 3, 10), vec![(0, 28), (28, 58), (40, 70)]);
+    }
+
+    #[test]
+    fn snapped_windows_keep_the_minimum_length() {
+        // transcribe_stream with 1.2s chunks at 10 units a second: overlap (and snap
+        // tolerance) of 3, so ending the first window at the pause at 9 would leave 0.9s
+        let windows = plan_windows(40, 12, 9, &[9, 21, 38], 3, 10);
+        assert_eq!(windows, vec![(0, 12), (9, 21), (18, 30), (27, 38), (28, 40)]);
+        assert!(windows.iter().all(|&(start, end)| end - start >= 10));
+        // A pause that keeps the window long enough is still used
+        assert_eq!(plan_windows(40, 12, 9, &[11], 3, 10)[0], (0, 11));
+    }
 }
@@ -59,24 +59,92 @@
 //! | Speed | ✅ Fast | ✅ Fast | ⚠️ Network latency |
 //! | GPU | ✅ Auto | ✅ Manual | N/A |
 
+pub mod audio;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod diagnostics;
 pub mod engine;
 pub mod model;
+pub mod pool;
+pub mod postprocess;
+pub mod source;
+pub mod streaming;
+pub mod trigger;
 
-pub use engine::SttEngine;
-pub use model::{default_model_path, ensure_model};
+pub use diagnostics::{diagnostics, Check, CheckStatus};
+pub use engine::{Backend, GpuOptions, InferenceProgress, LogitsFilter, ModelInfo, Preset, Segment, Sensitivity, SttEngine, SttEngineBuilder, TokenCategory, TranscribeOptions, TranscribeOutcome, Transcript, TranscriptUpdate, TranscriptionStats, Word};
+pub use model::{default_model_path, download_model, download_model_with, ensure_model, ensure_model_in, ensure_model_kind, ensure_model_with_progress, is_offline, locate_model, prefetch_model, set_offline, validate_model, CancelToken, DownloadOptions, DownloadProgress, ModelKind, ProgressCallback};
 
 /// Default Whisper model name (small.en Q5_1)
 /// 
 /// The model will be automatically downloaded to the cache directory on first use.
 pub const DEFAULT_MODEL: &str = "ggml-small.en-q5_1.bin";
 
-/// Simple error type
+/// Error type
 #[derive(Debug)]
-pub struct Error(pub String);
+pub enum Error {
+    /// Model could not be downloaded or loaded
+    Model(String),
+    /// Model file does not exist (and was not downloaded)
+    ModelNotFound {
+        path: std::path::PathBuf,
+        /// Every location that was checked, in order (see [`model::locate_model`])
+        searched: Vec<std::path::PathBuf>,
+    },
+    /// Model file exists but is not a valid GGML model (e.g. truncated download)
+    CorruptModel {
+        path: std::path::PathBuf,
+        reason: String,
+    },
+    /// Transcription failed
+    Inference(String),
+    /// Invalid configuration value
+    Config(String),
+    /// Audio input could not be captured or decoded (missing device, malformed PCM data)
+    Audio(String),
+    /// Clip is shorter than the 1 second whisper needs (e.g. the hotkey was tapped rather
+    /// than held). Both counts are at the engine's input sample rate.
+    AudioTooShort {
+        samples: usize,
+        min_samples: usize,
+    },
+    /// Operation was cancelled through a [`CancelToken`](model::CancelToken). (Cancelled
+    /// transcriptions return their partial text instead; see
+    /// [`SttEngine::transcribe_cancellable`].)
+    Cancelled,
+}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            Error::Model(msg) | Error::Inference(msg) | Error::Config(msg) | Error::Audio(msg) => write!(f, "{}", msg),
+            Error::CorruptModel { path, reason } => write!(
+                f,
+                "Corrupt model file {}: {}. Delete it and try again.",
+                path.display(),
+                reason
+            ),
+            Error::ModelNotFound { path, searched } => {
+                write!(f, "Model not found: {}", path.display())?;
+                if !searched.is_empty() {
+                    let searched: Vec<_> = searched.iter().map(|p| p.display().to_string()).collect();
+                    write!(f, " (looked in {})", searched.join(", "))?;
+                }
+                if model::is_offline() {
+                    return write!(f, ". Downloads are disabled (offline mode); place the model file there by hand");
+                }
+                write!(
+                    f,
+                    ". Only known models are downloaded automatically; download others with memo_stt::model::download_model or from https://huggingface.co/ggerganov/whisper.cpp"
+                )
+            }
+            Error::AudioTooShort { samples, min_samples } => write!(
+                f,
+                "Audio too short: {} samples, need at least {}",
+                samples, min_samples
+            ),
+            Error::Cancelled => write!(f, "Cancelled"),
+        }
     }
 }
 
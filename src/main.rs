@@ -3,7 +3,12 @@
 //! This binary provides a complete STT application with keyboard triggers.
 //! For library usage, see the examples directory.
 
-use memo_stt::SttEngine;
+use memo_stt::{SttEngine, Word};
+use memo_stt::postprocess::{RuleBasedProcessor, TranscriptProcessor};
+use memo_stt::capture::{
+    best_input_config, compute_rms, extend_buffer_mono_f32, extend_buffer_mono_i16, extend_buffer_mono_u16,
+    find_input_device_by_spec, select_input_device, AutoStop,
+};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use rdev::{listen, Event, EventType, Key};
 use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
@@ -24,19 +29,117 @@ macro_rules! println_ui_flush {
     }};
 }
 
-/// Minimum interval between `AUDIO_LEVELS:` lines (ms). `0` = no throttle (emit every callback / decoded frame).
-/// Set `MEMO_AUDIO_LEVELS_INTERVAL_MS` (e.g. `33` for ~30 fps) if stdout/IPC cannot keep up.
-static MEMO_AUDIO_LEVELS_INTERVAL_MS: std::sync::OnceLock<u64> = std::sync::OnceLock::new();
+/// Shape of the `AUDIO_LEVELS:` waveform, set by `--waveform-bands` and `--waveform-fps`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct WaveformConfig {
+    /// Bars per `AUDIO_LEVELS:` line
+    bands: usize,
+    /// Minimum interval between `AUDIO_LEVELS:` lines (ms). `0` = no throttle (emit every callback / decoded frame).
+    /// Without `--waveform-fps`, set `MEMO_AUDIO_LEVELS_INTERVAL_MS` (e.g. `33` for ~30 fps) if stdout/IPC cannot keep up.
+    interval_ms: u64,
+}
+
+const DEFAULT_WAVEFORM_BANDS: usize = 7;
+const MAX_WAVEFORM_BANDS: usize = 64;
+
+static WAVEFORM: std::sync::OnceLock<WaveformConfig> = std::sync::OnceLock::new();
+
+fn waveform() -> WaveformConfig {
+    *WAVEFORM.get_or_init(|| WaveformConfig::from_args(None, None))
+}
+
+impl WaveformConfig {
+    /// Values of `--waveform-bands` / `--waveform-fps` (`0` fps = unthrottled); invalid or
+    /// missing values fall back to 7 bands and `MEMO_AUDIO_LEVELS_INTERVAL_MS`.
+    fn from_args(bands: Option<&str>, fps: Option<&str>) -> Self {
+        let bands = match bands.map(|b| b.trim().parse::<usize>()) {
+            Some(Ok(n)) if (1..=MAX_WAVEFORM_BANDS).contains(&n) => n,
+            Some(_) => {
+                eprintln!("Warning: --waveform-bands must be 1-{}, using {}", MAX_WAVEFORM_BANDS, DEFAULT_WAVEFORM_BANDS);
+                DEFAULT_WAVEFORM_BANDS
+            }
+            None => DEFAULT_WAVEFORM_BANDS,
+        };
+        let env_interval = || {
+            std::env::var("MEMO_AUDIO_LEVELS_INTERVAL_MS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0)
+        };
+        let interval_ms = match fps.map(|f| f.trim().parse::<u64>()) {
+            Some(Ok(0)) => 0,
+            Some(Ok(fps)) => (1000 / fps).max(1),
+            Some(Err(_)) => {
+                eprintln!("Warning: --waveform-fps must be a number, ignoring");
+                env_interval()
+            }
+            None => env_interval(),
+        };
+        Self { bands, interval_ms }
+    }
+}
 
 fn memo_audio_levels_interval_ms() -> u64 {
-    *MEMO_AUDIO_LEVELS_INTERVAL_MS.get_or_init(|| {
-        std::env::var("MEMO_AUDIO_LEVELS_INTERVAL_MS")
-            .ok()
-            .and_then(|s| s.parse().ok())
-            .unwrap_or(0)
+    waveform().interval_ms
+}
+
+/// Default for `MEMO_APP_CONTEXT_TEMPLATE`
+const DEFAULT_APP_CONTEXT_TEMPLATE: &str = "You are transcribing for {app}. The current window is: {title}.";
+
+/// How much of the focused app goes into the decoding prompt (`MEMO_APP_CONTEXT`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AppContextMode {
+    /// App name and window title (default)
+    Full,
+    /// App name only; window titles are often noise (document names, URLs, chat previews)
+    AppOnly,
+    /// Leave the app out of the prompt
+    Off,
+}
+
+/// Sentence describing the focused app that starts the decoding prompt.
+/// Configured with `MEMO_APP_CONTEXT` (`full`, `app` or `off`) and `MEMO_APP_CONTEXT_TEMPLATE`.
+#[derive(Debug, Clone)]
+struct AppContextPrompt {
+    mode: AppContextMode,
+    template: String,
+}
+
+static APP_CONTEXT_PROMPT: std::sync::OnceLock<AppContextPrompt> = std::sync::OnceLock::new();
+
+fn app_context_prompt() -> &'static AppContextPrompt {
+    APP_CONTEXT_PROMPT.get_or_init(|| {
+        let mode = match std::env::var("MEMO_APP_CONTEXT").unwrap_or_default().trim().to_lowercase().as_str() {
+            "off" | "0" | "false" => AppContextMode::Off,
+            "app" => AppContextMode::AppOnly,
+            _ => AppContextMode::Full,
+        };
+        let template = std::env::var("MEMO_APP_CONTEXT_TEMPLATE")
+            .unwrap_or_else(|_| DEFAULT_APP_CONTEXT_TEMPLATE.to_string());
+        AppContextPrompt { mode, template }
     })
 }
 
+impl AppContextPrompt {
+    /// Fill `{app}` and `{title}` into the template. Sentences mentioning `{title}` are left
+    /// out when there is no title (or titles are disabled); `None` if nothing is left.
+    fn render(&self, app_name: &str, window_title: &str) -> Option<String> {
+        if self.mode == AppContextMode::Off || app_name.is_empty() || app_name == "Unknown" {
+            return None;
+        }
+        let title = if self.mode == AppContextMode::Full { window_title.trim() } else { "" };
+        let rendered: String = self
+            .template
+            .split_inclusive(['.', '!', '?'])
+            .filter(|sentence| !title.is_empty() || !sentence.contains("{title}"))
+            .collect::<String>()
+            .replace("{app}", app_name)
+            .replace("{title}", title);
+        let rendered = rendered.trim();
+        (!rendered.is_empty()).then(|| rendered.to_string())
+    }
+}
+
 fn should_emit_audio_levels_throttled(last_sent: &mut Option<Instant>, interval_ms: u64) -> bool {
     if interval_ms == 0 {
         return true;
@@ -55,10 +158,45 @@ fn should_emit_audio_levels_throttled(last_sent: &mut Option<Instant>, interval_
     }
 }
 
+/// Where transcribed text is injected (`--inject-target`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InjectionTarget {
+    /// Whatever window is frontmost when the transcript is ready (default)
+    Frontmost,
+    /// The app that was frontmost when recording started, re-focused before pasting
+    /// (for overlays that steal focus while recording; macOS only)
+    RecordingStart,
+}
+
+impl InjectionTarget {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "frontmost" => Some(InjectionTarget::Frontmost),
+            "recording-start" | "start" => Some(InjectionTarget::RecordingStart),
+            _ => None,
+        }
+    }
+}
+
+static INJECTION_TARGET: std::sync::OnceLock<InjectionTarget> = std::sync::OnceLock::new();
+/// App captured by [`remember_injection_target`] for [`InjectionTarget::RecordingStart`]
+static RECORDING_TARGET_APP: Mutex<Option<String>> = Mutex::new(None);
+
+/// Call when a recording starts: remembers the frontmost app if injection targets it.
+fn remember_injection_target() {
+    if INJECTION_TARGET.get() != Some(&InjectionTarget::RecordingStart) {
+        return;
+    }
+    let app = app_detection::get_active_application().ok().filter(|a| !a.is_empty() && a != "Unknown");
+    *RECORDING_TARGET_APP.lock().unwrap() = app;
+}
+
 #[cfg(feature = "binary")]
 mod ble;
 #[cfg(feature = "binary")]
 mod opus_decoder;
+#[cfg(feature = "binary")]
+mod pcm_input;
 
 /// Trailing phrases often triggered by button/PTT click sounds — strip from end of transcript.
 const SIGN_OFF_PHRASES: &[&str] = &[
@@ -136,6 +274,52 @@ fn strip_leading_dash_space(text: &str) -> String {
     }
 }
 
+/// Build the `processedText` for a FINAL event: rule-based cleanup (fillers, capitalization)
+/// followed by the push-to-talk specific strip passes. `rawTranscript` stays verbatim.
+fn process_transcript(text: &str) -> String {
+    static PROCESSOR: std::sync::OnceLock<RuleBasedProcessor> = std::sync::OnceLock::new();
+    let cleaned = PROCESSOR.get_or_init(RuleBasedProcessor::default).process(text);
+    strip_leading_dash_space(&strip_trailing_signoffs(&strip_periods_from_short_phrases(&cleaned)))
+}
+
+/// Set by `--word-timestamps`: add a `words` array to FINAL events
+static WORD_TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+
+/// Transcribe `samples` for a FINAL event. With `--word-timestamps` the text is built from
+/// whisper's word timings, which are returned for [`add_final_words`].
+fn transcribe_final(engine: &mut SttEngine, samples: &[i16]) -> memo_stt::Result<(String, Option<Vec<Word>>)> {
+    if !WORD_TIMESTAMPS.load(Ordering::Relaxed) {
+        return engine.transcribe(samples).map(|text| (text, None));
+    }
+    let words = engine.transcribe_words(samples)?;
+    let text = words.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" ");
+    Ok((text, Some(words)))
+}
+
+/// Add `words: [{ text, start_ms, end_ms, probability }]` to a FINAL event
+fn add_final_words(json_output: &mut serde_json::Value, words: Option<&[Word]>) {
+    let Some(words) = words else { return };
+    json_output["words"] = words
+        .iter()
+        .map(|w| json!({
+            "text": w.text,
+            "start_ms": w.start_ms(),
+            "end_ms": w.end_ms(),
+            "probability": w.probability,
+        }))
+        .collect();
+}
+
+/// Last 200 characters of the previous transcript, used as decoding context. Cut on a char
+/// boundary so accented or CJK text from a multilingual model can't panic the slice.
+fn prompt_context(prev: &str) -> &str {
+    const MAX_CHARS: usize = 200;
+    match prev.char_indices().rev().nth(MAX_CHARS - 1) {
+        Some((start, _)) => &prev[start..],
+        None => prev,
+    }
+}
+
 /// Join streaming transcription segments with proper sentence boundaries.
 /// Ensures each non-final segment ends with punctuation so sentences don't run together.
 fn join_segments(parts: &[String]) -> String {
@@ -159,10 +343,11 @@ fn join_segments(parts: &[String]) -> String {
 }
 
 // Calculate audio levels for waveform visualization
-// Returns 7 normalized levels (0.0-1.0) for the 7 bars
+// Returns one normalized level (0.0-1.0) per bar (`--waveform-bands`, 7 by default)
 fn calculate_audio_levels(samples: &[i16]) -> Vec<f32> {
+    let bands = waveform().bands;
     if samples.is_empty() {
-        return vec![0.0; 7];
+        return vec![0.0; bands];
     }
     
     // Calculate RMS (Root Mean Square) for audio level
@@ -178,18 +363,34 @@ fn calculate_audio_levels(samples: &[i16]) -> Vec<f32> {
     // Apply exponential scaling for better visual response
     let scaled = normalized.powf(0.4);
     
-    // Create 7 bands with symmetric weighting (center bars higher, edges taper down)
-    let weights = vec![0.6, 0.8, 0.95, 1.0, 0.95, 0.8, 0.6];
-    weights.into_iter()
+    band_weights(bands).into_iter()
         .map(|w| (scaled * w).min(1.0))
         .collect()
 }
 
+/// Symmetric weighting for `bands` bars: center bars higher, edges taper down.
+/// 7 bands give the original 0.6, 0.8, 0.95, 1.0, 0.95, 0.8, 0.6; other counts interpolate
+/// that profile.
+fn band_weights(bands: usize) -> Vec<f32> {
+    const PROFILE: [f32; 4] = [1.0, 0.95, 0.8, 0.6]; // center to edge
+    let center = (bands as f32 - 1.0) / 2.0;
+    (0..bands)
+        .map(|i| {
+            let distance = if center > 0.0 { (i as f32 - center).abs() / center } else { 0.0 };
+            let pos = distance * (PROFILE.len() - 1) as f32;
+            let lower = (pos.floor() as usize).min(PROFILE.len() - 2);
+            let frac = pos - lower as f32;
+            PROFILE[lower] + (PROFILE[lower + 1] - PROFILE[lower]) * frac
+        })
+        .collect()
+}
+
 // Calculate audio levels for BLE waveform overlay (0.0–1.0 per bar).
 // Calibrated for firmware 20ms frames / current PDM gain; tune via env if needed.
 fn calculate_audio_levels_ble(samples: &[i16]) -> Vec<f32> {
+    let bands = waveform().bands;
     if samples.is_empty() {
-        return vec![0.0; 7];
+        return vec![0.0; bands];
     }
 
     let sum_squares: i64 = samples.iter().map(|&s| (s as i64).pow(2)).sum();
@@ -208,8 +409,7 @@ fn calculate_audio_levels_ble(samples: &[i16]) -> Vec<f32> {
     let normalized = ((rms / threshold) * gain).min(1.0);
 
     let scaled = normalized.powf(0.4);
-    let weights = vec![0.6, 0.8, 0.95, 1.0, 0.95, 0.8, 0.6];
-    weights.into_iter().map(|w| (scaled * w).min(1.0)).collect()
+    band_weights(bands).into_iter().map(|w| (scaled * w).min(1.0)).collect()
 }
 #[cfg(not(target_os = "macos"))]
 use enigo::{Enigo, KeyboardControllable, Key as EnigoKey};
@@ -217,106 +417,86 @@ use enigo::{Enigo, KeyboardControllable, Key as EnigoKey};
 // Default trigger key (can be overridden via --hotkey argument)
 const DEFAULT_TRIGGER_KEY: Key = Key::Function;
 
-/// Resolve input device: "default", numeric index, or substring name match (e.g. "AirPods", "External Microphone").
-fn find_input_device_by_spec(host: &cpal::Host, spec: &str) -> Option<cpal::Device> {
-    let spec = spec.trim();
-    if spec.is_empty() || spec.eq_ignore_ascii_case("default") {
-        return host.default_input_device();
-    }
-    let devices: Vec<cpal::Device> = match host.input_devices() {
-        Ok(iter) => iter.collect(),
-        Err(_) => return host.default_input_device(),
-    };
-    if let Ok(idx) = spec.parse::<usize>() {
-        if idx < devices.len() {
-            return Some(devices[idx].clone());
+/// Hotkey recordings quieter than this RMS (i16 scale) are dead-air taps and skip the model
+const DEAD_AIR_RMS: f32 = 100.0;
+
+/// Hotkey listener restart delays: doubled after each failure, reset once it has run a while
+const LISTENER_MIN_BACKOFF: std::time::Duration = std::time::Duration::from_secs(1);
+const LISTENER_MAX_BACKOFF: std::time::Duration = std::time::Duration::from_secs(30);
+const LISTENER_STABLE_AFTER: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Run the global keyboard listener, restarting it with backoff if it stops (e.g. the
+/// accessibility permission was revoked). Each failure is reported on stderr and as a
+/// `HOTKEY_ERROR:` line so front-ends can tell the user why the hotkey stopped working.
+fn listen_with_restart(on_event: impl Fn(Event) + Send + Sync + 'static) {
+    let on_event = Arc::new(on_event);
+    let mut backoff = LISTENER_MIN_BACKOFF;
+    loop {
+        let started = Instant::now();
+        let handler = on_event.clone();
+        let error = match listen(move |event| handler(event)) {
+            Ok(()) => "listener stopped".to_string(),
+            Err(e) => format!("{:?}", e),
+        };
+        if started.elapsed() >= LISTENER_STABLE_AFTER {
+            backoff = LISTENER_MIN_BACKOFF;
         }
-        return host.default_input_device();
-    }
-    let spec_lower = spec.to_lowercase();
-    for dev in &devices {
-        if let Ok(name) = dev.name() {
-            if name.to_lowercase().contains(&spec_lower) {
-                return Some(dev.clone());
-            }
+        eprintln!("❌ Hotkey listener failed: {} (restarting in {}s)", error, backoff.as_secs());
+        let hint = listener_failure_hint();
+        if let Some(hint) = hint {
+            eprintln!("   {}", hint);
         }
+        println_ui_flush!("HOTKEY_ERROR:{}", json!({
+            "error": error,
+            "hint": hint,
+            "retryInMs": backoff.as_millis() as u64,
+        }));
+        std::thread::sleep(backoff);
+        backoff = (backoff * 2).min(LISTENER_MAX_BACKOFF);
     }
-    host.default_input_device()
 }
 
-/// Prefer mono input and the highest sample rate up to 48 kHz (better quality when the device allows it).
-fn best_input_config(device: &cpal::Device) -> Result<cpal::SupportedStreamConfig, Box<dyn std::error::Error>> {
-    use cpal::SampleRate;
-    let default = device.default_input_config()?;
-    let Ok(mut configs) = device.supported_input_configs() else {
-        return Ok(default);
-    };
-    let candidates: Vec<cpal::SupportedStreamConfigRange> = configs.collect();
-    if candidates.is_empty() {
-        return Ok(default);
-    }
-    let pick_mono = candidates
-        .iter()
-        .filter(|c| c.channels() == 1)
-        .max_by_key(|c| c.max_sample_rate().0);
-    let pick_any = candidates
-        .iter()
-        .max_by_key(|c| c.max_sample_rate().0);
-    let range = pick_mono.or(pick_any).unwrap_or(&candidates[0]);
-    let max_sr = range.max_sample_rate().0;
-    let min_sr = range.min_sample_rate().0;
-    let target = max_sr.min(48_000).max(min_sr);
-    Ok(range.with_sample_rate(SampleRate(target)))
+/// What the user can do about a failed or deaf hotkey listener
+#[cfg(target_os = "macos")]
+fn listener_failure_hint() -> Option<&'static str> {
+    (!accessibility_trusted()).then_some(
+        "Grant this app (or your terminal) Accessibility access in System Settings > Privacy & Security > Accessibility, then restart it.",
+    )
 }
 
-fn extend_buffer_mono_i16(buf: &mut Vec<i16>, data: &[i16], channels: usize) {
-    match channels {
-        1 => buf.extend_from_slice(data),
-        n if n > 1 => {
-            for frame in data.chunks_exact(n) {
-                let sum: i32 = frame.iter().map(|&s| s as i32).sum();
-                buf.push((sum / n as i32) as i16);
-            }
-        }
-        _ => {}
-    }
+#[cfg(target_os = "linux")]
+fn listener_failure_hint() -> Option<&'static str> {
+    is_wayland_session(std::env::var("XDG_SESSION_TYPE").ok().as_deref(), std::env::var_os("WAYLAND_DISPLAY").is_some()).then_some(
+        "Global hotkeys don't work in a Wayland session. Log in to an X11 session (e.g. \"Ubuntu on Xorg\"), or start and stop recording from the desktop app or a BLE device.",
+    )
 }
 
-fn extend_buffer_mono_f32(buf: &mut Vec<i16>, data: &[f32], channels: usize) {
-    match channels {
-        1 => {
-            for &s in data {
-                buf.push((s.clamp(-1.0, 1.0) * 32767.0) as i16);
-            }
-        }
-        n if n > 1 => {
-            for frame in data.chunks_exact(n) {
-                let mut acc = 0.0f32;
-                for &s in frame {
-                    acc += s.clamp(-1.0, 1.0);
-                }
-                buf.push(((acc / n as f32) * 32767.0) as i16);
-            }
-        }
-        _ => {}
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn listener_failure_hint() -> Option<&'static str> {
+    None
+}
+
+/// Whether this is a Wayland session, where the X11 key grab `rdev` uses never sees a key
+/// press (even with XWayland's `DISPLAY` set)
+#[cfg(any(target_os = "linux", test))]
+fn is_wayland_session(session_type: Option<&str>, wayland_display: bool) -> bool {
+    match session_type.map(str::trim) {
+        Some(t) if t.eq_ignore_ascii_case("wayland") => true,
+        Some(t) if t.eq_ignore_ascii_case("x11") => false,
+        _ => wayland_display,
     }
 }
 
-fn extend_buffer_mono_u16(buf: &mut Vec<i16>, data: &[u16], channels: usize) {
-    match channels {
-        1 => {
-            for &s in data {
-                buf.push(((s as i32) - 32768) as i16);
-            }
-        }
-        n if n > 1 => {
-            for frame in data.chunks_exact(n) {
-                let sum: i32 = frame.iter().map(|&s| (s as i32) - 32768).sum();
-                buf.push((sum / n as i32) as i16);
-            }
-        }
-        _ => {}
+/// Whether macOS lets this process observe global key events. Without Accessibility access
+/// the listener may start but never see a key press.
+#[cfg(target_os = "macos")]
+fn accessibility_trusted() -> bool {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
     }
+    // SAFETY: takes no arguments and only reads the process's trust state
+    unsafe { AXIsProcessTrusted() }
 }
 
 fn audio_levels_interleaved_i16(data: &[i16], ch: usize) -> Vec<f32> {
@@ -403,15 +583,8 @@ enum KeyEvent {
     StartRecording,
     StopRecording,
     ToggleLock,
-}
-
-/// Compute RMS (root mean square) of i16 samples for VAD.
-fn compute_rms(samples: &[i16]) -> f32 {
-    if samples.is_empty() {
-        return 0.0;
-    }
-    let sum_squares: i64 = samples.iter().map(|&s| (s as i64).pow(2)).sum();
-    (sum_squares as f32 / samples.len() as f32).sqrt()
+    /// Stop and discard the current recording without transcribing
+    CancelRecording,
 }
 
 // Calculate the rate of increase in realtime factor per second of audio
@@ -441,6 +614,12 @@ fn inject_text(text: &str, press_enter: bool) -> Result<(), Box<dyn std::error::
         return Ok(());
     }
 
+    if let Some(app) = RECORDING_TARGET_APP.lock().unwrap().take() {
+        if let Err(e) = app_detection::activate_application(&app) {
+            eprintln!("⚠️  Could not re-focus {}: {}", app, e);
+        }
+    }
+
     #[cfg(target_os = "macos")]
     {
         use std::io::Write;
@@ -501,10 +680,30 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
     println!("Starting BLE audio mode...");
     
     // Initialize Opus decoder (preserved during reconnection)
-    let mut decoder = OpusDecoder::new(16000, 20)?;
+    // Devices that negotiate other frame sizes: MEMO_BLE_FRAME_MS (10, 20, 40 or 60)
+    let frame_ms = std::env::var("MEMO_BLE_FRAME_MS")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(opus_decoder::DEFAULT_FRAME_DURATION_MS);
+    let mut decoder = OpusDecoder::new(16000, frame_ms)?;
     
     // Initialize BLE receiver
-    let mut ble_receiver = BleAudioReceiver::new().await?;
+    let mut ble_receiver = BleAudioReceiver::new(ble_config_from_env()).await?;
+    // Report link state changes (connected, lost, reconnecting, failed) to the UI
+    let mut connection_events = ble_receiver.connection_events();
+    tokio::spawn(async move {
+        loop {
+            match connection_events.recv().await {
+                Ok(event) => println_ui_flush!("BLE_STATE:{}", ble_state_json(&event)),
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+    // Optional keep-alive for BLE stacks that drop idle peripherals (MEMO_BLE_KEEPALIVE_SECS)
+    if let Some(secs) = std::env::var("MEMO_BLE_KEEPALIVE_SECS").ok().and_then(|v| v.parse::<u64>().ok()) {
+        ble_receiver.set_keep_alive((secs > 0).then(|| std::time::Duration::from_secs(secs)));
+    }
     
     // DO NOT auto-connect - wait for CONNECT_UID command from Electron
     // This prevents duplicate connections
@@ -535,13 +734,7 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
     let build_prompt = |app_name: String, window_title: String, vocab: &Vocabulary| -> Option<String> {
         let mut parts = Vec::new();
         
-        if !app_name.is_empty() && app_name != "Unknown" {
-            if !window_title.is_empty() {
-                parts.push(format!("You are transcribing for {}. The current window is: {}.", app_name, window_title));
-            } else {
-                parts.push(format!("You are transcribing for {}.", app_name));
-            }
-        }
+        parts.extend(app_context_prompt().render(&app_name, &window_title));
         
         if !vocab.app_names.is_empty() {
             parts.push(format!("Voice commands: open {}.", vocab.app_names.join(", ")));
@@ -567,6 +760,12 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
     
     // Channel for connection commands from stdin handler
     let (connect_tx, mut connect_rx) = tokio::sync::mpsc::unbounded_channel::<Option<String>>();
+    // Commands for the device (BLE_CONTROL:<command>, transcription acknowledgements)
+    let (control_tx, mut control_rx) = tokio::sync::mpsc::unbounded_channel::<ble::ControlCommand>();
+    // Opt-in (MEMO_BLE_ACK_LED=1): flash the device LED after each transcription
+    let ack_led = std::env::var("MEMO_BLE_ACK_LED")
+        .map(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
     
     // Spawn thread to read commands from stdin
     let press_enter_clone = press_enter_after_paste.clone();
@@ -574,6 +773,7 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
     let input_source_clone = input_source.clone();
     let vocabulary_clone = vocabulary.clone();
     let connect_tx_for_stdin = connect_tx.clone();
+    let control_tx_for_stdin = control_tx.clone();
     std::thread::spawn(move || {
         use std::io::{self, BufRead};
         let stdin = io::stdin();
@@ -593,14 +793,21 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                 } else if let Some(uid) = cmd.strip_prefix("CONNECT_UID:") {
                     let uid = uid.trim().to_uppercase();
                     eprintln!("MIC: Connecting to device with UID: {}", uid);
-                    // Format device name: memo_XXXXX
-                    let device_name = format!("memo_{}", uid);
+                    // Format device name: memo_XXXXX (addresses are passed through as-is)
+                    let device_name = if ble::is_device_address(&uid) { uid } else { format!("memo_{}", uid) };
                     // Send connection request via channel
                     let _ = connect_tx_for_stdin.send(Some(device_name));
                 } else if cmd.trim() == "DISCONNECT" {
                     eprintln!("MIC: Disconnecting from BLE device");
                     // Send disconnect request via channel (None means disconnect)
                     let _ = connect_tx_for_stdin.send(None);
+                } else if let Some(name) = cmd.strip_prefix("BLE_CONTROL:") {
+                    match ble::ControlCommand::parse(name) {
+                        Some(command) => {
+                            let _ = control_tx_for_stdin.send(command);
+                        }
+                        None => eprintln!("MIC: Unknown BLE control command: {}", name.trim()),
+                    }
                 } else if let Some(value) = cmd.strip_prefix("VOCAB:") {
                     if let Ok(vocab_json) = serde_json::from_str::<serde_json::Value>(value.trim()) {
                         let mut vocab = vocabulary_clone.lock().unwrap();
@@ -635,9 +842,24 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
     let is_recording_clone = is_recording.clone();
     let audio_buffer_clone = audio_buffer.clone();
     
-    use ble::NotificationResult;
+    use ble::{ConnectionEvent, ControlAction, ControlDebounce, DisconnectReason, NotificationResult};
     use futures::StreamExt;
     use tokio::time::timeout;
+
+    // Filters duplicated / bouncing START-END codes from a flaky link; kept across reconnects
+    // so it stays in sync with is_recording
+    let debounce_ms: u64 = std::env::var("MEMO_BLE_DEBOUNCE_MS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(ble::DEFAULT_DEBOUNCE_WINDOW.as_millis() as u64);
+    let mut control_debounce = ControlDebounce::new(std::time::Duration::from_millis(debounce_ms));
+
+    // Opt-in (MEMO_BLE_AUTO_RECONNECT=1): when the link is lost, keep reconnecting to the same
+    // device in the background instead of leaving BLE mode
+    let auto_reconnect = std::env::var("MEMO_BLE_AUTO_RECONNECT")
+        .map(|v| v.trim() == "1" || v.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    let mut reconnect_pending = false;
     
     // Outer loop: wait for CONNECT_UID command, then connect and process notifications
     loop {
@@ -650,12 +872,25 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
             }
         }
         
-        // Wait for CONNECT_UID command - DO NOT auto-connect
-        let device_name = loop {
-            match connect_rx.recv().await {
+        // Wait for CONNECT_UID command - DO NOT auto-connect (unless reconnecting after a lost
+        // link, where a CONNECT_UID / disconnect command still takes over)
+        let command = if reconnect_pending {
+            reconnect_pending = false;
+            eprintln!("Reconnecting to last BLE device...");
+            tokio::select! {
+                // Only returns once connected
+                _ = ble_receiver.connect_with_auto_reconnect(None) => None,
+                command = connect_rx.recv() => Some(command),
+            }
+        } else {
+            Some(connect_rx.recv().await)
+        };
+
+        if let Some(command) = command {
+            let device_name = match command {
                 Some(Some(name)) => {
                     // Got connect command
-                    break Some(name);
+                    name
                 }
                 Some(None) => {
                     // Got disconnect command
@@ -668,22 +903,26 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                     // Channel closed
                     return Ok(());
                 }
+            };
+
+            // Connect to device
+            eprintln!("Connecting to device: {}", device_name);
+            // A device picker may pass the chosen device's address instead of its name
+            let connected = if ble::is_device_address(&device_name) {
+                ble_receiver.connect_by_address(&device_name).await
+            } else {
+                ble_receiver.connect(Some(&device_name)).await
+            };
+            if let Err(e) = connected {
+                eprintln!("Failed to connect to device {}: {}", device_name, e);
+                println!("DISCONNECTED:connection_failed");
+                continue; // Wait for next CONNECT_UID command
             }
-        };
-        
-        let device_name = match device_name {
-            Some(name) => name,
-            None => continue,
-        };
-        
-        // Connect to device
-        eprintln!("Connecting to device: {}", device_name);
-        if let Err(e) = ble_receiver.connect(Some(&device_name)).await {
-            eprintln!("Failed to connect to device {}: {}", device_name, e);
-            println!("DISCONNECTED:connection_failed");
-            continue; // Wait for next CONNECT_UID command
         }
         
+        // Drop device commands queued while disconnected (e.g. stale acknowledgements)
+        while control_rx.try_recv().is_ok() {}
+
         // Get the notification stream
         let mut notifications = match ble_receiver.notifications().await {
             Ok(stream) => stream,
@@ -717,8 +956,8 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
         recording_health_check_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
         let mut recording_health_failure_count: u32 = 0;
 
-        // Track expected bundle index for packet-loss detection; use FEC when previous packet was lost.
-        let mut expected_bundle_index: Option<u8> = None;
+        // Track the bundle index for packet-loss detection; use FEC when previous packet was lost.
+        let mut bundle_sequence = opus_decoder::BundleSequence::default();
         let last_audio_level_sent_ble = Arc::new(Mutex::new(None::<Instant>));
 
         loop {
@@ -733,6 +972,12 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
             
             // Use select! to monitor notifications, connection health, and connection commands
             tokio::select! {
+                // Commands for the device
+                Some(command) = control_rx.recv() => {
+                    if let Err(e) = ble_receiver.send_command(command).await {
+                        eprintln!("BLE control command {:?} failed: {:#}", command, e);
+                    }
+                }
                 // Low-frequency poll while idle: a small GATT read to confirm the link is alive.
                 _ = poll_interval.tick() => {
                     if ble_receiver.poll_link().await {
@@ -766,7 +1011,13 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                             // Need to reconnect - break inner loop
                             eprintln!("Reconnecting to device: {}", device_name);
                             ble_receiver.disconnect().await.ok();
-                            if let Err(e) = ble_receiver.connect(Some(&device_name)).await {
+                            ble_receiver.emit_connection_event(ConnectionEvent::Reconnecting);
+                            let connected = if ble::is_device_address(&device_name) {
+                                ble_receiver.connect_by_address(&device_name).await
+                            } else {
+                                ble_receiver.connect(Some(&device_name)).await
+                            };
+                            if let Err(e) = connected {
                                 eprintln!("Failed to connect: {}", e);
                                 println!("DISCONNECTED:connection_failed");
                             }
@@ -828,14 +1079,23 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                             match ble_receiver.process_notification(notification) {
                                 NotificationResult::Control(0x01) => {
                                     // RESP_SPEECH_START - Button pressed, start recording
+                                    if control_debounce.on_control(0x01) != ControlAction::Start {
+                                        continue;
+                                    }
                                     if !is_recording_clone.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
                                         continue; // Already recording
                                     }
                                     println_ui_flush!("🎤 Recording... (button pressed)");
+                                    remember_injection_target();
                                     audio_buffer_clone.lock().unwrap().clear();
+                                    decoder.reset_stats();
                                 }
                                 NotificationResult::Control(0x02) => {
                                     // RESP_SPEECH_END - Button pressed again, stop recording and transcribe
+                                    let action = control_debounce.on_control(0x02);
+                                    if action == ControlAction::Ignore {
+                                        continue;
+                                    }
                                     if !is_recording_clone.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
                                         continue; // Not recording
                                     }
@@ -845,6 +1105,16 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                                         let mut buf = audio_buffer_clone.lock().unwrap();
                                         std::mem::take(&mut *buf)
                                     };
+                                    let stats = decoder.stats();
+                                    debug!(
+                                        "BLE decode: {} frames, {} lost ({} concealed), {} samples",
+                                        stats.frames_decoded, stats.frames_lost, stats.plc_frames, stats.samples_produced
+                                    );
+
+                                    if action == ControlAction::Discard {
+                                        println_ui_flush!("⏹️  Stopped (too short, discarded)");
+                                        continue;
+                                    }
                                     
                                     if !samples.is_empty() {
                                         println_ui_flush!("⏹️  Stopped ({} samples, {:.2}s)", samples.len(), samples.len() as f32 / 16000.0);
@@ -855,11 +1125,10 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                                         
                                         // Encode in a separate thread to avoid blocking transcription
                                         std::thread::spawn(move || {
-                            use opus_decoder::OpusEncoder;
                             #[cfg(feature = "binary")]
                             use base64::{Engine as _, engine::general_purpose::STANDARD};
                             
-                            match OpusEncoder::new(16000, 20) {
+                            match recording_encoder() {
                                 Ok(mut encoder_for_thread) => {
                                     match encoder_for_thread.encode_buffer(&samples_for_encoding) {
                                         Ok(opus_data) => {
@@ -920,10 +1189,15 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                         let press_enter_clone = press_enter_after_paste.clone();
                         let no_inject_clone = no_inject_flag.clone();
                         let vocabulary_for_thread = vocabulary.clone();
+                        let control_tx_for_thread = control_tx.clone();
                         let sample_count = samples.len();
                         let audio_duration = sample_count as f32 / 16000.0;
                         
                         std::thread::spawn(move || {
+                            if memo_stt::audio::is_silent(&samples, DEAD_AIR_RMS) {
+                                println!("📝 (no speech detected)");
+                                return;
+                            }
                             println!("🔄 Transcribing...");
                             let mut eng = engine_for_thread.lock().unwrap();
                             
@@ -934,8 +1208,8 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                             eng.set_prompt(prompt);
                             
                             let transcribe_start = Instant::now();
-                            match eng.transcribe(&samples) {
-                                Ok(text) => {
+                            match transcribe_final(&mut eng, &samples) {
+                                Ok((text, words)) => {
                                     let transcribe_time = transcribe_start.elapsed();
                                     let realtime_factor = audio_duration / transcribe_time.as_secs_f32();
                                     
@@ -952,8 +1226,8 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                                         println!("📝 (no speech detected)");
                                     } else {
                                         let (app_name, window_title) = app_detection::get_application_context();
-                                        let processed_text = strip_leading_dash_space(&strip_trailing_signoffs(&strip_periods_from_short_phrases(&text)));
-                                        let json_output = json!({
+                                        let processed_text = process_transcript(&text);
+                                        let mut json_output = json!({
                                             "rawTranscript": text,
                                             "processedText": processed_text,
                                             "wasProcessedByLLM": false,
@@ -962,7 +1236,11 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                                                 "windowTitle": window_title
                                             }
                                         });
+                                        add_final_words(&mut json_output, words.as_deref());
                                         println!("FINAL: {}", json_output);
+                                        if ack_led {
+                                            let _ = control_tx_for_thread.send(ble::ControlCommand::FlashLed);
+                                        }
                                         
                                         // Only inject if not in Electron mode
                                         if !no_inject_clone.load(Ordering::Acquire) {
@@ -983,6 +1261,9 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
                                         }
                                     }
                                 }
+                                Err(memo_stt::Error::AudioTooShort { samples, min_samples }) => {
+                                    println!("⏱️  Too short ({:.1}s), hold longer", samples as f32 / min_samples as f32);
+                                }
                                 Err(e) => {
                                     eprintln!("❌ Error: {}", e);
                                 }
@@ -1018,27 +1299,24 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
 
                                     debug!("Received audio packet: bundle_index={}, size={} bytes", bundle_index, audio_data.len());
 
-                                    let decode_result = match expected_bundle_index {
-                                        None => {
-                                            expected_bundle_index = Some(bundle_index.wrapping_add(1));
-                                            decoder.decode_bundle(bundle_data)
-                                        }
-                                        Some(expected) => {
-                                            if bundle_index == expected {
-                                                expected_bundle_index = Some(bundle_index.wrapping_add(1));
-                                                decoder.decode_bundle(bundle_data)
-                                            } else {
-                                                // Gap: one or more packets lost
-                                                let lost = bundle_index.wrapping_sub(expected);
-                                                for _ in 0..lost.saturating_sub(1) {
-                                                    if let Ok(plc) = decoder.decode_plc() {
-                                                        audio_buffer_clone.lock().unwrap().extend_from_slice(&plc);
-                                                    }
-                                                }
-                                                expected_bundle_index = Some(bundle_index.wrapping_add(1));
-                                                decoder.decode_bundle_with_fec(bundle_data)
+                                    if bundle_sequence.is_stale(bundle_index) {
+                                        // Duplicate or reordered packet: its audio is already in the buffer
+                                        debug!("Dropping stale audio packet: bundle_index={}", bundle_index);
+                                        bundle_sequence.observe(bundle_index);
+                                        continue;
+                                    }
+                                    let lost = bundle_sequence.observe(bundle_index);
+                                    let decode_result = if lost == 0 {
+                                        decoder.decode_bundle(bundle_data)
+                                    } else {
+                                        // Gap: one or more packets lost. Conceal all but the last,
+                                        // which FEC in this packet recovers.
+                                        for _ in 0..lost - 1 {
+                                            if let Ok(plc) = decoder.decode_lost_frame() {
+                                                audio_buffer_clone.lock().unwrap().extend_from_slice(&plc);
                                             }
                                         }
+                                        decoder.decode_bundle_with_fec(bundle_data)
                                     };
 
                                     match decode_result {
@@ -1091,7 +1369,11 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
         // Stream ended or failed - disconnect and exit
         eprintln!("BLE device disconnected");
         println!("DISCONNECTED:connection_lost");
-        ble_receiver.disconnect().await.ok();
+        if auto_reconnect {
+            reconnect_pending = true;
+            continue;
+        }
+        ble_receiver.disconnect_with_reason(DisconnectReason::ConnectionLost).await.ok();
         break;
     }
     
@@ -1100,6 +1382,205 @@ async fn run_ble_audio_mode(engine: Arc<Mutex<SttEngine>>, no_inject: bool) -> R
     Ok(())
 }
 
+/// `--doctor`: print the library's setup checks plus the binary's own, failing if any check
+/// failed
+fn run_doctor() -> Result<(), Box<dyn std::error::Error>> {
+    use memo_stt::CheckStatus;
+
+    println!("memo-stt {} setup check\n", env!("CARGO_PKG_VERSION"));
+    #[allow(unused_mut)]
+    let mut checks = memo_stt::diagnostics();
+    #[cfg(target_os = "macos")]
+    checks.push(match listener_failure_hint() {
+        None => memo_stt::Check::new("accessibility", CheckStatus::Pass, "granted (hotkey and text injection work)"),
+        Some(hint) => memo_stt::Check::new("accessibility", CheckStatus::Fail, hint),
+    });
+    #[cfg(target_os = "linux")]
+    checks.push(match listener_failure_hint() {
+        None => memo_stt::Check::new("hotkey", CheckStatus::Pass, "X11 session (global hotkey works)"),
+        Some(hint) => memo_stt::Check::new("hotkey", CheckStatus::Fail, hint),
+    });
+    for check in &checks {
+        println!("{}", check);
+    }
+    match checks.iter().filter(|c| c.status == CheckStatus::Fail).count() {
+        0 => {
+            println!("\nReady to transcribe.");
+            Ok(())
+        }
+        failed => Err(format!("{} check(s) failed", failed).into()),
+    }
+}
+
+/// BLE device selection from the environment.
+/// Other hardware: MEMO_BLE_NAME_PREFIX, or MEMO_BLE_ADDRESS to skip scanning for a name
+#[cfg(feature = "binary")]
+fn ble_config_from_env() -> ble::BleConfig {
+    let mut ble_config = ble::BleConfig::default();
+    if let Ok(prefix) = std::env::var("MEMO_BLE_NAME_PREFIX") {
+        ble_config.name_pattern = prefix;
+    }
+    ble_config.device_address = std::env::var("MEMO_BLE_ADDRESS").ok().filter(|a| !a.trim().is_empty());
+    ble_config
+}
+
+/// `BLE_STATE:` payload for a connection state change, e.g.
+/// `{"state":"disconnected","reason":"connection_lost"}`
+#[cfg(feature = "binary")]
+fn ble_state_json(event: &ble::ConnectionEvent) -> serde_json::Value {
+    use ble::{ConnectionEvent, DisconnectReason};
+    match event {
+        ConnectionEvent::Connected { device_name } => json!({ "state": "connected", "deviceName": device_name }),
+        ConnectionEvent::Disconnected(reason) => json!({
+            "state": "disconnected",
+            "reason": match reason {
+                DisconnectReason::UserRequested => "user_requested",
+                DisconnectReason::ConnectionLost => "connection_lost",
+            },
+        }),
+        ConnectionEvent::Reconnecting => json!({ "state": "reconnecting" }),
+        ConnectionEvent::ConnectFailed { reason } => json!({ "state": "connect_failed", "reason": reason }),
+    }
+}
+
+/// `--scan-ble`: list nearby BLE devices (strongest first) for a device picker and exit.
+/// The `address` of each `DEVICE_FOUND:` line can be sent back as `CONNECT_UID:<address>`.
+#[cfg(feature = "binary")]
+async fn run_ble_scan(scan_secs: u64) -> Result<(), Box<dyn std::error::Error>> {
+    let receiver = ble::BleAudioReceiver::new(ble_config_from_env()).await?;
+    eprintln!("🔍 Scanning for BLE devices ({}s)...", scan_secs);
+    let devices = receiver.scan_devices(std::time::Duration::from_secs(scan_secs)).await?;
+    for device in &devices {
+        let event = json!({
+            "name": device.name,
+            "address": device.address,
+            "rssi": device.rssi,
+        });
+        println!("DEVICE_FOUND: {}", event);
+    }
+    println!("SCAN_COMPLETE");
+    Ok(())
+}
+
+/// Transcribe a saved Ogg-Opus recording (`--file recording.opus`) and print it like a live
+/// transcription
+#[cfg(feature = "binary")]
+fn run_ogg_file_mode(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let samples = if path == "-" {
+        // Ogg pages are read with seeking, so buffer stdin first
+        let mut data = Vec::new();
+        std::io::Read::read_to_end(&mut std::io::stdin(), &mut data)?;
+        opus_decoder::OpusDecoder::decode_ogg_reader(std::io::Cursor::new(data))?
+    } else {
+        opus_decoder::OpusDecoder::decode_ogg_file(path)?
+    };
+    println!("Decoded {} ({:.1}s)", path, samples.len() as f32 / 16000.0);
+
+    let mut engine = SttEngine::new_default(16000)?;
+    let (text, words) = transcribe_final(&mut engine, &samples)?;
+    let processed_text = process_transcript(&text);
+    let mut json_output = json!({
+        "rawTranscript": text,
+        "processedText": processed_text,
+        "wasProcessedByLLM": false,
+    });
+    add_final_words(&mut json_output, words.as_deref());
+    println!("FINAL: {}", json_output);
+    println!("📝 {}", text);
+    Ok(())
+}
+
+/// Opus encoder for the `AUDIO_DATA:` recording output, tuned by MEMO_OPUS_BITRATE (bits/s),
+/// MEMO_OPUS_COMPLEXITY (0-10) and MEMO_OPUS_VBR (0 for constant bitrate)
+#[cfg(feature = "binary")]
+fn recording_encoder() -> anyhow::Result<opus_decoder::OpusEncoder> {
+    let mut encoder = opus_decoder::OpusEncoder::new(16000, opus_decoder::DEFAULT_FRAME_DURATION_MS)?;
+    if let Ok(value) = std::env::var("MEMO_OPUS_BITRATE") {
+        encoder.set_bitrate(value.trim().parse().map_err(|_| anyhow::anyhow!("Invalid MEMO_OPUS_BITRATE {:?}", value))?)?;
+    }
+    if let Ok(value) = std::env::var("MEMO_OPUS_COMPLEXITY") {
+        encoder.set_complexity(value.trim().parse().map_err(|_| anyhow::anyhow!("Invalid MEMO_OPUS_COMPLEXITY {:?}", value))?)?;
+    }
+    if let Ok(value) = std::env::var("MEMO_OPUS_VBR") {
+        encoder.set_vbr(value.trim() == "1" || value.trim().eq_ignore_ascii_case("true"))?;
+    }
+    Ok(encoder)
+}
+
+/// Transcribe utterances from streamed 16kHz PCM (`--input stdin` / `--input ws://...`)
+#[cfg(feature = "binary")]
+fn run_pcm_input_mode(source: pcm_input::PcmSource, no_inject: bool) -> Result<(), Box<dyn std::error::Error>> {
+    use pcm_input::{SegmentEvent, Segmenter, PCM_SAMPLE_RATE};
+
+    println!("Loading Whisper model ({} Hz PCM input)...", PCM_SAMPLE_RATE);
+    let mut engine = SttEngine::new_default(PCM_SAMPLE_RATE)?;
+    println!("Warming up GPU...");
+    engine.warmup()?;
+    println!("Ready!");
+
+    let (tx, rx) = mpsc::channel::<Vec<i16>>();
+    let reader = pcm_input::spawn_reader(source, tx);
+    println!("\nTrigger: VAD (PCM input)");
+    println!("Speak to start recording, silence to transcribe.\n");
+
+    let mut segmenter = Segmenter::from_env();
+    let mut last_audio_level_sent = None::<Instant>;
+    let mut transcribe_utterance = |samples: Vec<i16>| {
+        println_ui_flush!("⏹️  Stopped");
+        match transcribe_final(&mut engine, &samples) {
+            Ok((text, _)) if text.trim().is_empty() => println!("📝 (no speech detected)"),
+            Ok((text, words)) => {
+                let (app_name, window_title) = app_detection::get_application_context();
+                let processed_text = process_transcript(&text);
+                let mut json_output = json!({
+                    "rawTranscript": text,
+                    "processedText": processed_text,
+                    "wasProcessedByLLM": false,
+                    "appContext": {
+                        "appName": app_name,
+                        "windowTitle": window_title
+                    }
+                });
+                add_final_words(&mut json_output, words.as_deref());
+                println!("FINAL: {}", json_output);
+                println!("📝 {}", text);
+                if !no_inject {
+                    match inject_text(&processed_text, false) {
+                        Ok(_) => println!("✅ Injected"),
+                        Err(e) => eprintln!("❌ Injection failed: {}", e),
+                    }
+                }
+            }
+            Err(memo_stt::Error::AudioTooShort { .. }) => println!("⏹️  Too short, discarded"),
+            Err(e) => eprintln!("❌ Error: {}", e),
+        }
+    };
+
+    for chunk in rx {
+        let recording = segmenter.is_recording();
+        if recording && should_emit_audio_levels_throttled(&mut last_audio_level_sent, memo_audio_levels_interval_ms()) {
+            println_ui_flush!("AUDIO_LEVELS:{}", json!(calculate_audio_levels(&chunk)));
+        }
+        for event in segmenter.push(&chunk) {
+            match event {
+                SegmentEvent::Started => {
+                    println_ui_flush!("🎤 Recording...");
+                    remember_injection_target();
+                }
+                SegmentEvent::Utterance(samples) => transcribe_utterance(samples),
+            }
+        }
+    }
+    if let Some(SegmentEvent::Utterance(samples)) = segmenter.finish() {
+        transcribe_utterance(samples);
+    }
+
+    match reader.join() {
+        Ok(result) => result.map_err(|e| e.into()),
+        Err(_) => Err("PCM input thread panicked".into()),
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Check INPUT_SOURCE environment variable
     let input_source = std::env::var("INPUT_SOURCE").unwrap_or_else(|_| "system".to_string());
@@ -1108,6 +1589,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = std::env::args().collect();
     let mut trigger_key = DEFAULT_TRIGGER_KEY;
     let mut no_inject = false;
+    let mut device_name: Option<String> = None;
+    let mut auto_stop = false;
+    let mut pcm_input_spec: Option<String> = None;
+    let mut ogg_file: Option<String> = None;
+    let mut waveform_bands: Option<String> = None;
+    let mut waveform_fps: Option<String> = None;
+    let mut doctor = false;
+    let mut scan_ble_secs: Option<u64> = None;
     
     for i in 0..args.len() {
         if args[i] == "--hotkey" && i + 1 < args.len() {
@@ -1120,6 +1609,77 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         } else if args[i] == "--no-inject" {
             no_inject = true;
             println!("Auto-injection disabled (Electron mode)");
+        } else if args[i] == "--device" && i + 1 < args.len() {
+            device_name = Some(args[i + 1].clone());
+        } else if args[i] == "--auto-stop" {
+            auto_stop = true;
+            println!("Auto-stop enabled (tap to start, stops after silence)");
+        } else if args[i] == "--inject-target" && i + 1 < args.len() {
+            match InjectionTarget::parse(&args[i + 1]) {
+                Some(target) => {
+                    let _ = INJECTION_TARGET.set(target);
+                }
+                None => eprintln!("Warning: Unknown inject target '{}', using frontmost", args[i + 1]),
+            }
+        } else if args[i] == "--input" && i + 1 < args.len() {
+            pcm_input_spec = Some(args[i + 1].clone());
+        } else if args[i] == "--file" && i + 1 < args.len() {
+            ogg_file = Some(args[i + 1].clone());
+        } else if args[i] == "--doctor" {
+            doctor = true;
+        } else if args[i] == "--scan-ble" {
+            // Optional scan duration in seconds (default 5)
+            scan_ble_secs = Some(args.get(i + 1).and_then(|v| v.parse().ok()).unwrap_or(5));
+        } else if args[i] == "--word-timestamps" {
+            WORD_TIMESTAMPS.store(true, Ordering::Relaxed);
+            println!("Word timestamps enabled (FINAL events include words)");
+        } else if args[i] == "--waveform-bands" && i + 1 < args.len() {
+            waveform_bands = Some(args[i + 1].clone());
+        } else if args[i] == "--waveform-fps" && i + 1 < args.len() {
+            waveform_fps = Some(args[i + 1].clone());
+        }
+    }
+    let _ = WAVEFORM.set(WaveformConfig::from_args(waveform_bands.as_deref(), waveform_fps.as_deref()));
+
+    if doctor {
+        return run_doctor();
+    }
+
+    if let Some(secs) = scan_ble_secs {
+        #[cfg(feature = "binary")]
+        {
+            let rt = tokio::runtime::Runtime::new()?;
+            return rt.block_on(run_ble_scan(secs));
+        }
+        #[cfg(not(feature = "binary"))]
+        {
+            eprintln!("--scan-ble {} requires binary feature", secs);
+            return Err("BLE scanning not available".into());
+        }
+    }
+
+    if let Some(path) = ogg_file {
+        #[cfg(feature = "binary")]
+        {
+            return run_ogg_file_mode(&path);
+        }
+        #[cfg(not(feature = "binary"))]
+        {
+            eprintln!("--file {} requires binary feature", path);
+            return Err("Ogg-Opus decoding not available".into());
+        }
+    }
+
+    if let Some(spec) = pcm_input_spec {
+        #[cfg(feature = "binary")]
+        {
+            let source = pcm_input::PcmSource::parse(&spec)?;
+            return run_pcm_input_mode(source, no_inject);
+        }
+        #[cfg(not(feature = "binary"))]
+        {
+            eprintln!("--input {} requires binary feature", spec);
+            return Err("PCM input not available".into());
         }
     }
     
@@ -1154,6 +1714,8 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             .unwrap_or_else(|_| "External Microphone".to_string());
         find_input_device_by_spec(&host, &radio_spec)
             .ok_or_else(|| format!("No input device found for Radio (spec: {:?}). Set MEMO_RADIO_INPUT_DEVICE=0 or device name.", radio_spec))?
+    } else if let Some(ref name) = device_name {
+        select_input_device(Some(name))?
     } else {
         let spec = std::env::var("MEMO_SYSTEM_INPUT_DEVICE").unwrap_or_default();
         if !spec.trim().is_empty() {
@@ -1204,6 +1766,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let segmenter_active = Arc::new(AtomicBool::new(false));
     let last_segment_text: Arc<Mutex<Option<String>>> = Arc::new(Mutex::new(None));
 
+    // Auto-stop (hotkey mode): end the recording after trailing silence instead of on key release
+    let auto_stop_threshold: f32 = std::env::var("AUTO_STOP_THRESHOLD")
+        .ok().and_then(|s| s.parse().ok()).unwrap_or(600.0);
+    let auto_stop_silence_ms: u64 = std::env::var("AUTO_STOP_SILENCE_MS")
+        .ok().and_then(|s| s.parse().ok()).unwrap_or(800);
+    // Keep capturing this long after the hotkey is released: people let go as they finish the
+    // last word, and stopping immediately clips its ending
+    let release_delay = std::time::Duration::from_millis(
+        std::env::var("MEMO_RELEASE_DELAY_MS").ok().and_then(|s| s.parse().ok()).unwrap_or(250),
+    );
+    // Push-to-talk presses shorter than this are a brush of the key: the recording is
+    // discarded instead of transcribed (whisper hallucinates text from a near-empty clip)
+    let min_hold = std::time::Duration::from_millis(
+        std::env::var("MEMO_MIN_HOLD_MS").ok().and_then(|s| s.parse().ok()).unwrap_or(150),
+    );
+    // Bumped on every recording start so a stale watcher from the previous recording exits
+    let auto_stop_generation = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
     let audio_buffer_clone = audio_buffer.clone();
     let is_recording_clone = is_recording.clone();
     let is_locked_clone = is_locked.clone();
@@ -1424,34 +2004,56 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         let trigger_pressed = Arc::new(AtomicBool::new(false));
         let control_pressed = Arc::new(AtomicBool::new(false));
         let lock_toggle_processed = Arc::new(AtomicBool::new(false));
+        // When the current press started a push-to-talk recording
+        let pressed_at = Arc::new(Mutex::new(None::<Instant>));
 
         let trigger_pressed_clone = trigger_pressed.clone();
         let control_pressed_clone = control_pressed.clone();
         let lock_toggle_processed_clone = lock_toggle_processed.clone();
         let is_locked_listener = is_locked.clone();
+        let is_recording_listener = is_recording.clone();
 
         let trigger_key_for_listener = trigger_key;
         let tx_keyboard = tx.clone();
+        if let Some(hint) = listener_failure_hint() {
+            eprintln!("⚠️  The hotkey will not work. {}", hint);
+            println_ui_flush!("HOTKEY_ERROR:{}", json!({ "error": "hotkey unavailable", "hint": hint }));
+        }
         std::thread::spawn(move || {
-            listen(move |event: Event| {
+            listen_with_restart(move |event: Event| {
                 match event.event_type {
                     EventType::KeyPress(key) if key == trigger_key_for_listener => {
-                        trigger_pressed_clone.store(true, Ordering::Release);
+                        let was_pressed = trigger_pressed_clone.swap(true, Ordering::AcqRel);
 
                         if control_pressed_clone.load(Ordering::Acquire) {
                             if !lock_toggle_processed_clone.swap(true, Ordering::Acquire) {
                                 let _ = tx_keyboard.send(KeyEvent::ToggleLock);
                             }
-                        } else {
+                        } else if !auto_stop {
+                            if !was_pressed {
+                                *pressed_at.lock().unwrap() = Some(Instant::now());
+                            }
                             let _ = tx_keyboard.send(KeyEvent::StartRecording);
+                        } else if !was_pressed && !is_locked_listener.load(Ordering::Acquire) {
+                            // Auto-stop: a tap toggles (ignore key repeat while held)
+                            if is_recording_listener.load(Ordering::Acquire) {
+                                let _ = tx_keyboard.send(KeyEvent::StopRecording);
+                            } else {
+                                let _ = tx_keyboard.send(KeyEvent::StartRecording);
+                            }
                         }
                     }
                     EventType::KeyRelease(key) if key == trigger_key_for_listener => {
                         trigger_pressed_clone.store(false, Ordering::Release);
                         lock_toggle_processed_clone.store(false, Ordering::Release);
 
-                        if !is_locked_listener.load(Ordering::Acquire) {
-                            let _ = tx_keyboard.send(KeyEvent::StopRecording);
+                        let pressed_at = pressed_at.lock().unwrap().take();
+                        if !auto_stop && !is_locked_listener.load(Ordering::Acquire) {
+                            if pressed_at.is_some_and(|at| at.elapsed() < min_hold) {
+                                let _ = tx_keyboard.send(KeyEvent::CancelRecording);
+                            } else {
+                                let _ = tx_keyboard.send(KeyEvent::StopRecording);
+                            }
                         }
                     }
                     EventType::KeyPress(Key::ControlLeft) | EventType::KeyPress(Key::ControlRight) => {
@@ -1467,13 +2069,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         control_pressed_clone.store(false, Ordering::Release);
                         lock_toggle_processed_clone.store(false, Ordering::Release);
                     }
+                    EventType::KeyPress(Key::Escape) if is_recording_listener.load(Ordering::Acquire) => {
+                        let _ = tx_keyboard.send(KeyEvent::CancelRecording);
+                    }
                     _ => {}
                 }
-            }).ok();
+            });
         });
 
         println!("\nTrigger: Function key (or BLE device button)");
-        println!("Press and hold to record, release to transcribe.");
+        if auto_stop {
+            println!("Tap to record, stops after {}ms of silence (or tap again).", auto_stop_silence_ms);
+        } else {
+            println!("Press and hold to record, release to transcribe.");
+        }
         println!("Lock: Function+Control to toggle lock (keeps recording on)\n");
     }
     
@@ -1495,13 +2104,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let build_prompt = |app_name: String, window_title: String, vocab: &Vocabulary| -> Option<String> {
         let mut parts = Vec::new();
         
-        if !app_name.is_empty() && app_name != "Unknown" {
-            if !window_title.is_empty() {
-                parts.push(format!("You are transcribing for {}. The current window is: {}.", app_name, window_title));
-            } else {
-                parts.push(format!("You are transcribing for {}.", app_name));
-            }
-        }
+        parts.extend(app_context_prompt().render(&app_name, &window_title));
         
         if !vocab.app_names.is_empty() {
             parts.push(format!("Voice commands: open {}.", vocab.app_names.join(", ")));
@@ -1588,8 +2191,48 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             Ok(KeyEvent::StartRecording) => {
                 if is_recording_clone.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
                     println_ui_flush!("🎤 Recording...");
+                    remember_injection_target();
                     audio_buffer_clone.lock().unwrap().clear();
 
+                    if auto_stop && !use_vad_trigger {
+                        // Watch the recording buffer and stop after trailing silence. Lock mode
+                        // suspends auto-stop: a locked recording only ends when unlocked.
+                        let generation = auto_stop_generation.fetch_add(1, Ordering::AcqRel) + 1;
+                        let generation_auto = auto_stop_generation.clone();
+                        let buf_auto = audio_buffer_clone.clone();
+                        let recording_auto = is_recording_clone.clone();
+                        let locked_auto = is_locked_clone.clone();
+                        let tx_auto = tx.clone();
+                        let mut detector = AutoStop::new(
+                            sample_rate,
+                            auto_stop_threshold,
+                            std::time::Duration::from_millis(auto_stop_silence_ms),
+                        );
+                        std::thread::spawn(move || {
+                            let mut consumed = 0;
+                            while recording_auto.load(Ordering::Acquire)
+                                && generation_auto.load(Ordering::Acquire) == generation
+                            {
+                                std::thread::sleep(std::time::Duration::from_millis(50));
+                                let chunk: Vec<i16> = {
+                                    let buf = buf_auto.lock().unwrap();
+                                    let chunk = buf[consumed.min(buf.len())..].to_vec();
+                                    consumed = buf.len();
+                                    chunk
+                                };
+                                if locked_auto.load(Ordering::Acquire) {
+                                    detector.reset();
+                                    continue;
+                                }
+                                if detector.push(&chunk) {
+                                    println_ui_flush!("🔇 Silence detected, stopping");
+                                    let _ = tx_auto.send(KeyEvent::StopRecording);
+                                    break;
+                                }
+                            }
+                        });
+                    }
+
                     if streaming_enabled {
                         segment_results_clone.lock().unwrap().clear();
                         *segment_boundary_clone.lock().unwrap() = 0;
@@ -1656,7 +2299,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         let vocab = voc.lock().unwrap();
                                         let mut prompt = build_prompt(app_name, window_title, &vocab);
                                         if let Some(ref prev) = *prev_text.lock().unwrap() {
-                                            let context = if prev.len() > 200 { &prev[prev.len()-200..] } else { prev.as_str() };
+                                            let context = prompt_context(prev);
                                             prompt = Some(match prompt {
                                                 Some(p) => format!("{} {}", p, context),
                                                 None => context.to_string(),
@@ -1773,6 +2416,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
             }
             Ok(KeyEvent::StopRecording) => {
+                // The stream keeps filling the buffer while is_recording is still set
+                if !use_vad_trigger && !release_delay.is_zero() && is_recording_clone.load(Ordering::Acquire) {
+                    std::thread::sleep(release_delay);
+                }
                 if is_recording_clone.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
                     segmenter_active_clone.store(false, Ordering::Release);
 
@@ -1790,68 +2437,66 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     } else { 0 };
                     
                     if !samples.is_empty() {
-                        // Encode audio to OPUS for saving (only for 16kHz, resample if needed)
+                        // Encode audio to OPUS for saving (the encoder runs at 16kHz)
                         let samples_for_encoding = samples.clone();
                         let sample_rate_for_encoding = sample_rate;
                         let audio_duration = samples.len() as f32 / sample_rate as f32;
                         
-                        // Encode in a separate thread (only if 16kHz, otherwise skip)
-                        if sample_rate_for_encoding == 16000 {
-                            std::thread::spawn(move || {
-                                use opus_decoder::OpusEncoder;
-                                #[cfg(feature = "binary")]
-                                use base64::{Engine as _, engine::general_purpose::STANDARD};
-                                
-                                match OpusEncoder::new(16000, 20) {
-                                    Ok(mut encoder_for_thread) => {
-                                        match encoder_for_thread.encode_buffer(&samples_for_encoding) {
-                                            Ok(opus_data) => {
-                                                #[cfg(feature = "binary")]
-                                                {
-                                                    let base64_data = STANDARD.encode(&opus_data);
-                                                    println!("AUDIO_DATA:{}", base64_data);
-                                                    println!("AUDIO_DURATION:{:.2}", audio_duration);
-                                                    
-                                                    // Also output WAV data for easy playback
-                                                    let sample_rate = 16000u32;
-                                                    let channels = 1u16;
-                                                    let bits_per_sample = 16u16;
-                                                    let pcm_data_len = samples_for_encoding.len() * 2;
-                                                    let wav_size = 44 + pcm_data_len;
-                                                    
-                                                    let mut wav_data = Vec::with_capacity(wav_size);
-                                                    wav_data.extend_from_slice(b"RIFF");
-                                                    wav_data.extend_from_slice(&(36u32 + pcm_data_len as u32).to_le_bytes());
-                                                    wav_data.extend_from_slice(b"WAVE");
-                                                    wav_data.extend_from_slice(b"fmt ");
-                                                    wav_data.extend_from_slice(&16u32.to_le_bytes());
-                                                    wav_data.extend_from_slice(&1u16.to_le_bytes());
-                                                    wav_data.extend_from_slice(&channels.to_le_bytes());
-                                                    wav_data.extend_from_slice(&sample_rate.to_le_bytes());
-                                                    wav_data.extend_from_slice(&(sample_rate as u32 * channels as u32 * (bits_per_sample as u32 / 8)).to_le_bytes());
-                                                    wav_data.extend_from_slice(&(channels * (bits_per_sample / 8)).to_le_bytes());
-                                                    wav_data.extend_from_slice(&bits_per_sample.to_le_bytes());
-                                                    wav_data.extend_from_slice(b"data");
-                                                    wav_data.extend_from_slice(&(pcm_data_len as u32).to_le_bytes());
-                                                    for &sample in &samples_for_encoding {
-                                                        wav_data.extend_from_slice(&sample.to_le_bytes());
-                                                    }
-                                                    
-                                                    let wav_base64 = STANDARD.encode(&wav_data);
-                                                    println!("AUDIO_WAV:{}", wav_base64);
+                        // Encode in a separate thread
+                        std::thread::spawn(move || {
+                            let samples_for_encoding = memo_stt::audio::resample_i16(&samples_for_encoding, sample_rate_for_encoding, 16000);
+                            #[cfg(feature = "binary")]
+                            use base64::{Engine as _, engine::general_purpose::STANDARD};
+                            
+                            match recording_encoder() {
+                                Ok(mut encoder_for_thread) => {
+                                    match encoder_for_thread.encode_buffer(&samples_for_encoding) {
+                                        Ok(opus_data) => {
+                                            #[cfg(feature = "binary")]
+                                            {
+                                                let base64_data = STANDARD.encode(&opus_data);
+                                                println!("AUDIO_DATA:{}", base64_data);
+                                                println!("AUDIO_DURATION:{:.2}", audio_duration);
+                                                
+                                                // Also output WAV data for easy playback
+                                                let sample_rate = 16000u32;
+                                                let channels = 1u16;
+                                                let bits_per_sample = 16u16;
+                                                let pcm_data_len = samples_for_encoding.len() * 2;
+                                                let wav_size = 44 + pcm_data_len;
+                                                
+                                                let mut wav_data = Vec::with_capacity(wav_size);
+                                                wav_data.extend_from_slice(b"RIFF");
+                                                wav_data.extend_from_slice(&(36u32 + pcm_data_len as u32).to_le_bytes());
+                                                wav_data.extend_from_slice(b"WAVE");
+                                                wav_data.extend_from_slice(b"fmt ");
+                                                wav_data.extend_from_slice(&16u32.to_le_bytes());
+                                                wav_data.extend_from_slice(&1u16.to_le_bytes());
+                                                wav_data.extend_from_slice(&channels.to_le_bytes());
+                                                wav_data.extend_from_slice(&sample_rate.to_le_bytes());
+                                                wav_data.extend_from_slice(&(sample_rate as u32 * channels as u32 * (bits_per_sample as u32 / 8)).to_le_bytes());
+                                                wav_data.extend_from_slice(&(channels * (bits_per_sample / 8)).to_le_bytes());
+                                                wav_data.extend_from_slice(&bits_per_sample.to_le_bytes());
+                                                wav_data.extend_from_slice(b"data");
+                                                wav_data.extend_from_slice(&(pcm_data_len as u32).to_le_bytes());
+                                                for &sample in &samples_for_encoding {
+                                                    wav_data.extend_from_slice(&sample.to_le_bytes());
                                                 }
-                                            }
-                                            Err(e) => {
-                                                eprintln!("Failed to encode audio: {}", e);
+                                                
+                                                let wav_base64 = STANDARD.encode(&wav_data);
+                                                println!("AUDIO_WAV:{}", wav_base64);
                                             }
                                         }
-                                    }
-                                    Err(e) => {
-                                        eprintln!("Failed to create Opus encoder: {}", e);
+                                        Err(e) => {
+                                            eprintln!("Failed to encode audio: {}", e);
+                                        }
                                     }
                                 }
-                            });
-                        }
+                                Err(e) => {
+                                    eprintln!("Failed to create Opus encoder: {}", e);
+                                }
+                            }
+                        });
                         
                         // Spawn transcription thread immediately for fastest response
                         let engine_for_thread = engine_clone.clone();
@@ -1875,7 +2520,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                             let mut prompt = build_prompt(app_name, window_title, &vocab);
                             if streaming_boundary > 0 {
                                 if let Some(ref prev) = *last_seg_text_for_thread.lock().unwrap() {
-                                    let context = if prev.len() > 200 { &prev[prev.len()-200..] } else { prev.as_str() };
+                                    let context = prompt_context(prev);
                                     prompt = Some(match prompt {
                                         Some(p) => format!("{} {}", p, context),
                                         None => context.to_string(),
@@ -1906,18 +2551,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 }
                                 let combined = join_segments(&parts);
                                 if combined.trim().is_empty() {
-                                    eng.transcribe(&samples)
+                                    transcribe_final(&mut eng, &samples)
                                 } else {
                                     if pre_processed_count > 0 {
                                         eprintln!("[Streaming] {} segments pre-processed, final segment transcribed", pre_processed_count);
                                     }
-                                    Ok(combined)
+                                    Ok((combined, None))
                                 }
                             } else {
-                                eng.transcribe(&samples)
+                                transcribe_final(&mut eng, &samples)
                             };
                             match transcribe_result {
-                                Ok(text) => {
+                                Ok((text, words)) => {
                                     let transcribe_time = transcribe_start.elapsed();
                                     let realtime_factor = audio_duration / transcribe_time.as_secs_f32();
                                     
@@ -1960,11 +2605,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         // Capture application context (already captured before transcription)
                                         let (app_name, window_title) = app_detection::get_application_context();
                                         
-                                        // Process text to strip periods from short phrases
-                                        let processed_text = strip_leading_dash_space(&strip_trailing_signoffs(&strip_periods_from_short_phrases(&text)));
+                                        // Clean up text for injection (raw transcript is kept as-is)
+                                        let processed_text = process_transcript(&text);
                                         
                                         // Output FINAL: JSON for Electron app integration
-                                        let json_output = json!({
+                                        let mut json_output = json!({
                                             "rawTranscript": text,
                                             "processedText": processed_text,
                                             "wasProcessedByLLM": false,
@@ -1973,6 +2618,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                                 "windowTitle": window_title
                                             }
                                         });
+                                        add_final_words(&mut json_output, words.as_deref());
                                         println!("FINAL: {}", json_output);
                                         
                                         // Only inject if not in Electron mode
@@ -2040,6 +2686,19 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
             }
+            Ok(KeyEvent::CancelRecording) => {
+                if is_recording_clone.compare_exchange(true, false, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
+                    segmenter_active_clone.store(false, Ordering::Release);
+                    is_locked_clone.store(false, Ordering::Release);
+
+                    if !use_vad_trigger {
+                        recording_stream_clone.lock().unwrap().take();
+                    }
+                    audio_buffer_clone.lock().unwrap().clear();
+                    segment_results_clone.lock().unwrap().clear();
+                    println_ui_flush!("🚫 Cancelled (recording discarded)");
+                }
+            }
             Ok(KeyEvent::ToggleLock) => {
                 let was_locked = is_locked_clone.load(Ordering::Acquire);
                 let now_locked = !was_locked;
@@ -2053,6 +2712,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                         // Manually trigger start recording logic
                         if is_recording_clone.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
                             println_ui_flush!("🎤 Recording...");
+                            remember_injection_target();
                             audio_buffer_clone.lock().unwrap().clear();
 
                             if streaming_enabled {
@@ -2104,7 +2764,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                                 let vocab = voc.lock().unwrap();
                                                 let mut prompt = build_prompt(app_name, window_title, &vocab);
                                                 if let Some(ref prev) = *prev_text.lock().unwrap() {
-                                                    let context = if prev.len() > 200 { &prev[prev.len()-200..] } else { prev.as_str() };
+                                                    let context = prompt_context(prev);
                                                     prompt = Some(match prompt {
                                                         Some(p) => format!("{} {}", p, context),
                                                         None => context.to_string(),
@@ -2257,7 +2917,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                     let mut prompt = build_prompt(app_name, window_title, &vocab);
                                     if streaming_boundary > 0 {
                                         if let Some(ref prev) = *last_seg_text_for_thread.lock().unwrap() {
-                                            let context = if prev.len() > 200 { &prev[prev.len()-200..] } else { prev.as_str() };
+                                            let context = prompt_context(prev);
                                             prompt = Some(match prompt {
                                                 Some(p) => format!("{} {}", p, context),
                                                 None => context.to_string(),
@@ -2288,18 +2948,18 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                         }
                                         let combined = join_segments(&parts);
                                         if combined.trim().is_empty() {
-                                            eng.transcribe(&samples)
+                                            transcribe_final(&mut eng, &samples)
                                         } else {
                                             if pre_processed_count > 0 {
                                                 eprintln!("[Streaming] {} segments pre-processed, final segment transcribed", pre_processed_count);
                                             }
-                                            Ok(combined)
+                                            Ok((combined, None))
                                         }
                                     } else {
-                                        eng.transcribe(&samples)
+                                        transcribe_final(&mut eng, &samples)
                                     };
                                     match transcribe_result {
-                                        Ok(text) => {
+                                        Ok((text, words)) => {
                                             let transcribe_time = transcribe_start.elapsed();
                                             let realtime_factor = audio_duration / transcribe_time.as_secs_f32();
                                             
@@ -2342,11 +3002,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                                 // Capture application context (already captured before transcription)
                                                 let (app_name, window_title) = app_detection::get_application_context();
                                                 
-                                                // Process text to strip periods from short phrases
-                                                let processed_text = strip_leading_dash_space(&strip_trailing_signoffs(&strip_periods_from_short_phrases(&text)));
+                                                // Clean up text for injection (raw transcript is kept as-is)
+                                                let processed_text = process_transcript(&text);
                                                 
                                                 // Output FINAL: JSON for Electron app integration
-                                                let json_output = json!({
+                                                let mut json_output = json!({
                                                     "rawTranscript": text,
                                                     "processedText": processed_text,
                                                     "wasProcessedByLLM": false,
@@ -2355,6 +3015,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                                                         "windowTitle": window_title
                                                     }
                                                 });
+                                                add_final_words(&mut json_output, words.as_deref());
                                                 println!("FINAL: {}", json_output);
                                                 
                                                 // Only inject if not in Electron mode
@@ -2432,3 +3093,153 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn ble_state_lines_describe_connection_events() {
+        use ble::{ConnectionEvent, DisconnectReason};
+        assert_eq!(
+            ble_state_json(&ConnectionEvent::Connected { device_name: "memo_C9AA6".to_string() }),
+            json!({ "state": "connected", "deviceName": "memo_C9AA6" })
+        );
+        assert_eq!(
+            ble_state_json(&ConnectionEvent::Disconnected(DisconnectReason::ConnectionLost))["reason"],
+            "connection_lost"
+        );
+        assert_eq!(ble_state_json(&ConnectionEvent::Reconnecting)["state"], "reconnecting");
+        assert_eq!(
+            ble_state_json(&ConnectionEvent::ConnectFailed { reason: "Device not found".to_string() })["reason"],
+            "Device not found"
+        );
+    }
+
+    #[test]
+    fn app_context_template_fills_placeholders() {
+        let full = AppContextPrompt { mode: AppContextMode::Full, template: DEFAULT_APP_CONTEXT_TEMPLATE.to_string() };
+        assert_eq!(
+            full.render("Slack", "general").as_deref(),
+            Some("You are transcribing for Slack. The current window is: general.")
+        );
+        // No title: the sentence about it is dropped, matching the old hardcoded prompt
+        assert_eq!(full.render("Slack", "").as_deref(), Some("You are transcribing for Slack."));
+        assert_eq!(full.render("Unknown", "general"), None);
+        assert_eq!(full.render("", "general"), None);
+
+        let app_only = AppContextPrompt { mode: AppContextMode::AppOnly, ..full.clone() };
+        assert_eq!(app_only.render("Slack", "general").as_deref(), Some("You are transcribing for Slack."));
+        let off = AppContextPrompt { mode: AppContextMode::Off, ..full.clone() };
+        assert_eq!(off.render("Slack", "general"), None);
+
+        let custom = AppContextPrompt { mode: AppContextMode::Full, template: "Diktat in {app} ({title}).".to_string() };
+        assert_eq!(custom.render("Mail", "Entwurf").as_deref(), Some("Diktat in Mail (Entwurf)."));
+        assert_eq!(custom.render("Mail", ""), None);
+        let empty = AppContextPrompt { mode: AppContextMode::Full, template: String::new() };
+        assert_eq!(empty.render("Mail", "Entwurf"), None);
+    }
+
+    #[test]
+    fn prompt_context_cuts_on_char_boundaries() {
+        assert_eq!(prompt_context("short café"), "short café");
+        let ascii = "a".repeat(250);
+        assert_eq!(prompt_context(&ascii), &ascii[50..]);
+        // Byte offset len-200 lands inside a multi-byte character here
+        let mixed = format!("{}{}", "é".repeat(150), "日本語".repeat(50));
+        let context = prompt_context(&mixed);
+        assert_eq!(context.chars().count(), 200);
+        assert!(mixed.ends_with(context));
+        assert!(context.starts_with("éé"));
+    }
+
+    #[test]
+    fn rate_of_increase_is_regression_slope() {
+        assert_eq!(calculate_rate_of_increase(&[]), None);
+        assert_eq!(calculate_rate_of_increase(&[(1.0, 5.0)]), None);
+        // All samples at the same duration: slope undefined
+        assert_eq!(calculate_rate_of_increase(&[(2.0, 5.0), (2.0, 6.0)]), None);
+
+        let slope = calculate_rate_of_increase(&[(1.0, 3.0), (2.0, 5.0), (3.0, 7.0)]).unwrap();
+        assert!((slope - 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn audio_levels_are_symmetric_and_bounded() {
+        assert_eq!(calculate_audio_levels(&[]), vec![0.0; 7]);
+        assert!(calculate_audio_levels(&[0; 320]).iter().all(|&l| l == 0.0));
+
+        let loud = calculate_audio_levels(&[i16::MAX; 320]);
+        assert_eq!(loud.len(), 7);
+        assert!(loud.iter().all(|&l| (0.0..=1.0).contains(&l)));
+        assert_eq!(loud[3], 1.0);
+        assert_eq!(loud[0], loud[6]);
+
+        let quiet = calculate_audio_levels(&[500; 320]);
+        assert!(quiet[3] > 0.0 && quiet[3] < loud[3]);
+    }
+
+    #[test]
+    fn wayland_sessions_are_detected() {
+        assert!(is_wayland_session(Some("wayland"), false));
+        assert!(is_wayland_session(None, true));
+        assert!(!is_wayland_session(Some("x11"), true), "the session type wins over a stray WAYLAND_DISPLAY");
+        assert!(!is_wayland_session(Some("tty"), false));
+        assert!(!is_wayland_session(None, false));
+    }
+
+    #[test]
+    fn band_weights_taper_symmetrically_for_any_count() {
+        let seven = band_weights(7);
+        for (w, expected) in seven.iter().zip([0.6, 0.8, 0.95, 1.0, 0.95, 0.8, 0.6]) {
+            assert!((w - expected).abs() < 1e-6, "{:?}", seven);
+        }
+        assert_eq!(band_weights(1), vec![1.0]);
+        assert_eq!(band_weights(3), vec![0.6, 1.0, 0.6]);
+        let wide = band_weights(32);
+        assert_eq!(wide.len(), 32);
+        assert_eq!(wide[0], wide[31]);
+        assert!(wide.iter().all(|&w| (0.6..=1.0).contains(&w)));
+        assert!(wide[..16].windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn final_words_are_reported_in_milliseconds() {
+        let mut json_output = json!({ "rawTranscript": "Ask not," });
+        add_final_words(&mut json_output, None);
+        assert!(json_output.get("words").is_none());
+
+        let words = [Word { start_secs: 0.25, end_secs: 0.6004, text: "Ask".into(), probability: 0.9 }];
+        add_final_words(&mut json_output, Some(&words));
+        assert_eq!(json_output["words"][0]["text"], "Ask");
+        assert_eq!(json_output["words"][0]["start_ms"], 250);
+        assert_eq!(json_output["words"][0]["end_ms"], 600);
+        assert!((json_output["words"][0]["probability"].as_f64().unwrap() - 0.9).abs() < 1e-6);
+    }
+
+    #[test]
+    fn waveform_flags_set_bands_and_interval() {
+        assert_eq!(WaveformConfig::from_args(Some("3"), Some("30")), WaveformConfig { bands: 3, interval_ms: 33 });
+        assert_eq!(WaveformConfig::from_args(Some("32"), Some("0")).interval_ms, 0);
+        assert_eq!(WaveformConfig::from_args(Some("0"), Some("20")).bands, 7);
+        assert_eq!(WaveformConfig::from_args(Some("65"), Some("20")).bands, 7);
+    }
+
+    #[test]
+    fn interleaved_levels_downmix_channels() {
+        let stereo: Vec<i16> = [4000i16, -4000].repeat(160);
+        assert!(audio_levels_interleaved_i16(&stereo, 2).iter().all(|&l| l == 0.0));
+        assert_eq!(audio_levels_interleaved_i16(&[4000; 320], 1), calculate_audio_levels(&[4000; 320]));
+    }
+
+    #[test]
+    fn joined_segments_get_sentence_boundaries() {
+        assert_eq!(join_segments(&[]), "");
+        assert_eq!(join_segments(&["one".into()]), "one");
+        assert_eq!(
+            join_segments(&["first part".into(), " ".into(), "second, ".into(), "done".into()]),
+            "first part. second, done"
+        );
+    }
+}
@@ -1,45 +1,106 @@
 /*
  * Opus Codec - Encodes PCM to Opus and decodes Opus-encoded audio frames to PCM
+ * (live BLE bundles, or saved Ogg-Opus files)
  *
- * Receiver frame size must match firmware: 20ms at 16kHz = 320 samples per frame.
+ * Receiver frame size must match firmware: 20ms at 16kHz = 320 samples per frame by default.
  * (Firmware changed from 10ms/160 to 20ms/320; opus_decode() must output 320 samples per frame.)
+ * Devices that negotiate 10/40/60ms frames need a decoder created with that duration.
+ * Other sources may use any Opus rate (8/12/16/24/48kHz); the engine resamples non-16kHz PCM.
  */
 
 use anyhow::{Context, Result};
 use audiopus::coder::{Decoder, Encoder};
 use audiopus::{Application, Channels, SampleRate};
 use log::{debug, warn};
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// Granule positions and pre-skip in Ogg-Opus are always counted at 48 kHz
+const OGG_OPUS_RATE: u64 = 48000;
+/// Longest Opus packet (120ms) at 16 kHz, per channel
+const MAX_PACKET_SAMPLES: usize = 1920;
+/// Frame duration of the memo firmware
+pub const DEFAULT_FRAME_DURATION_MS: u32 = 20;
+/// Frame durations accepted by [`OpusDecoder::new`] and [`OpusEncoder::new`]
+pub const SUPPORTED_FRAME_DURATIONS_MS: [u32; 4] = [10, 20, 40, 60];
+
+/// Encoder settings used by [`OpusEncoder::new`]: 24kbps VBR at complexity 5 suits speech
+pub const DEFAULT_BITRATE: i32 = 24000;
+pub const DEFAULT_COMPLEXITY: u8 = 5;
+/// Bitrates (bits/s) libopus accepts
+const BITRATE_RANGE: std::ops::RangeInclusive<i32> = 500..=512_000;
+/// Output buffer for one encoded packet; libopus recommends 4000 bytes as always enough
+const MAX_PACKET_BYTES: usize = 4000;
+
+/// Sample rates accepted by [`OpusDecoder::new`] and [`OpusEncoder::new`]
+pub const SUPPORTED_SAMPLE_RATES: [u32; 5] = [8000, 12000, 16000, 24000, 48000];
+
+fn opus_sample_rate(sample_rate: u32) -> Result<SampleRate> {
+    Ok(match sample_rate {
+        8000 => SampleRate::Hz8000,
+        12000 => SampleRate::Hz12000,
+        16000 => SampleRate::Hz16000,
+        24000 => SampleRate::Hz24000,
+        48000 => SampleRate::Hz48000,
+        _ => anyhow::bail!(
+            "Unsupported Opus sample rate {} Hz (supported: {:?})",
+            sample_rate, SUPPORTED_SAMPLE_RATES
+        ),
+    })
+}
+
+fn check_frame_duration(frame_duration_ms: u32) -> Result<()> {
+    if !SUPPORTED_FRAME_DURATIONS_MS.contains(&frame_duration_ms) {
+        anyhow::bail!(
+            "Unsupported Opus frame duration {}ms (supported: {:?})",
+            frame_duration_ms, SUPPORTED_FRAME_DURATIONS_MS
+        );
+    }
+    Ok(())
+}
+
+/// Decode counters accumulated by [`OpusDecoder`] since creation or the last
+/// [`OpusDecoder::reset_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// Frames decoded from received packets
+    pub frames_decoded: u64,
+    /// Frames reported lost (recovered with FEC or concealed with PLC)
+    pub frames_lost: u64,
+    /// PCM samples produced, including FEC and PLC output
+    pub samples_produced: u64,
+    /// Lost frames filled in by packet-loss concealment
+    pub plc_frames: u64,
+}
 
 /// Opus decoder wrapper.
-/// Frame size is 320 samples (20ms at 16kHz); must match firmware encoder.
+/// Frame size is 320 samples (20ms at 16kHz) by default; must match firmware encoder.
 pub struct OpusDecoder {
     decoder: Decoder,
     sample_rate: u32,
     /// 320 samples = 20ms at 16kHz (must match firmware)
     frame_size_samples: usize,
+    stats: DecodeStats,
 }
 
 impl OpusDecoder {
     /// Create a new Opus decoder
     ///
     /// # Arguments
-    /// * `sample_rate` - Sample rate in Hz (must be 16000)
-    /// * `frame_duration_ms` - Frame duration in milliseconds (must be 20 to match firmware)
+    /// * `sample_rate` - Output sample rate in Hz: 8000, 12000, 16000 (memo firmware), 24000
+    ///   or 48000. Opus decodes any stream at any of these rates.
+    /// * `frame_duration_ms` - Frame duration in milliseconds: 10, 20, 40 or 60, matching the
+    ///   device's encoder ([`DEFAULT_FRAME_DURATION_MS`] for memo firmware)
     pub fn new(sample_rate: u32, frame_duration_ms: u32) -> Result<Self> {
-        if sample_rate != 16000 {
-            anyhow::bail!("Opus decoder only supports 16kHz");
-        }
-
-        if frame_duration_ms != 20 {
-            anyhow::bail!("Opus decoder only supports 20ms frames (must match firmware)");
-        }
+        let opus_rate = opus_sample_rate(sample_rate)?;
+        check_frame_duration(frame_duration_ms)?;
 
         // 20ms at 16kHz = 320 samples per frame (firmware sends 1 frame per bundle with 20ms)
         let frame_size_samples = (sample_rate * frame_duration_ms / 1000) as usize;
         
-        // Create Opus decoder (mono, 16kHz)
+        // Create Opus decoder (mono)
         let decoder = Decoder::new(
-            SampleRate::Hz16000,
+            opus_rate,
             Channels::Mono,
         ).context("Failed to create Opus decoder")?;
         
@@ -47,9 +108,20 @@ impl OpusDecoder {
             decoder,
             sample_rate,
             frame_size_samples,
+            stats: DecodeStats::default(),
         })
     }
 
+    /// Decode counters since creation or the last [`reset_stats`](Self::reset_stats)
+    pub fn stats(&self) -> DecodeStats {
+        self.stats
+    }
+
+    /// Zero the decode counters (e.g. at the start of a recording)
+    pub fn reset_stats(&mut self) {
+        self.stats = DecodeStats::default();
+    }
+
     /// Decode a single Opus frame to PCM
     /// 
     /// # Arguments
@@ -72,6 +144,8 @@ impl OpusDecoder {
         
         // Truncate to actual number of samples decoded
         pcm.truncate(samples_decoded);
+        self.stats.frames_decoded += 1;
+        self.stats.samples_produced += samples_decoded as u64;
 
         Ok(pcm)
     }
@@ -89,17 +163,27 @@ impl OpusDecoder {
             .decode(Some(next_frame_data), &mut pcm, true)
             .context("Failed to decode FEC frame")?;
         pcm.truncate(samples_decoded);
+        self.stats.frames_lost += 1;
+        self.stats.samples_produced += samples_decoded as u64;
         Ok(pcm)
     }
 
-    /// Generate one frame of packet-loss concealment (PLC). Use when a packet was lost and FEC is not used.
-    pub fn decode_plc(&mut self) -> Result<Vec<i16>> {
+    /// Synthesize a replacement for one lost frame with packet-loss concealment (PLC). Use
+    /// when a packet was lost and FEC is not used (see [`BundleSequence`] for spotting losses).
+    ///
+    /// Prefer this over inserting silence: the decoder extrapolates from the audio before the
+    /// gap, so the gap doesn't click, and its internal state moves on by one frame, so the
+    /// next real frame decodes smoothly instead of glitching against stale prediction state.
+    pub fn decode_lost_frame(&mut self) -> Result<Vec<i16>> {
         let mut pcm = vec![0i16; self.frame_size_samples];
         let samples_decoded = self
             .decoder
             .decode(None::<&[u8]>, &mut pcm, false)
             .context("Failed to decode PLC")?;
         pcm.truncate(samples_decoded);
+        self.stats.frames_lost += 1;
+        self.stats.plc_frames += 1;
+        self.stats.samples_produced += samples_decoded as u64;
         Ok(pcm)
     }
 
@@ -113,48 +197,17 @@ impl OpusDecoder {
     /// # Returns
     /// Decoded PCM samples from all frames in the bundle
     pub fn decode_bundle(&mut self, bundle_data: &[u8]) -> Result<Vec<i16>> {
-        if bundle_data.is_empty() {
-            return Ok(Vec::new());
-        }
+        let frames = bundle_frames(bundle_data);
+        debug!("Decoding bundle with {} frames", frames.len());
 
-        // Parse bundle header: [num_frames:1]
-        if bundle_data.len() < 1 {
-            anyhow::bail!("Bundle too short: missing frame count");
-        }
-        
-        let num_frames = bundle_data[0] as usize;
-        debug!("Decoding bundle with {} frames", num_frames);
-
-        let mut pcm_samples = Vec::new();
-        let mut offset = 1; // Skip frame count byte
-
-        for frame_idx in 0..num_frames {
-            if offset >= bundle_data.len() {
-                warn!("Bundle truncated at frame {}", frame_idx);
-                break;
-            }
-
-            // Read frame size (1 byte)
-            let frame_size = bundle_data[offset] as usize;
-            offset += 1;
-
-            if offset + frame_size > bundle_data.len() {
-                warn!("Frame {} size exceeds bundle data", frame_idx);
-                break;
-            }
-
-            // Extract frame data
-            let frame_data = &bundle_data[offset..offset + frame_size];
-            
-            // Decode frame
+        let mut pcm_samples = Vec::with_capacity(frames.len() * self.frame_size_samples);
+        for (frame_idx, frame_data) in frames.into_iter().enumerate() {
             let decoded = self.decode_frame(frame_data)
                 .with_context(|| format!("Failed to decode frame {}", frame_idx))?;
-            
             pcm_samples.extend_from_slice(&decoded);
-            offset += frame_size;
         }
 
-        debug!("Decoded {} frames to {} PCM samples", num_frames, pcm_samples.len());
+        debug!("Decoded bundle to {} PCM samples", pcm_samples.len());
         Ok(pcm_samples)
     }
 
@@ -162,18 +215,9 @@ impl OpusDecoder {
     /// reconstruct the lost frame, then decode this bundle normally. Returns
     /// [reconstructed_previous_frame][this_bundle_frames].
     pub fn decode_bundle_with_fec(&mut self, bundle_data: &[u8]) -> Result<Vec<i16>> {
-        if bundle_data.len() < 2 {
-            return self.decode_bundle(bundle_data);
-        }
-        let num_frames = bundle_data[0] as usize;
-        if num_frames == 0 {
+        let Some(first_frame) = bundle_frames(bundle_data).first().copied() else {
             return self.decode_bundle(bundle_data);
-        }
-        let first_frame_len = bundle_data[1] as usize;
-        if 2 + first_frame_len > bundle_data.len() {
-            return self.decode_bundle(bundle_data);
-        }
-        let first_frame = &bundle_data[2..2 + first_frame_len];
+        };
         let mut fec_pcm = self.decode_frame_with_fec(first_frame)?;
         let mut bundle_pcm = self.decode_bundle(bundle_data)?;
         fec_pcm.append(&mut bundle_pcm);
@@ -184,6 +228,178 @@ impl OpusDecoder {
     pub fn frame_size_samples(&self) -> usize {
         self.frame_size_samples
     }
+
+    /// Decode a whole Ogg-Opus file (`.opus`, e.g. a saved device recording) to 16 kHz mono
+    /// PCM, ready for transcription.
+    ///
+    /// Opus decodes to any rate regardless of the rate recorded in the header, so the output
+    /// is always 16 kHz; stereo is averaged to mono and the header's output gain applied.
+    /// The encoder delay (pre-skip) is dropped and the end trimmed to the final granule
+    /// position, so the PCM is as long as the original recording.
+    pub fn decode_ogg_file(path: impl AsRef<Path>) -> Result<Vec<i16>> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        decode_ogg(std::io::BufReader::new(file)).with_context(|| format!("Failed to decode {}", path.display()))
+    }
+
+    /// Like [`decode_ogg_file`](Self::decode_ogg_file), reading the Ogg-Opus stream from
+    /// `reader` (e.g. a `Cursor` over bytes received from elsewhere)
+    pub fn decode_ogg_reader<R: Read + Seek>(reader: R) -> Result<Vec<i16>> {
+        decode_ogg(reader).context("Failed to decode Ogg-Opus stream")
+    }
+}
+
+/// `OpusHead` fields needed for decoding (RFC 7845 section 5.1)
+struct OpusHead {
+    channels: u8,
+    /// Samples at 48 kHz to drop from the start
+    pre_skip: u16,
+    /// Q7.8 dB
+    output_gain: i16,
+}
+
+impl OpusHead {
+    fn parse(packet: &[u8]) -> Result<Self> {
+        if packet.len() < 19 || !packet.starts_with(b"OpusHead") {
+            anyhow::bail!("Not an Ogg-Opus stream (missing OpusHead)");
+        }
+        let channels = packet[9];
+        let mapping_family = packet.get(18).copied().unwrap_or(0);
+        if !(1..=2).contains(&channels) || mapping_family != 0 {
+            anyhow::bail!("Unsupported Opus channel layout ({} channels, mapping family {})", channels, mapping_family);
+        }
+        Ok(Self {
+            channels,
+            pre_skip: u16::from_le_bytes([packet[10], packet[11]]),
+            output_gain: i16::from_le_bytes([packet[16], packet[17]]),
+        })
+    }
+}
+
+/// Decode the first logical Opus stream in an Ogg container to 16 kHz mono
+fn decode_ogg<R: Read + Seek>(reader: R) -> Result<Vec<i16>> {
+    let mut packets = ogg::reading::PacketReader::new(reader);
+    let head_packet = packets.read_packet()?.context("Empty Ogg file")?;
+    let serial = head_packet.stream_serial();
+    let head = OpusHead::parse(&head_packet.data)?;
+    let channels = head.channels as usize;
+    let mut decoder = Decoder::new(
+        SampleRate::Hz16000,
+        if channels == 2 { Channels::Stereo } else { Channels::Mono },
+    )
+    .context("Failed to create Opus decoder")?;
+
+    let mut pcm = Vec::new();
+    let mut frame = vec![0i16; MAX_PACKET_SAMPLES * channels];
+    let mut final_granule = None;
+    let mut seen_tags = false;
+    while let Some(packet) = packets.read_packet()? {
+        if packet.stream_serial() != serial {
+            continue;
+        }
+        // The packet after OpusHead is OpusTags (metadata)
+        if !seen_tags {
+            seen_tags = true;
+            continue;
+        }
+        let decoded = decoder
+            .decode(Some(&packet.data[..]), &mut frame[..], false)
+            .context("Failed to decode Opus packet")?;
+        pcm.extend(frame[..decoded * channels].chunks_exact(channels).map(|c| {
+            (c.iter().map(|&s| s as i32).sum::<i32>() / channels as i32) as i16
+        }));
+        // u64::MAX marks a page on which no packet ends
+        if packet.absgp_page() != u64::MAX {
+            final_granule = Some(packet.absgp_page());
+        }
+        if packet.last_in_stream() {
+            break;
+        }
+    }
+
+    let to_16k = |samples_48k: u64| (samples_48k * 16000 / OGG_OPUS_RATE) as usize;
+    let pre_skip = to_16k(head.pre_skip as u64).min(pcm.len());
+    if let Some(granule) = final_granule {
+        pcm.truncate(to_16k(granule).max(pre_skip));
+    }
+    pcm.drain(..pre_skip);
+
+    if head.output_gain != 0 {
+        let gain = 10f32.powf(head.output_gain as f32 / (20.0 * 256.0));
+        for sample in &mut pcm {
+            *sample = (*sample as f32 * gain).clamp(i16::MIN as f32, i16::MAX as f32) as i16;
+        }
+    }
+    debug!("Decoded Ogg-Opus stream to {} samples ({} channel(s), pre-skip {})", pcm.len(), channels, pre_skip);
+    Ok(pcm)
+}
+
+/// Tracks the 1-byte bundle index sent in front of each BLE audio packet, to tell how many
+/// packets were lost in between (the index wraps at 255). Start a new one per connection.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BundleSequence {
+    expected: Option<u8>,
+    /// Stale packets seen in a row, see [`MAX_STALE_RUN`]
+    stale_run: u8,
+}
+
+/// A jump of at least this many bundles is taken as a step backwards (duplicate or reordered
+/// packet) rather than a loss
+const STALE_DISTANCE: u8 = 128;
+/// Stale packets in a row after which the sequence resynchronizes to the device's count
+/// (e.g. after a real gap of over [`STALE_DISTANCE`] bundles)
+const MAX_STALE_RUN: u8 = 4;
+
+impl BundleSequence {
+    /// Whether `bundle_index` is behind the sequence (a duplicate or reordered packet that
+    /// should be dropped)
+    pub fn is_stale(&self, bundle_index: u8) -> bool {
+        self.expected
+            .is_some_and(|expected| bundle_index.wrapping_sub(expected) >= STALE_DISTANCE && self.stale_run < MAX_STALE_RUN)
+    }
+
+    /// Record a received bundle index and return how many bundles were lost just before it
+    /// (0 for the first bundle seen and for in-order bundles). A stale index (see
+    /// [`is_stale`](Self::is_stale)) also returns 0 and leaves the sequence where it was.
+    pub fn observe(&mut self, bundle_index: u8) -> u8 {
+        if self.is_stale(bundle_index) {
+            self.stale_run += 1;
+            return 0;
+        }
+        let lost = match self.expected {
+            Some(expected) if self.stale_run < MAX_STALE_RUN => bundle_index.wrapping_sub(expected),
+            _ => 0,
+        };
+        self.stale_run = 0;
+        self.expected = Some(bundle_index.wrapping_add(1));
+        lost
+    }
+}
+
+/// Split a bundle into its frames: [num_frames:1][frame1_size:1][frame1_data:N]...
+///
+/// Bundles arrive over the radio, so the header is never trusted: a bundle claiming more
+/// frames or bytes than it holds yields just its complete frames, and slicing is done with
+/// `split_first`/`split_at` rather than offset arithmetic.
+fn bundle_frames(bundle_data: &[u8]) -> Vec<&[u8]> {
+    let Some((&num_frames, mut rest)) = bundle_data.split_first() else {
+        return Vec::new();
+    };
+    let mut frames = Vec::with_capacity(num_frames as usize);
+    for frame_idx in 0..num_frames {
+        let Some((&frame_size, data)) = rest.split_first() else {
+            warn!("Bundle truncated at frame {}", frame_idx);
+            break;
+        };
+        if frame_size as usize > data.len() {
+            warn!("Frame {} size exceeds bundle data", frame_idx);
+            break;
+        }
+        let (frame, next) = data.split_at(frame_size as usize);
+        frames.push(frame);
+        rest = next;
+    }
+    frames
 }
 
 /// Opus encoder wrapper
@@ -197,41 +413,62 @@ impl OpusEncoder {
     /// Create a new Opus encoder
     /// 
     /// # Arguments
-    /// * `sample_rate` - Sample rate in Hz (must be 16000)
-    /// * `frame_duration_ms` - Frame duration in milliseconds (must be 20 for Opus)
+    /// * `sample_rate` - Input sample rate in Hz: 8000, 12000, 16000, 24000 or 48000
+    /// * `frame_duration_ms` - Frame duration in milliseconds: 10, 20, 40 or 60
     pub fn new(sample_rate: u32, frame_duration_ms: u32) -> Result<Self> {
-        if sample_rate != 16000 {
-            anyhow::bail!("Opus encoder only supports 16kHz");
-        }
-        
-        if frame_duration_ms != 20 {
-            anyhow::bail!("Opus encoder only supports 20ms frames");
-        }
+        let opus_rate = opus_sample_rate(sample_rate)?;
+        check_frame_duration(frame_duration_ms)?;
         
         let frame_size_samples = (sample_rate * frame_duration_ms / 1000) as usize;
         
-        // Create Opus encoder (mono, 16kHz, optimized for voice)
+        // Create Opus encoder (mono, optimized for voice)
         let mut encoder = Encoder::new(
-            SampleRate::Hz16000,
+            opus_rate,
             Channels::Mono,
             Application::Voip,
         ).context("Failed to create Opus encoder")?;
         
-        // Configure encoder for optimal voice encoding
-        encoder.set_bitrate(audiopus::Bitrate::BitsPerSecond(24000))
-            .map_err(|e| anyhow::anyhow!("Failed to set bitrate: {:?}", e))?;
-        encoder.set_vbr(true)
-            .map_err(|e| anyhow::anyhow!("Failed to set VBR: {:?}", e))?;
-        encoder.set_complexity(5)
-            .map_err(|e| anyhow::anyhow!("Failed to set complexity: {:?}", e))?;
+        // Configure encoder for optimal voice encoding (tunable with the setters below)
         encoder.set_signal(audiopus::Signal::Voice)
             .map_err(|e| anyhow::anyhow!("Failed to set signal type: {:?}", e))?;
-        
-        Ok(Self {
+        let mut encoder = Self {
             encoder,
             sample_rate,
             frame_size_samples,
-        })
+        };
+        encoder.set_bitrate(DEFAULT_BITRATE)?;
+        encoder.set_vbr(true)?;
+        encoder.set_complexity(DEFAULT_COMPLEXITY)?;
+        Ok(encoder)
+    }
+
+    /// Set the target bitrate in bits/s (500 to 512000; default [`DEFAULT_BITRATE`]).
+    /// Higher for archival quality, lower for constrained links.
+    pub fn set_bitrate(&mut self, bits_per_second: i32) -> Result<()> {
+        if !BITRATE_RANGE.contains(&bits_per_second) {
+            anyhow::bail!(
+                "Opus bitrate must be {}-{} bits/s, got {}",
+                BITRATE_RANGE.start(), BITRATE_RANGE.end(), bits_per_second
+            );
+        }
+        self.encoder.set_bitrate(audiopus::Bitrate::BitsPerSecond(bits_per_second))
+            .map_err(|e| anyhow::anyhow!("Failed to set bitrate {}: {:?}", bits_per_second, e))
+    }
+
+    /// Set the encoder complexity, 0 (fastest) to 10 (best quality; default
+    /// [`DEFAULT_COMPLEXITY`])
+    pub fn set_complexity(&mut self, complexity: u8) -> Result<()> {
+        if complexity > 10 {
+            anyhow::bail!("Opus complexity must be 0-10, got {}", complexity);
+        }
+        self.encoder.set_complexity(complexity)
+            .map_err(|e| anyhow::anyhow!("Failed to set complexity {}: {:?}", complexity, e))
+    }
+
+    /// Turn variable bitrate on (the default) or off (constant bitrate, predictable packet sizes)
+    pub fn set_vbr(&mut self, vbr: bool) -> Result<()> {
+        self.encoder.set_vbr(vbr)
+            .map_err(|e| anyhow::anyhow!("Failed to set VBR: {:?}", e))
     }
 
     /// Encode a single PCM frame to Opus
@@ -247,8 +484,8 @@ impl OpusEncoder {
                 self.frame_size_samples, pcm_samples.len());
         }
         
-        // Allocate output buffer (max Opus frame size is typically ~400 bytes for 20ms)
-        let mut opus_frame = vec![0u8; 400];
+        // Allocate output buffer (large enough for any bitrate and frame duration)
+        let mut opus_frame = vec![0u8; MAX_PACKET_BYTES];
         
         // Encode frame
         let encoded_bytes = self.encoder
@@ -264,7 +501,7 @@ impl OpusEncoder {
     /// Encode a PCM buffer to Opus frames
     /// 
     /// # Arguments
-    /// * `pcm_samples` - PCM samples (any length, will be encoded in 20ms frames)
+    /// * `pcm_samples` - PCM samples (any length, will be encoded in frame-sized chunks)
     /// 
     /// # Returns
     /// Encoded Opus data (all frames concatenated)
@@ -276,7 +513,7 @@ impl OpusEncoder {
         let mut opus_data = Vec::new();
         let mut offset = 0;
         
-        // Encode in 20ms frames (320 samples at 16kHz)
+        // Encode in frames (320 samples = 20ms at 16kHz by default)
         while offset + self.frame_size_samples <= pcm_samples.len() {
             let frame = &pcm_samples[offset..offset + self.frame_size_samples];
             let encoded_frame = self.encode_frame(frame)
@@ -304,3 +541,222 @@ impl OpusEncoder {
         self.frame_size_samples
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Encode `frames` 20ms frames of a tone and pack them as a firmware bundle
+    fn bundle(frames: usize) -> Vec<u8> {
+        bundle_of(frames, 20)
+    }
+
+    /// Like [`bundle`] with frames of `frame_ms`
+    fn bundle_of(frames: usize, frame_ms: u32) -> Vec<u8> {
+        let mut encoder = OpusEncoder::new(16000, frame_ms).unwrap();
+        let frame_size = encoder.frame_size_samples();
+        let mut bundle = vec![frames as u8];
+        for f in 0..frames {
+            let pcm: Vec<i16> = (0..frame_size)
+                .map(|i| (((f * frame_size + i) as f32 * 0.1).sin() * 8000.0) as i16)
+                .collect();
+            let frame = encoder.encode_frame(&pcm).unwrap();
+            bundle.push(frame.len() as u8);
+            bundle.extend_from_slice(&frame);
+        }
+        bundle
+    }
+
+    /// Ogg-Opus file of `samples` (16 kHz mono) with the given pre-skip (at 48 kHz)
+    fn ogg_opus(samples: &[i16], pre_skip: u16) -> Vec<u8> {
+        let mut encoder = OpusEncoder::new(16000, 20).unwrap();
+        let mut head = b"OpusHead".to_vec();
+        head.extend_from_slice(&[1, 1]);
+        head.extend_from_slice(&pre_skip.to_le_bytes());
+        head.extend_from_slice(&16000u32.to_le_bytes());
+        head.extend_from_slice(&[0, 0, 0]);
+
+        let mut writer = ogg::writing::PacketWriter::new(std::io::Cursor::new(Vec::new()));
+        let end_page = ogg::writing::PacketWriteEndInfo::EndPage;
+        writer.write_packet(head, 7, end_page, 0).unwrap();
+        writer.write_packet(b"OpusTags\0\0\0\0\0\0\0\0".to_vec(), 7, end_page, 0).unwrap();
+        // Pad with the pre-skip worth of leading audio like a real encoder's delay
+        let padded: Vec<i16> = std::iter::repeat_n(0, pre_skip as usize / 3).chain(samples.iter().copied()).collect();
+        let frames: Vec<&[i16]> = padded.chunks(320).collect();
+        for (i, chunk) in frames.iter().enumerate() {
+            let mut frame = chunk.to_vec();
+            frame.resize(320, 0);
+            let last = i + 1 == frames.len();
+            let granule = if last { (pre_skip as usize + samples.len() * 3) as u64 } else { ((i + 1) * 960) as u64 };
+            let info = if last { ogg::writing::PacketWriteEndInfo::EndStream } else { end_page };
+            writer.write_packet(encoder.encode_frame(&frame).unwrap(), 7, info, granule).unwrap();
+        }
+        writer.into_inner().into_inner()
+    }
+
+    #[test]
+    fn ogg_file_decodes_to_original_length() {
+        let tone: Vec<i16> = (0..16000 + 100).map(|i| ((i as f32 * 0.1).sin() * 8000.0) as i16).collect();
+        let pcm = decode_ogg(std::io::Cursor::new(ogg_opus(&tone, 312))).unwrap();
+        assert_eq!(pcm.len(), tone.len());
+        assert!(pcm.iter().any(|&s| s.abs() > 1000));
+
+        let pcm = OpusDecoder::decode_ogg_reader(std::io::Cursor::new(ogg_opus(&tone, 0))).unwrap();
+        assert_eq!(pcm.len(), tone.len());
+    }
+
+    #[test]
+    fn ogg_rejects_non_opus_streams() {
+        assert!(decode_ogg(std::io::Cursor::new(Vec::new())).is_err());
+        let mut writer = ogg::writing::PacketWriter::new(std::io::Cursor::new(Vec::new()));
+        writer.write_packet(b"\x01vorbis".to_vec(), 1, ogg::writing::PacketWriteEndInfo::EndStream, 0).unwrap();
+        assert!(decode_ogg(std::io::Cursor::new(writer.into_inner().into_inner())).is_err());
+    }
+
+    #[test]
+    fn rejects_unsupported_config() {
+        let err = OpusDecoder::new(44100, 20).err().unwrap().to_string();
+        assert!(err.contains("44100") && err.contains("48000"), "{}", err);
+        assert!(OpusEncoder::new(22050, 20).is_err());
+        assert!(OpusDecoder::new(16000, 30).is_err());
+        assert!(OpusEncoder::new(16000, 5).is_err());
+    }
+
+    #[test]
+    fn encoder_settings_are_validated() {
+        let mut encoder = OpusEncoder::new(16000, 20).unwrap();
+        assert!(encoder.set_complexity(10).is_ok());
+        assert!(encoder.set_complexity(11).unwrap_err().to_string().contains("0-10"));
+        assert!(encoder.set_bitrate(64_000).is_ok());
+        assert!(encoder.set_bitrate(499).is_err());
+        assert!(encoder.set_bitrate(1_000_000).is_err());
+        assert!(encoder.set_vbr(false).is_ok());
+
+        let tone: Vec<i16> = (0..320).map(|i| ((i as f32 * 0.1).sin() * 8000.0) as i16).collect();
+        let high = encoder.encode_frame(&tone).unwrap().len();
+        encoder.set_bitrate(8000).unwrap();
+        let low = encoder.encode_frame(&tone).unwrap().len();
+        assert!(low < high, "{} bytes at 8kbps vs {} at 64kbps", low, high);
+    }
+
+    #[test]
+    fn round_trips_at_every_supported_rate() {
+        for rate in SUPPORTED_SAMPLE_RATES {
+            let mut encoder = OpusEncoder::new(rate, 20).unwrap();
+            let mut decoder = OpusDecoder::new(rate, 20).unwrap();
+            let frame_size = (rate / 50) as usize;
+            assert_eq!(encoder.frame_size_samples(), frame_size);
+            let tone: Vec<i16> = (0..frame_size).map(|i| ((i as f32 * 0.05).sin() * 8000.0) as i16).collect();
+            let frame = encoder.encode_frame(&tone).unwrap();
+            assert_eq!(decoder.decode_frame(&frame).unwrap().len(), frame_size);
+        }
+    }
+
+    #[test]
+    fn decodes_every_supported_frame_duration() {
+        for frame_ms in SUPPORTED_FRAME_DURATIONS_MS {
+            let mut decoder = OpusDecoder::new(16000, frame_ms).unwrap();
+            let frame_size = (16 * frame_ms) as usize;
+            assert_eq!(decoder.frame_size_samples(), frame_size);
+            assert_eq!(decoder.decode_bundle(&bundle_of(2, frame_ms)).unwrap().len(), 2 * frame_size);
+            assert_eq!(decoder.decode_lost_frame().unwrap().len(), frame_size);
+        }
+    }
+
+    #[test]
+    fn decodes_every_frame_in_bundle() {
+        let mut decoder = OpusDecoder::new(16000, 20).unwrap();
+        assert_eq!(decoder.decode_bundle(&bundle(3)).unwrap().len(), 3 * 320);
+    }
+
+    #[test]
+    fn empty_bundle_decodes_to_nothing() {
+        let mut decoder = OpusDecoder::new(16000, 20).unwrap();
+        assert!(decoder.decode_bundle(&[]).unwrap().is_empty());
+        assert!(decoder.decode_bundle(&[0]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn truncated_bundle_keeps_complete_frames() {
+        let mut decoder = OpusDecoder::new(16000, 20).unwrap();
+        let data = bundle(2);
+        let second_frame_start = 2 + data[1] as usize;
+        // Header claims 2 frames but the second is cut short
+        let truncated = &data[..second_frame_start + 2];
+        assert_eq!(decoder.decode_bundle(truncated).unwrap().len(), 320);
+    }
+
+    #[test]
+    fn malformed_bundles_decode_without_panicking() {
+        let mut decoder = OpusDecoder::new(16000, 20).unwrap();
+        let valid = bundle(1);
+        let malformed: [&[u8]; 6] = [
+            &[255],                    // frames claimed, none present
+            &[255, 255, 1, 2, 3],      // frame size past the end
+            &[3, 0, 0, 0],             // empty frames
+            &[1, 0xFF, 0xFF],          // garbage frame bytes
+            &[2, 200],                 // size byte with no data
+            &valid[..valid.len() - 1], // last byte missing
+        ];
+        for data in malformed {
+            let _ = decoder.decode_bundle(data);
+            let _ = decoder.decode_bundle_with_fec(data);
+        }
+        assert_eq!(bundle_frames(&[255, 2, 7, 8, 9]), vec![&[7u8, 8][..]]);
+        assert!(bundle_frames(&[5]).is_empty());
+    }
+
+    #[test]
+    fn fec_bundle_prepends_recovered_frame() {
+        let mut decoder = OpusDecoder::new(16000, 20).unwrap();
+        assert_eq!(decoder.decode_bundle_with_fec(&bundle(2)).unwrap().len(), 3 * 320);
+    }
+
+    #[test]
+    fn bundle_sequence_counts_lost_bundles() {
+        let mut sequence = BundleSequence::default();
+        assert_eq!(sequence.observe(7), 0);
+        assert_eq!(sequence.observe(8), 0);
+        assert_eq!(sequence.observe(11), 2);
+        assert_eq!(sequence.observe(100), 88);
+        assert_eq!(sequence.observe(200), 99);
+        assert_eq!(sequence.observe(255), 54);
+        assert_eq!(sequence.observe(1), 1);
+    }
+
+    #[test]
+    fn bundle_sequence_skips_duplicate_and_reordered_bundles() {
+        let mut sequence = BundleSequence::default();
+        assert_eq!(sequence.observe(5), 0);
+        assert!(sequence.is_stale(5));
+        assert_eq!(sequence.observe(5), 0);
+        assert_eq!(sequence.observe(6), 0);
+        assert_eq!(sequence.observe(8), 1);
+        assert!(sequence.is_stale(7));
+        assert_eq!(sequence.observe(7), 0);
+        assert_eq!(sequence.observe(9), 0);
+
+        // The device restarted its count: resynchronize after a few stale packets
+        for index in 0..MAX_STALE_RUN {
+            assert_eq!(sequence.observe(index), 0);
+        }
+        assert!(!sequence.is_stale(MAX_STALE_RUN));
+        assert_eq!(sequence.observe(MAX_STALE_RUN), 0);
+        assert_eq!(sequence.observe(MAX_STALE_RUN + 1), 0);
+    }
+
+    #[test]
+    fn stats_count_decoded_and_lost_frames() {
+        let mut decoder = OpusDecoder::new(16000, 20).unwrap();
+        decoder.decode_bundle(&bundle(2)).unwrap();
+        decoder.decode_lost_frame().unwrap();
+        decoder.decode_bundle_with_fec(&bundle(1)).unwrap();
+        assert_eq!(
+            decoder.stats(),
+            DecodeStats { frames_decoded: 3, frames_lost: 2, samples_produced: 5 * 320, plc_frames: 1 }
+        );
+
+        decoder.reset_stats();
+        assert_eq!(decoder.stats(), DecodeStats::default());
+    }
+}
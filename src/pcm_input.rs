@@ -0,0 +1,260 @@
+/*
+ * Raw PCM input from another process or machine (`--input stdin` / `--input ws://...`)
+ *
+ * Audio must be 16kHz mono 16-bit little-endian PCM. Utterances are cut with the same
+ * energy VAD as Radio mode (speech start) and AutoStop (trailing silence).
+ */
+
+use anyhow::{Context, Result};
+use log::debug;
+use memo_stt::capture::{compute_rms, AutoStop};
+use std::io::Read;
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Sample rate of streamed PCM
+pub const PCM_SAMPLE_RATE: u32 = 16000;
+
+/// Utterances are flushed at this length even without a pause (whisper's window is 30s)
+const MAX_UTTERANCE_SAMPLES: usize = PCM_SAMPLE_RATE as usize * 28;
+
+/// Where streamed PCM comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PcmSource {
+    Stdin,
+    /// WebSocket URL to connect to; binary messages carry PCM
+    WebSocket(String),
+}
+
+impl PcmSource {
+    /// Parse the `--input` value: `stdin` or a `ws://` / `wss://` URL
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec.eq_ignore_ascii_case("stdin") || spec == "-" {
+            Ok(PcmSource::Stdin)
+        } else if spec.starts_with("ws://") || spec.starts_with("wss://") {
+            Ok(PcmSource::WebSocket(spec.to_string()))
+        } else {
+            anyhow::bail!("Unknown input {:?} (expected \"stdin\" or a ws:// URL)", spec)
+        }
+    }
+}
+
+/// Read PCM from `source` on a background thread, sending mono i16 chunks to `tx`.
+///
+/// The channel closes when the source ends (EOF or WebSocket close).
+pub fn spawn_reader(source: PcmSource, tx: mpsc::Sender<Vec<i16>>) -> std::thread::JoinHandle<Result<()>> {
+    std::thread::spawn(move || match source {
+        PcmSource::Stdin => read_stream(std::io::stdin().lock(), &tx),
+        PcmSource::WebSocket(url) => read_websocket(&url, &tx),
+    })
+}
+
+fn read_stream(mut reader: impl Read, tx: &mpsc::Sender<Vec<i16>>) -> Result<()> {
+    let mut buf = [0u8; 3200]; // 100ms
+    let mut decoder = PcmDecoder::default();
+    loop {
+        let n = reader.read(&mut buf).context("Failed to read PCM input")?;
+        if n == 0 {
+            debug!("PCM input reached EOF");
+            return Ok(());
+        }
+        if tx.send(decoder.decode(&buf[..n])).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+fn read_websocket(url: &str, tx: &mpsc::Sender<Vec<i16>>) -> Result<()> {
+    let (mut socket, _) = tungstenite::connect(url).with_context(|| format!("Failed to connect to {}", url))?;
+    println!("Connected to {}", url);
+    let mut decoder = PcmDecoder::default();
+    loop {
+        match socket.read() {
+            Ok(tungstenite::Message::Binary(data)) => {
+                if tx.send(decoder.decode(&data)).is_err() {
+                    return Ok(());
+                }
+            }
+            Ok(tungstenite::Message::Close(_)) => {
+                debug!("WebSocket closed by peer");
+                return Ok(());
+            }
+            Ok(_) => {} // Text/ping/pong carry no audio
+            Err(tungstenite::Error::ConnectionClosed) => return Ok(()),
+            Err(e) => return Err(e).context("WebSocket read failed"),
+        }
+    }
+}
+
+/// Little-endian i16 decoder that carries an odd trailing byte over to the next read
+#[derive(Default)]
+struct PcmDecoder {
+    pending: Option<u8>,
+}
+
+impl PcmDecoder {
+    fn decode(&mut self, data: &[u8]) -> Vec<i16> {
+        let mut bytes = Vec::with_capacity(data.len() + 1);
+        bytes.extend(self.pending.take());
+        bytes.extend_from_slice(data);
+        if bytes.len() % 2 == 1 {
+            self.pending = bytes.pop();
+        }
+        bytes
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect()
+    }
+}
+
+/// What happened after feeding audio to the [`Segmenter`]
+#[derive(Debug, PartialEq)]
+pub enum SegmentEvent {
+    /// Speech started; audio is now being buffered
+    Started,
+    /// A complete utterance, ready to transcribe
+    Utterance(Vec<i16>),
+}
+
+/// Cuts a continuous stream into utterances: starts once RMS stays above the speech
+/// threshold for `VAD_SPEECH_START_MS`, ends after `VAD_SILENCE_MS` below the silence
+/// threshold (same env vars and defaults as Radio mode).
+pub struct Segmenter {
+    speech_threshold: f32,
+    start_samples: usize,
+    window: usize,
+    above: usize,
+    pending: Vec<i16>,
+    recording: bool,
+    utterance: Vec<i16>,
+    auto_stop: AutoStop,
+}
+
+impl Segmenter {
+    pub fn from_env() -> Self {
+        let env = |name: &str, default: f32| {
+            std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        };
+        let speech_threshold = env("VAD_SPEECH_THRESHOLD", 800.0);
+        let silence_threshold = env("VAD_SILENCE_THRESHOLD", 600.0);
+        let start_ms = env("VAD_SPEECH_START_MS", 200.0) as u64;
+        let silence_ms = env("VAD_SILENCE_MS", 1200.0) as u64;
+        Self {
+            speech_threshold,
+            start_samples: (PCM_SAMPLE_RATE as u64 * start_ms / 1000) as usize,
+            window: PCM_SAMPLE_RATE as usize / 20, // 50ms, like the Radio mode poll
+            above: 0,
+            pending: Vec::new(),
+            recording: false,
+            utterance: Vec::new(),
+            auto_stop: AutoStop::new(PCM_SAMPLE_RATE, silence_threshold, Duration::from_millis(silence_ms)),
+        }
+    }
+
+    /// Whether an utterance is in progress
+    pub fn is_recording(&self) -> bool {
+        self.recording
+    }
+
+    /// Feed a chunk of audio; returns the state changes it caused, in order.
+    pub fn push(&mut self, samples: &[i16]) -> Vec<SegmentEvent> {
+        let mut events = Vec::new();
+        self.pending.extend_from_slice(samples);
+        let full = self.pending.len() / self.window * self.window;
+        let chunk: Vec<i16> = self.pending.drain(..full).collect();
+        for window in chunk.chunks(self.window) {
+            if !self.recording {
+                if compute_rms(window) > self.speech_threshold {
+                    self.above += window.len();
+                    // Keep the onset so the first word isn't clipped
+                    self.utterance.extend_from_slice(window);
+                    if self.above >= self.start_samples {
+                        self.recording = true;
+                        self.auto_stop.reset();
+                        self.auto_stop.push(&self.utterance);
+                        events.push(SegmentEvent::Started);
+                    }
+                } else {
+                    self.above = 0;
+                    self.utterance.clear();
+                }
+                continue;
+            }
+
+            self.utterance.extend_from_slice(window);
+            if self.auto_stop.push(window) || self.utterance.len() >= MAX_UTTERANCE_SAMPLES {
+                events.extend(self.end_utterance());
+            }
+        }
+        events
+    }
+
+    /// End of input: flush the utterance in progress, if any.
+    pub fn finish(&mut self) -> Option<SegmentEvent> {
+        let rest = std::mem::take(&mut self.pending);
+        if self.recording {
+            self.utterance.extend_from_slice(&rest);
+        }
+        self.end_utterance()
+    }
+
+    fn end_utterance(&mut self) -> Option<SegmentEvent> {
+        let was_recording = std::mem::replace(&mut self.recording, false);
+        self.above = 0;
+        let utterance = std::mem::take(&mut self.utterance);
+        was_recording.then_some(SegmentEvent::Utterance(utterance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_input_spec() {
+        assert_eq!(PcmSource::parse("stdin").unwrap(), PcmSource::Stdin);
+        assert_eq!(
+            PcmSource::parse("ws://phone.local:9000/pcm").unwrap(),
+            PcmSource::WebSocket("ws://phone.local:9000/pcm".into())
+        );
+        assert!(PcmSource::parse("/dev/audio").is_err());
+    }
+
+    #[test]
+    fn decoder_carries_odd_bytes() {
+        let mut decoder = PcmDecoder::default();
+        assert_eq!(decoder.decode(&[0x01, 0x02, 0x03]), vec![0x0201]);
+        assert_eq!(decoder.decode(&[0x04]), vec![0x0403]);
+    }
+
+    #[test]
+    fn segmenter_cuts_utterance_after_silence() {
+        let mut segmenter = Segmenter::from_env();
+        assert!(segmenter.push(&[0; 8000]).is_empty());
+
+        let events = segmenter.push(&[3000; 16000]);
+        assert_eq!(events, vec![SegmentEvent::Started]);
+
+        let events = segmenter.push(&[0; 32000]);
+        match events.as_slice() {
+            [SegmentEvent::Utterance(samples)] => {
+                // 1s of speech plus the trailing silence that ended it
+                assert!(samples.len() >= 16000 + 19200 && samples.len() < 16000 + 32000);
+                assert!(samples[..16000].iter().all(|&s| s == 3000));
+            }
+            other => panic!("expected one utterance, got {:?}", other),
+        }
+        assert!(segmenter.finish().is_none());
+    }
+
+    #[test]
+    fn finish_flushes_utterance_in_progress() {
+        let mut segmenter = Segmenter::from_env();
+        segmenter.push(&[3000; 16010]);
+        match segmenter.finish() {
+            Some(SegmentEvent::Utterance(samples)) => assert_eq!(samples.len(), 16010),
+            other => panic!("expected utterance, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Concurrent transcription with one copy of the model
+//!
+//! An [`SttEngine`] runs one transcription at a time, so a server sharing one engine has to
+//! queue its requests. An [`SttEnginePool`] holds several engines that share the model
+//! weights but each have their own decoder state, and hands them out to callers on any
+//! thread: up to `n_states` requests transcribe at once, later ones wait for a free engine.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use memo_stt::pool::SttEnginePool;
+//! use memo_stt::SttEngine;
+//! use std::sync::Arc;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let pool = Arc::new(SttEnginePool::new(SttEngine::new_default(16000)?, 4)?);
+//!
+//! let handles: Vec<_> = (0..8).map(|_| {
+//!     let pool = Arc::clone(&pool);
+//!     std::thread::spawn(move || {
+//!         let samples: Vec<i16> = vec![]; // e.g. the body of an HTTP request
+//!         pool.transcribe(&samples)
+//!     })
+//! }).collect();
+//! for handle in handles {
+//!     println!("{}", handle.join().unwrap()?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::{Condvar, Mutex};
+use crate::{Result, SttEngine};
+
+/// Engines sharing one model, for transcribing from several threads at once.
+///
+/// Each engine beyond the first costs whisper's per-state buffers (tens of MB on the CPU,
+/// more on a GPU) rather than another copy of the model. Settings are copied from the
+/// engine passed to [`new`](Self::new); change them on every engine with
+/// [`for_each`](Self::for_each). `SttEnginePool` is `Send + Sync`, so share it with an
+/// `Arc` instead of a `Mutex`.
+pub struct SttEnginePool {
+    idle: Mutex<Vec<SttEngine>>,
+    returned: Condvar,
+    size: usize,
+}
+
+impl SttEnginePool {
+    /// A pool of `n_states` engines: `engine` plus `n_states - 1` more with the same
+    /// settings and model.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if `n_states` is 0, and
+    /// [`Error::Model`](crate::Error::Model) if whisper can't allocate another state.
+    pub fn new(engine: SttEngine, n_states: usize) -> Result<Self> {
+        if n_states == 0 {
+            return Err(crate::Error::Config("A pool needs at least one engine".into()));
+        }
+        let mut engines = Vec::with_capacity(n_states);
+        for _ in 1..n_states {
+            engines.push(engine.sibling()?);
+        }
+        engines.push(engine);
+        Ok(Self { idle: Mutex::new(engines), returned: Condvar::new(), size: n_states })
+    }
+
+    /// Transcribe on a free engine, waiting for one if all are busy.
+    /// See [`SttEngine::transcribe`].
+    pub fn transcribe(&self, samples: &[i16]) -> Result<String> {
+        self.with(|engine| engine.transcribe(samples))
+    }
+
+    /// Run `f` on a free engine, waiting for one if all are busy, e.g. to use
+    /// [`transcribe_segments`](SttEngine::transcribe_segments) or per-request options.
+    ///
+    /// Settings changed inside `f` stay on that one engine; use
+    /// [`transcribe_with`](SttEngine::transcribe_with) for per-request overrides.
+    pub fn with<T>(&self, f: impl FnOnce(&mut SttEngine) -> T) -> T {
+        let mut checkout = Checkout { pool: self, engine: Some(self.take()) };
+        f(checkout.engine.as_mut().expect("engine is only taken on drop"))
+    }
+
+    /// Apply `f` to every engine, e.g. to change the prompt for all later requests.
+    /// Waits until no engine is in use.
+    pub fn for_each(&self, mut f: impl FnMut(&mut SttEngine)) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        while idle.len() < self.size {
+            idle = self.returned.wait(idle).unwrap_or_else(|e| e.into_inner());
+        }
+        idle.iter_mut().for_each(&mut f);
+    }
+
+    /// Number of engines, i.e. how many transcriptions can run at once
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Engines not in use right now
+    pub fn available(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    fn take(&self) -> SttEngine {
+        // The lock only guards the list, so a poisoned one is still consistent
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(engine) = idle.pop() {
+                return engine;
+            }
+            idle = self.returned.wait(idle).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+}
+
+/// An engine taken from the pool, put back when dropped (even if the caller panicked)
+struct Checkout<'a> {
+    pool: &'a SttEnginePool,
+    engine: Option<SttEngine>,
+}
+
+impl Drop for Checkout<'_> {
+    fn drop(&mut self) {
+        if let Some(engine) = self.engine.take() {
+            self.pool.idle.lock().unwrap_or_else(|e| e.into_inner()).push(engine);
+            self.pool.returned.notify_all();
+        }
+    }
+}
@@ -0,0 +1,645 @@
+//! Transcript post-processing
+//!
+//! Whisper returns a verbatim transcript. A [`TranscriptProcessor`] turns it into the
+//! cleaned text that gets shown or injected, while the raw transcript stays available
+//! via [`SttEngine::transcribe_detailed`](crate::SttEngine::transcribe_detailed).
+
+use regex::Regex;
+use crate::Result;
+
+/// Turns a raw transcript into cleaned text.
+///
+/// Implement this to plug your own cleanup (LLM rewrite, custom vocabulary fixes, etc.)
+/// into the engine with [`SttEngine::set_processor`](crate::SttEngine::set_processor).
+pub trait TranscriptProcessor: Send + Sync {
+    /// Return the cleaned version of `raw`. Must return an empty string for empty input.
+    fn process(&self, raw: &str) -> String;
+}
+
+/// Processor that returns the transcript unchanged (the engine default)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopProcessor;
+
+impl TranscriptProcessor for NoopProcessor {
+    fn process(&self, raw: &str) -> String {
+        raw.to_string()
+    }
+}
+
+/// Filler words removed by [`RuleBasedProcessor::default`]
+const DEFAULT_FILLER_WORDS: &[&str] = &["um", "uh", "uhm", "erm"];
+
+/// Filler words and phrases removed by [`remove_disfluencies`].
+///
+/// Multi-word fillers are only removed when set off by a comma ("it was, you know, fine"),
+/// so "do you know the way" is left alone.
+pub const DEFAULT_DISFLUENCIES: &[&str] = &["um", "uh", "uhm", "erm", "hmm", "you know", "i mean"];
+
+/// Words that are legitimately doubled in normal prose ("that that clause", "had had") and
+/// are never collapsed as stutters.
+const LEGIT_REPEATS: &[&str] = &[
+    "that", "had", "is", "do", "very", "really", "so", "no", "yes", "yeah", "ha", "bye", "well", "many", "long",
+];
+
+/// Simple rule-based cleanup: filler-word removal, regex replacements, and
+/// sentence capitalization (applied in that order).
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::postprocess::{RuleBasedProcessor, TranscriptProcessor};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let processor = RuleBasedProcessor::default()
+///     .with_replacement(r"(?i)\bmemo s t t\b", "memo-stt")?;
+/// assert_eq!(processor.process("um, try memo s t t. it works"), "Try memo-stt. It works");
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RuleBasedProcessor {
+    filler_words: Vec<String>,
+    replacements: Vec<(Regex, String)>,
+    capitalize: bool,
+}
+
+impl Default for RuleBasedProcessor {
+    fn default() -> Self {
+        Self {
+            filler_words: DEFAULT_FILLER_WORDS.iter().map(|w| w.to_string()).collect(),
+            replacements: Vec::new(),
+            capitalize: true,
+        }
+    }
+}
+
+impl RuleBasedProcessor {
+    /// Replace the list of filler words or phrases (matched case-insensitively as whole words)
+    pub fn with_filler_words(mut self, words: &[&str]) -> Self {
+        self.filler_words = words.iter().map(|w| w.to_lowercase()).collect();
+        self
+    }
+
+    /// Add a regex replacement; `replacement` may use `$1`-style capture references
+    pub fn with_replacement(mut self, pattern: &str, replacement: &str) -> Result<Self> {
+        let re = Regex::new(pattern)
+            .map_err(|e| crate::Error::Config(format!("Invalid replacement pattern: {}", e)))?;
+        self.replacements.push((re, replacement.to_string()));
+        Ok(self)
+    }
+
+    /// Enable or disable capitalizing the first letter of each sentence
+    pub fn with_capitalization(mut self, enabled: bool) -> Self {
+        self.capitalize = enabled;
+        self
+    }
+}
+
+impl TranscriptProcessor for RuleBasedProcessor {
+    fn process(&self, raw: &str) -> String {
+        let mut text = remove_fillers(raw, &self.filler_words);
+
+        for (re, replacement) in &self.replacements {
+            text = re.replace_all(&text, replacement.as_str()).into_owned();
+        }
+
+        if self.capitalize {
+            text = capitalize_sentences(&text);
+        }
+        text
+    }
+}
+
+/// Uppercase the first letter of the text and of each word following `.`, `!` or `?`.
+fn capitalize_sentences(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut at_sentence_start = true;
+    for c in text.chars() {
+        if at_sentence_start && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+            at_sentence_start = false;
+        } else {
+            out.push(c);
+            if c == '.' || c == '!' || c == '?' {
+                at_sentence_start = true;
+            } else if !c.is_whitespace() {
+                at_sentence_start = false;
+            }
+        }
+    }
+    out
+}
+
+/// Remove filler words ([`DEFAULT_DISFLUENCIES`]) and stutter repeats ("the the meeting").
+///
+/// Kept conservative: words that are legitimately doubled ("that that clause") are never
+/// collapsed, and multi-word fillers must be set off by a comma.
+///
+/// ```
+/// use memo_stt::postprocess::remove_disfluencies;
+///
+/// assert_eq!(remove_disfluencies("Um, the the meeting is, you know, at noon."), "The meeting is at noon.");
+/// assert_eq!(remove_disfluencies("I said that that clause stays"), "I said that that clause stays");
+/// ```
+pub fn remove_disfluencies(text: &str) -> String {
+    remove_disfluencies_with(text, DEFAULT_DISFLUENCIES)
+}
+
+/// [`remove_disfluencies`] with a custom list of filler words/phrases.
+pub fn remove_disfluencies_with<S: AsRef<str>>(text: &str, fillers: &[S]) -> String {
+    collapse_repeats(&remove_fillers(text, fillers))
+}
+
+/// Lowercased word without surrounding punctuation (apostrophes kept)
+fn bare_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'').to_lowercase()
+}
+
+fn ends_sentence(word: &str) -> bool {
+    word.ends_with(['.', '!', '?'])
+}
+
+/// Uppercase the first character of `word`
+fn capitalize_word(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn remove_fillers<S: AsRef<str>>(text: &str, fillers: &[S]) -> String {
+    let mut fillers: Vec<Vec<String>> = fillers
+        .iter()
+        .map(|f| f.as_ref().split_whitespace().map(bare_word).collect::<Vec<_>>())
+        .filter(|f| !f.is_empty())
+        .collect();
+    // Longest match first so "you know" wins over a configured "you"
+    fillers.sort_by_key(|f| std::cmp::Reverse(f.len()));
+    let words: Vec<&str> = text.split_whitespace().collect();
+
+    let mut out: Vec<String> = Vec::with_capacity(words.len());
+    let mut capitalize_next = false;
+    let mut i = 0;
+    while i < words.len() {
+        let set_off = |n: usize, out: &[String]| {
+            n == 1
+                || words[i + n - 1].ends_with(',')
+                || out.last().is_none_or(|p| p.ends_with(',') || ends_sentence(p))
+        };
+        let matched = fillers
+            .iter()
+            .filter(|f| i + f.len() <= words.len())
+            .filter(|f| words[i..i + f.len()].iter().zip(f.iter()).all(|(w, f)| bare_word(w) == *f))
+            .map(Vec::len)
+            .find(|&n| set_off(n, &out));
+
+        let Some(n) = matched else {
+            let word = if capitalize_next { capitalize_word(words[i]) } else { words[i].to_string() };
+            capitalize_next = false;
+            out.push(word);
+            i += 1;
+            continue;
+        };
+
+        let last = words[i + n - 1];
+        if let Some(prev) = out.last_mut() {
+            if ends_sentence(last) && !ends_sentence(prev) {
+                // Keep the sentence boundary the filler carried ("go um." -> "go.")
+                let trimmed_len = prev.trim_end_matches(',').len();
+                prev.truncate(trimmed_len);
+                prev.push_str(&last[last.trim_end_matches(['.', '!', '?']).len()..]);
+            } else if last.ends_with(',') && prev.ends_with(',') {
+                // "it was, you know, fine" -> "it was fine"
+                prev.pop();
+            }
+        }
+        // "Um, so we..." -> "So we..."
+        if words[i].starts_with(char::is_uppercase) && out.last().is_none_or(|p| ends_sentence(p)) {
+            capitalize_next = true;
+        }
+        i += n;
+    }
+    out.join(" ")
+}
+
+fn collapse_repeats(text: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let bare = bare_word(word);
+        if let Some(prev) = out.last_mut() {
+            if !bare.is_empty()
+                && bare_word(prev) == bare
+                && !ends_sentence(prev)
+                && !LEGIT_REPEATS.contains(&bare.as_str())
+            {
+                // Keep the first occurrence's casing and the second occurrence's punctuation
+                let word_len = prev.trim_end_matches(|c: char| !c.is_alphanumeric()).len();
+                prev.truncate(word_len);
+                prev.push_str(&word[word.trim_end_matches(|c: char| !c.is_alphanumeric()).len()..]);
+                continue;
+            }
+        }
+        out.push(word.to_string());
+    }
+    out.join(" ")
+}
+
+/// Final touch-ups for where the text is going, e.g. dictating into the middle of an
+/// existing sentence. All off by default.
+///
+/// Applied by the engine last, after the [`TranscriptProcessor`] and disfluency removal
+/// (see [`SttEngine::set_output_style`](crate::SttEngine::set_output_style)).
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::postprocess::OutputStyle;
+///
+/// let inline = OutputStyle { lowercase_first: true, strip_trailing_period: true, trim: true };
+/// assert_eq!(inline.apply(" The quarterly numbers. "), "the quarterly numbers");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputStyle {
+    /// Lowercase the first letter, unless the first word is "I" or an acronym ("NASA")
+    pub lowercase_first: bool,
+    /// Drop a single trailing `.` (an ellipsis is kept)
+    pub strip_trailing_period: bool,
+    /// Trim leading and trailing whitespace
+    pub trim: bool,
+}
+
+impl OutputStyle {
+    /// Apply the enabled transformations to `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = if self.trim { text.trim() } else { text }.to_string();
+
+        if self.strip_trailing_period {
+            let body_len = out.trim_end().len();
+            if out[..body_len].ends_with('.') && !out[..body_len].ends_with("..") {
+                out.remove(body_len - 1);
+            }
+        }
+
+        if self.lowercase_first {
+            let start = out.len() - out.trim_start().len();
+            let first_word = out[start..].split_whitespace().next().unwrap_or("");
+            let bare = first_word.trim_end_matches(|c: char| !c.is_alphanumeric());
+            let keep = bare == "I"
+                || bare.starts_with("I'")
+                || bare.chars().skip(1).any(|c| c.is_uppercase());
+            if !keep {
+                if let Some(c) = first_word.chars().next().filter(|c| c.is_uppercase()) {
+                    let lower: String = c.to_lowercase().collect();
+                    out.replace_range(start..start + c.len_utf8(), &lower);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Written form for dates and amounts of money spoken as words, e.g. "March third twenty
+/// twenty four" and "twenty dollars and fifty cents". Off by default; see
+/// [`SttEngine::set_locale`](crate::SttEngine::set_locale).
+///
+/// Only English phrases are recognized, whether spelled out or already partly written in
+/// digits by whisper ("March 3rd, 2024"). Dates need a month name, day and year; the
+/// currency symbol comes from the spoken unit (dollars, pounds, euros). Other numbers are
+/// left alone.
+///
+/// # Example
+///
+/// ```
+/// use memo_stt::postprocess::Locale;
+///
+/// let text = "Paid twenty dollars and fifty cents on March third twenty twenty four.";
+/// assert_eq!(Locale::EnUs.apply(text), "Paid $20.50 on 3/3/2024.");
+/// assert_eq!(Locale::EnCa.apply(text), "Paid $20.50 on 2024-03-03.");
+/// assert_eq!(Locale::De.apply(text), "Paid 20,50 $ on 3.3.2024.");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    /// `3/3/2024` (month first), `$1,250.50`
+    EnUs,
+    /// `3/3/2024` (day first), `£1,250.50`
+    EnGb,
+    /// `2024-03-03`, `$1,250.50`
+    EnCa,
+    /// `3.3.2024`, `1.250,50 €`
+    De,
+}
+
+impl Locale {
+    /// Rewrite spoken dates and amounts of money in `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let toks = tokenize(text);
+        let mut out = String::with_capacity(text.len());
+        let mut copied = 0;
+        let mut i = 0;
+        while i < toks.len() {
+            let found = date(&toks, i)
+                .map(|(d, end)| (self.format_date(d), end))
+                .or_else(|| money(&toks, i).map(|(m, end)| (self.format_money(m), end)));
+            match found {
+                Some((formatted, end)) => {
+                    out.push_str(&text[copied..toks[i].start]);
+                    out.push_str(&formatted);
+                    copied = toks[end - 1].end;
+                    i = end;
+                }
+                None => i += 1,
+            }
+        }
+        out.push_str(&text[copied..]);
+        out
+    }
+
+    fn format_date(&self, d: Date) -> String {
+        match self {
+            Locale::EnUs => format!("{}/{}/{}", d.month, d.day, d.year),
+            Locale::EnGb => format!("{}/{}/{}", d.day, d.month, d.year),
+            Locale::EnCa => format!("{}-{:02}-{:02}", d.year, d.month, d.day),
+            Locale::De => format!("{}.{}.{}", d.day, d.month, d.year),
+        }
+    }
+
+    fn format_money(&self, m: Money) -> String {
+        let (group, decimal) = if *self == Locale::De { ('.', ',') } else { (',', '.') };
+        let digits = m.units.to_string();
+        let mut number = String::new();
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                number.push(group);
+            }
+            number.push(c);
+        }
+        if let Some(cents) = m.cents {
+            number.push_str(&format!("{}{:02}", decimal, cents));
+        }
+        match self {
+            Locale::De => format!("{} {}", number, m.symbol),
+            _ => format!("{}{}", m.symbol, number),
+        }
+    }
+}
+
+/// A run of letters or digits, lowercased, with its byte range in the source text
+struct Tok<'a> {
+    word: String,
+    start: usize,
+    end: usize,
+    /// Text between the previous token and this one
+    gap: &'a str,
+    capitalized: bool,
+}
+
+fn tokenize(text: &str) -> Vec<Tok<'_>> {
+    let mut toks = Vec::new();
+    let mut prev_end = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if !c.is_alphanumeric() {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, c)) = chars.peek().filter(|(_, c)| c.is_alphanumeric()) {
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        toks.push(Tok {
+            word: text[start..end].to_lowercase(),
+            start,
+            end,
+            gap: &text[prev_end..start],
+            capitalized: c.is_uppercase(),
+        });
+        prev_end = end;
+    }
+    toks
+}
+
+/// The token at `i`, if it continues the phrase before it (separated by spaces or a hyphen)
+fn next_word<'t>(toks: &'t [Tok], i: usize) -> Option<&'t str> {
+    toks.get(i)
+        .filter(|t| t.gap.chars().all(|c| c.is_whitespace() || c == '-'))
+        .map(|t| t.word.as_str())
+}
+
+/// Like [`next_word`], also allowing a comma ("March 3rd, 2024")
+fn next_word_after_comma<'t>(toks: &'t [Tok], i: usize) -> Option<&'t str> {
+    toks.get(i)
+        .filter(|t| t.gap.chars().all(|c| c.is_whitespace() || c == '-' || c == ','))
+        .map(|t| t.word.as_str())
+}
+
+const UNITS: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten",
+    "eleven", "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+const TENS: [&str; 8] = ["twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety"];
+const ORDINALS: [&str; 20] = [
+    "", "first", "second", "third", "fourth", "fifth", "sixth", "seventh", "eighth", "ninth", "tenth",
+    "eleventh", "twelfth", "thirteenth", "fourteenth", "fifteenth", "sixteenth", "seventeenth",
+    "eighteenth", "nineteenth",
+];
+const MONTHS: [&str; 12] = [
+    "january", "february", "march", "april", "may", "june", "july", "august", "september",
+    "october", "november", "december",
+];
+
+/// "zero" to "nineteen"
+fn unit_value(word: &str) -> Option<u64> {
+    UNITS.iter().position(|&w| w == word).map(|n| n as u64)
+}
+
+/// "twenty" to "ninety"
+fn tens_value(word: &str) -> Option<u64> {
+    TENS.iter().position(|&w| w == word).map(|n| n as u64 * 10 + 20)
+}
+
+fn digits_value(word: &str) -> Option<u64> {
+    if word.len() <= 12 && word.bytes().all(|b| b.is_ascii_digit()) { word.parse().ok() } else { None }
+}
+
+/// A whole number, spelled out ("one hundred and five") or in digits: its value and the
+/// index of the token after it
+fn cardinal(toks: &[Tok], i: usize) -> Option<(u64, usize)> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Last { Start, Unit, Teen, Tens, Hundred, Scale }
+
+    if let Some(n) = toks.get(i).and_then(|t| digits_value(&t.word)) {
+        return Some((n, i + 1));
+    }
+    let (mut total, mut current, mut last, mut largest_scale) = (0u64, 0u64, Last::Start, u64::MAX);
+    let mut j = i;
+    while let Some(word) = if j == i { toks.get(j).map(|t| t.word.as_str()) } else { next_word(toks, j) } {
+        let is_number = |w: Option<&str>| w.is_some_and(|w| unit_value(w).is_some() || tens_value(w).is_some());
+        match word {
+            "zero" if last == Last::Start => return Some((0, j + 1)),
+            "a" if last == Last::Start && matches!(next_word(toks, j + 1), Some("hundred" | "thousand" | "million")) => {
+                current = 1;
+                last = Last::Unit;
+            }
+            "and" if matches!(last, Last::Hundred | Last::Scale) && is_number(next_word(toks, j + 1)) => {
+                j += 1;
+                continue;
+            }
+            "hundred" if matches!(last, Last::Unit | Last::Teen) && current < 100 => {
+                current *= 100;
+                last = Last::Hundred;
+            }
+            "thousand" | "million" if !matches!(last, Last::Start | Last::Scale) => {
+                let scale = if word == "thousand" { 1_000 } else { 1_000_000 };
+                if scale >= largest_scale {
+                    break;
+                }
+                total += current * scale;
+                current = 0;
+                largest_scale = scale;
+                last = Last::Scale;
+            }
+            _ => match (unit_value(word), tens_value(word)) {
+                (Some(n @ 1..=9), _) if matches!(last, Last::Start | Last::Tens | Last::Hundred | Last::Scale) => {
+                    current += n;
+                    last = Last::Unit;
+                }
+                (Some(n @ 10..=19), _) if matches!(last, Last::Start | Last::Hundred | Last::Scale) => {
+                    current += n;
+                    last = Last::Teen;
+                }
+                (_, Some(n)) if matches!(last, Last::Start | Last::Hundred | Last::Scale) => {
+                    current += n;
+                    last = Last::Tens;
+                }
+                _ => break,
+            },
+        }
+        j += 1;
+    }
+    (last != Last::Start).then_some((total + current, j))
+}
+
+/// 10 to 99 spelled out ("twelve", "forty two")
+fn two_digit(toks: &[Tok], i: usize) -> Option<(u64, usize)> {
+    let word = toks.get(i)?.word.as_str();
+    if let Some(n @ 10..=19) = unit_value(word) {
+        return Some((n, i + 1));
+    }
+    let tens = tens_value(word)?;
+    match next_word(toks, i + 1).and_then(unit_value) {
+        Some(n @ 1..=9) => Some((tens + n, i + 2)),
+        _ => Some((tens, i + 1)),
+    }
+}
+
+/// A year from 1000 to 2999: "2024", "two thousand and five", or read in pairs ("twenty
+/// twenty four", "nineteen oh five", "nineteen hundred")
+fn year(toks: &[Tok], i: usize) -> Option<(u64, usize)> {
+    let valid = |(n, end): (u64, usize)| (1000..=2999).contains(&n).then_some((n, end));
+    if let Some(found) = cardinal(toks, i).and_then(valid) {
+        return Some(found);
+    }
+    let (century, j) = two_digit(toks, i)?;
+    let (rest, end) = match next_word(toks, j)? {
+        "hundred" => (0, j + 1),
+        "oh" | "o" => match next_word(toks, j + 1).and_then(unit_value) {
+            Some(n @ 1..=9) => (n, j + 2),
+            _ => return None,
+        },
+        _ => two_digit(toks, j)?,
+    };
+    valid((century * 100 + rest, end))
+}
+
+/// Day of the month: "3rd", "3", "third", "twenty first"
+fn day(toks: &[Tok], i: usize) -> Option<(u32, usize)> {
+    let word = toks.get(i)?.word.as_str();
+    let numeric = ["st", "nd", "rd", "th"].iter().find_map(|s| word.strip_suffix(s)).unwrap_or(word);
+    let (n, end) = if let Some(n) = digits_value(numeric).filter(|_| numeric.len() <= 2) {
+        (n, i + 1)
+    } else if let Some(n) = ORDINALS.iter().position(|&w| w == word).filter(|&n| n > 0) {
+        (n as u64, i + 1)
+    } else if word == "twentieth" || word == "thirtieth" {
+        (if word == "twentieth" { 20 } else { 30 }, i + 1)
+    } else {
+        let tens = tens_value(word).filter(|&t| t <= 30)?;
+        let n = ORDINALS[1..10].iter().position(|&w| Some(w) == next_word(toks, i + 1))? as u64 + 1;
+        (tens + n, i + 2)
+    };
+    (1..=31).contains(&n).then_some((n as u32, end))
+}
+
+/// A capitalized month name ("may" the verb stays put)
+fn month(toks: &[Tok], i: usize) -> Option<u32> {
+    let tok = toks.get(i).filter(|t| t.capitalized)?;
+    MONTHS.iter().position(|&m| m == tok.word).map(|m| m as u32 + 1)
+}
+
+struct Date {
+    year: u64,
+    month: u32,
+    day: u32,
+}
+
+/// "March [the] third[,] twenty twenty four" or "[the] third of March[,] 2024"
+fn date(toks: &[Tok], i: usize) -> Option<(Date, usize)> {
+    let (month, day, after_day) = if let Some(month) = month(toks, i) {
+        let j = if next_word(toks, i + 1) == Some("the") { i + 2 } else { i + 1 };
+        next_word(toks, j)?;
+        let (day, end) = day(toks, j)?;
+        (month, day, end)
+    } else {
+        let j = if toks[i].word == "the" { i + 1 } else { i };
+        if j > i {
+            next_word(toks, j)?;
+        }
+        let (day, end) = day(toks, j)?;
+        if next_word(toks, end) != Some("of") {
+            return None;
+        }
+        next_word(toks, end + 1)?;
+        (month(toks, end + 1)?, day, end + 2)
+    };
+    next_word_after_comma(toks, after_day)?;
+    let (year, end) = year(toks, after_day)?;
+
+    let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    (day <= days_in_month).then_some((Date { year, month, day }, end))
+}
+
+struct Money {
+    units: u64,
+    cents: Option<u64>,
+    symbol: &'static str,
+}
+
+/// "twenty dollars [and fifty cents]", "20 pounds 50 pence"
+fn money(toks: &[Tok], i: usize) -> Option<(Money, usize)> {
+    let (units, j) = cardinal(toks, i)?;
+    let symbol = match next_word(toks, j)? {
+        "dollar" | "dollars" => "$",
+        "pound" | "pounds" => "£",
+        "euro" | "euros" => "€",
+        _ => return None,
+    };
+    let mut money = Money { units, cents: None, symbol };
+    let mut end = j + 1;
+
+    let k = if next_word_after_comma(toks, end) == Some("and") { end + 1 } else { end };
+    if next_word_after_comma(toks, k).is_some() {
+        if let Some((cents @ 1..=99, l)) = cardinal(toks, k) {
+            if matches!(next_word(toks, l), Some("cent" | "cents" | "penny" | "pence")) {
+                money.cents = Some(cents);
+                end = l + 1;
+            }
+        }
+    }
+    Some((money, end))
+}
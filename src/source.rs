@@ -0,0 +1,82 @@
+//! Audio sources that yield utterances to transcribe
+//!
+//! [`AudioSource`] lets transcription code run unchanged over a file ([`FileSource`]), the
+//! microphone ([`MicSource`](crate::capture::MicSource), feature `capture`), or anything
+//! else that produces speech, such as audio received from a BLE device.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use memo_stt::source::{AudioSource, FileSource};
+//! use memo_stt::SttEngine;
+//!
+//! fn transcribe_all(engine: &mut SttEngine, source: &mut dyn AudioSource) -> memo_stt::Result<()> {
+//!     while let Some(text) = engine.transcribe_source(source)? {
+//!         println!("{}", text);
+//!     }
+//!     Ok(())
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut engine = SttEngine::new_default(16000)?;
+//! transcribe_all(&mut engine, &mut FileSource::open("meeting.wav")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+use crate::audio::{decode_pcm, decode_wav, PcmFormat};
+use crate::Result;
+
+/// Something that produces mono `i16` utterances, e.g. a file or a microphone.
+///
+/// Sources may use any sample rate; [`SttEngine::transcribe_source`](crate::SttEngine::transcribe_source)
+/// resamples to the engine's input rate.
+pub trait AudioSource {
+    /// Sample rate of the audio returned by [`next_utterance`](Self::next_utterance)
+    fn sample_rate(&self) -> u32;
+
+    /// Block until the next utterance is available. Returns `Ok(None)` once the source is
+    /// exhausted (end of file, device gone).
+    fn next_utterance(&mut self) -> Result<Option<Vec<i16>>>;
+}
+
+/// A WAV or raw PCM file, yielded as a single utterance.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    samples: Option<Vec<i16>>,
+    sample_rate: u32,
+}
+
+impl FileSource {
+    /// Read a WAV file (see [`decode_wav`] for the supported formats).
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let (samples, sample_rate) = decode_wav(&read(path.as_ref())?)?;
+        Ok(Self::from_samples(samples, sample_rate))
+    }
+
+    /// Read a headerless PCM file with a known layout and sample rate.
+    pub fn open_raw(path: impl AsRef<Path>, format: PcmFormat, sample_rate: u32) -> Result<Self> {
+        let samples = decode_pcm(&read(path.as_ref())?, format)?;
+        Ok(Self::from_samples(samples, sample_rate))
+    }
+
+    /// Audio already in memory
+    pub fn from_samples(samples: Vec<i16>, sample_rate: u32) -> Self {
+        Self { samples: Some(samples), sample_rate }
+    }
+}
+
+fn read(path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|e| crate::Error::Audio(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+impl AudioSource for FileSource {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn next_utterance(&mut self) -> Result<Option<Vec<i16>>> {
+        Ok(self.samples.take())
+    }
+}
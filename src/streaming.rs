@@ -0,0 +1,259 @@
+//! Transcription of audio that arrives a little at a time
+//!
+//! A [`StreamingSession`] buffers pushed audio and transcribes it a window at a time, so
+//! text appears while the speaker is still talking. Drive it yourself with
+//! [`push`](StreamingSession::push) and [`poll`](StreamingSession::poll), or hand it to a
+//! thread with [`spawn`](StreamingSession::spawn) and feed it from an audio callback.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use memo_stt::streaming::StreamingSession;
+//! use memo_stt::SttEngine;
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let engine = SttEngine::new_default(16000)?;
+//! let mut session = StreamingSession::new(engine, Duration::from_secs(5))?;
+//!
+//! let chunk: Vec<i16> = vec![]; // e.g. 20ms of microphone audio
+//! session.push(&chunk);
+//! while let Some(text) = session.poll()? {
+//!     print!("{} ", text);
+//! }
+//!
+//! // Speaker stopped: transcribe the rest
+//! println!("{}", session.finalize()?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::mpsc;
+use std::time::Duration;
+use crate::{Result, SttEngine};
+
+/// Shortest pause a window is cut at, so words aren't split between windows
+const WINDOW_MIN_GAP_MS: u32 = 200;
+
+/// Sends audio chunks (mono, at the engine's input rate) to a [spawned](StreamingSession::spawn)
+/// session. Cheap to clone; the session stops once every sender is dropped.
+pub type AudioSender = mpsc::Sender<Vec<i16>>;
+
+/// Receives the text of each window a [spawned](StreamingSession::spawn) session transcribes.
+pub type ResultReceiver = mpsc::Receiver<Result<String>>;
+
+/// An [`SttEngine`] transcribing a stream of audio window by window.
+///
+/// Each window is transcribed on its own, without the text of earlier windows as context.
+/// A window ends at the last pause in its second half (and at least 1 second in) when there
+/// is one, so words are rarely cut in two; otherwise it is cut at its full length.
+pub struct StreamingSession {
+    engine: SttEngine,
+    window: usize, // samples at the engine's input rate
+    buffer: Vec<i16>,
+    finished: bool,
+}
+
+impl StreamingSession {
+    /// Transcribe with `engine` in windows of `window` (1 to 30 seconds).
+    ///
+    /// Longer windows give whisper more context and are more accurate; shorter ones show
+    /// text sooner.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Config`](crate::Error::Config) if `window` is outside 1-30 seconds or
+    /// the engine expects multi-channel input (see [`SttEngine::set_channels`]).
+    pub fn new(engine: SttEngine, window: Duration) -> Result<Self> {
+        if !(Duration::from_secs(1)..=Duration::from_secs(30)).contains(&window) {
+            return Err(crate::Error::Config(format!("Streaming window must be 1-30s, got {:?}", window)));
+        }
+        if engine.channels() != 1 {
+            return Err(crate::Error::Config(format!(
+                "Streaming needs a mono engine, this one expects {} channels",
+                engine.channels()
+            )));
+        }
+        let window = (window.as_secs_f64() * engine.input_sample_rate() as f64) as usize;
+        Ok(Self { engine, window, buffer: Vec::with_capacity(window), finished: false })
+    }
+
+    /// Append mono samples at the engine's input rate. Ignored once the session is
+    /// [finalized](Self::finalize).
+    pub fn push(&mut self, samples: &[i16]) {
+        if !self.finished {
+            self.buffer.extend_from_slice(samples);
+        }
+    }
+
+    /// Transcribe the next window if a full one is buffered, or return `Ok(None)` to wait
+    /// for more audio. Call until it returns `None` after each [`push`](Self::push).
+    ///
+    /// A window without speech yields an empty string.
+    pub fn poll(&mut self) -> Result<Option<String>> {
+        if self.finished || self.buffer.len() < self.window {
+            return Ok(None);
+        }
+        self.poll_window().map(Some)
+    }
+
+    /// Transcribe the first window of the (at least window-long) buffer
+    fn poll_window(&mut self) -> Result<String> {
+        let end = window_end(&self.buffer[..self.window], self.engine.input_sample_rate());
+        // Drop the window even if it fails, so one bad window doesn't stall the stream
+        let window: Vec<i16> = self.buffer.drain(..end).collect();
+        self.engine.transcribe(&window)
+    }
+
+    /// Transcribe whatever is still buffered, shorter than a window or not, and end the
+    /// session: later pushes are ignored and [`poll`](Self::poll) returns `None`.
+    ///
+    /// Call when the speaker stops so the last few seconds aren't lost. A tail under the 1
+    /// second whisper needs is padded with silence; with nothing buffered (or when called
+    /// again) this returns an empty string.
+    ///
+    /// If a full window fails, that window is dropped and the session stays open: call
+    /// `finalize` again for the audio after it.
+    pub fn finalize(&mut self) -> Result<String> {
+        if self.finished {
+            return Ok(String::new());
+        }
+        // Full windows first, in case the caller stopped polling
+        let mut parts = Vec::new();
+        while self.buffer.len() >= self.window {
+            parts.push(self.poll_window()?);
+        }
+        self.finished = true;
+        if !self.buffer.is_empty() {
+            let mut tail = std::mem::take(&mut self.buffer);
+            let min_samples = self.engine.input_sample_rate() as usize;
+            if tail.len() < min_samples {
+                tail.resize(min_samples, 0);
+            }
+            parts.push(self.engine.transcribe(&tail)?);
+        }
+        parts.retain(|part| !part.is_empty());
+        Ok(parts.join(" "))
+    }
+
+    /// Whether [`finalize`](Self::finalize) has been called
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Samples pushed but not transcribed yet
+    pub fn buffered_samples(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The engine, e.g. to change its prompt between windows
+    pub fn engine_mut(&mut self) -> &mut SttEngine {
+        &mut self.engine
+    }
+
+    /// End the session and get the engine back, dropping audio not transcribed yet
+    pub fn into_engine(self) -> SttEngine {
+        self.engine
+    }
+
+    /// Run the session on its own thread, fed through a channel.
+    ///
+    /// For push-based audio such as a cpal callback or BLE notifications, where the code
+    /// receiving audio can't also own the session: the callback sends each chunk to the
+    /// [`AudioSender`], and the text of every window arrives on the [`ResultReceiver`].
+    /// Once all senders are dropped the thread [finalizes](Self::finalize) the session,
+    /// sends the text of the remaining audio (unless it is empty) and exits, disconnecting
+    /// the receiver.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use memo_stt::streaming::StreamingSession;
+    /// use memo_stt::SttEngine;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let engine = SttEngine::new_default(16000)?;
+    /// let (audio, results) = StreamingSession::new(engine, Duration::from_secs(5))?.spawn();
+    ///
+    /// // In the audio callback:
+    /// let chunk: Vec<i16> = vec![];
+    /// let _ = audio.send(chunk);
+    ///
+    /// drop(audio); // Stop recording
+    /// for text in results {
+    ///     println!("{}", text?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn spawn(mut self) -> (AudioSender, ResultReceiver) {
+        let (audio_tx, audio_rx) = mpsc::channel::<Vec<i16>>();
+        let (result_tx, result_rx) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("memo-stt-streaming".into())
+            .spawn(move || {
+                for chunk in audio_rx {
+                    self.push(&chunk);
+                    while let Some(result) = self.poll().transpose() {
+                        if result_tx.send(result).is_err() {
+                            return; // Nobody is listening
+                        }
+                    }
+                }
+                match self.finalize() {
+                    Ok(text) if text.is_empty() => {}
+                    result => {
+                        let _ = result_tx.send(result);
+                    }
+                }
+            })
+            .expect("failed to spawn streaming thread");
+        (audio_tx, result_rx)
+    }
+}
+
+/// Where to cut a window: at its last pause from halfway on, when there is one, but never
+/// before the 1 second whisper needs (a shorter chunk fails with `AudioTooShort`)
+fn window_end(window: &[i16], rate: u32) -> usize {
+    let min_end = (window.len() / 2).max(rate as usize);
+    crate::audio::find_silence_boundaries(window, rate, WINDOW_MIN_GAP_MS)
+        .into_iter()
+        .rev()
+        .find(|&pause| pause >= min_end)
+        .unwrap_or(window.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::window_end;
+
+    /// Loud audio with a 200ms pause centered on `pause_at` seconds
+    fn speech_with_pause(rate: usize, secs: f32, pause_at: f32) -> Vec<i16> {
+        let pause = (rate as f32 * (pause_at - 0.1)) as usize..(rate as f32 * (pause_at + 0.1)) as usize;
+        (0..(rate as f32 * secs) as usize)
+            .map(|i| if pause.contains(&i) { 0 } else if i % 2 == 0 { 8000 } else { -8000 })
+            .collect()
+    }
+
+    #[test]
+    fn short_window_is_not_cut_below_one_second() {
+        let window = speech_with_pause(16000, 1.0, 0.6);
+        assert_eq!(window_end(&window, 16000), 16000);
+    }
+
+    #[test]
+    fn long_window_is_cut_at_a_pause_in_its_second_half() {
+        let window = speech_with_pause(16000, 4.0, 3.0);
+        assert!(window_end(&window, 16000).abs_diff(48000) <= 160);
+        // A pause in the first half is too early
+        let window = speech_with_pause(16000, 4.0, 1.5);
+        assert_eq!(window_end(&window, 16000), 64000);
+    }
+
+    #[test]
+    fn pause_after_one_second_is_used_in_a_short_window() {
+        let window = speech_with_pause(16000, 1.5, 1.2);
+        assert!(window_end(&window, 16000).abs_diff(19200) <= 160);
+    }
+}
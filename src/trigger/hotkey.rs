@@ -3,21 +3,174 @@
 //! Uses `rdev` to listen for keyboard events and trigger recording.
 
 use crate::trigger::{Trigger, TriggerEvent};
-use crate::utils::error::{Error, Result};
+use crate::{Error, Result};
 use rdev::{listen, Event, EventType, Key};
 use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Listener restart delays: doubled after each failure, reset once it has run a while
+const LISTENER_MIN_BACKOFF: Duration = Duration::from_secs(1);
+const LISTENER_MAX_BACKOFF: Duration = Duration::from_secs(30);
+const LISTENER_STABLE_AFTER: Duration = Duration::from_secs(60);
+/// Size of a Linux `struct input_event` (a `timeval` of two native words, then 8 bytes)
+#[cfg(target_os = "linux")]
+const EVDEV_EVENT_SIZE: usize = 2 * std::mem::size_of::<usize>() + 8;
+/// `EV_KEY` event type: key presses, releases and repeats
+#[cfg(target_os = "linux")]
+const EV_KEY: u16 = 0x01;
+
+/// In [`HotkeyMode::DoubleTapLock`], a press this soon after the previous release locks
+const DOUBLE_TAP_WINDOW: Duration = Duration::from_millis(400);
+
+/// How key presses map to activation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HotkeyMode {
+    /// Active while the key is held
+    #[default]
+    PushToTalk,
+    /// Each press starts or stops; releases are ignored
+    Toggle,
+    /// Push-to-talk, but a double tap keeps it active after the key is let go, until the
+    /// next press (for long dictation)
+    DoubleTapLock,
+}
+
+/// Turns key presses and releases into trigger events for a [`HotkeyMode`]. Independent of
+/// `rdev`, for driving the same logic from another key source.
+///
+/// With a minimum hold, a press only counts once the key has been down that long (see
+/// [`poll`](Self::poll)); a shorter brush of the key produces no events at all.
+#[derive(Debug, Clone)]
+pub struct HotkeyGesture {
+    mode: HotkeyMode,
+    min_hold: Duration,
+    /// Key is down (presses while held are key repeat)
+    held: bool,
+    /// When the current press started, until it has been held for `min_hold`
+    pending: Option<Instant>,
+    active: bool,
+    locked: bool,
+    /// The press that unlocked shouldn't have its release count as a tap
+    ignore_release: bool,
+    last_release: Option<Instant>,
+}
+
+impl HotkeyGesture {
+    /// Start inactive in `mode`, ignoring presses shorter than `min_hold` (zero counts every
+    /// press)
+    pub fn new(mode: HotkeyMode, min_hold: Duration) -> Self {
+        Self {
+            mode,
+            min_hold,
+            held: false,
+            pending: None,
+            active: false,
+            locked: false,
+            ignore_release: false,
+            last_release: None,
+        }
+    }
+
+    /// Whether the gesture currently counts as active
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Whether a double tap has locked it active ([`HotkeyMode::DoubleTapLock`] only)
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// The key went down at `now`; returns the event to emit, if any. Auto-repeat presses
+    /// while the key is held are ignored.
+    ///
+    /// With a minimum hold the event is usually deferred: call [`poll`](Self::poll) once
+    /// `min_hold` has passed. The second press of a double tap counts at once.
+    pub fn press(&mut self, now: Instant) -> Option<TriggerEvent> {
+        if std::mem::replace(&mut self.held, true) {
+            return None;
+        }
+        if self.min_hold.is_zero() || self.is_double_tap(now) {
+            return self.count_press(now);
+        }
+        self.pending = Some(now);
+        None
+    }
+
+    /// Emit the deferred event of a press that has now been held for the minimum hold
+    pub fn poll(&mut self, now: Instant) -> Option<TriggerEvent> {
+        let pressed = self.pending.filter(|&at| now.duration_since(at) >= self.min_hold)?;
+        self.pending = None;
+        self.count_press(pressed)
+    }
+
+    /// Whether the key is down but hasn't been held for the minimum hold yet
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    fn is_double_tap(&self, now: Instant) -> bool {
+        self.mode == HotkeyMode::DoubleTapLock
+            && !self.locked
+            && self.last_release.is_some_and(|at| now.duration_since(at) <= DOUBLE_TAP_WINDOW)
+    }
+
+    fn count_press(&mut self, now: Instant) -> Option<TriggerEvent> {
+        match self.mode {
+            HotkeyMode::PushToTalk => self.set_active(true),
+            HotkeyMode::Toggle => self.set_active(!self.active),
+            HotkeyMode::DoubleTapLock if self.locked => {
+                self.locked = false;
+                self.ignore_release = true;
+                self.set_active(false)
+            }
+            HotkeyMode::DoubleTapLock => {
+                self.locked = self.is_double_tap(now);
+                self.set_active(true)
+            }
+        }
+    }
+
+    /// The key went up at `now`; returns the event to emit, if any. Call
+    /// [`poll`](Self::poll) first, so a press held long enough isn't taken for a brush.
+    pub fn release(&mut self, now: Instant) -> Option<TriggerEvent> {
+        self.held = false;
+        if self.pending.take().is_some() {
+            // Too short to count, though a quick tap can still start a double tap
+            if self.mode == HotkeyMode::DoubleTapLock && !self.locked {
+                self.last_release = Some(now);
+            }
+            return None;
+        }
+        match self.mode {
+            HotkeyMode::PushToTalk => self.set_active(false),
+            HotkeyMode::Toggle => None,
+            HotkeyMode::DoubleTapLock if std::mem::take(&mut self.ignore_release) => None,
+            HotkeyMode::DoubleTapLock if self.locked => None,
+            HotkeyMode::DoubleTapLock => {
+                self.last_release = Some(now);
+                self.set_active(false)
+            }
+        }
+    }
+
+    fn set_active(&mut self, active: bool) -> Option<TriggerEvent> {
+        self.active = active;
+        Some(if active { TriggerEvent::Activated } else { TriggerEvent::Deactivated })
+    }
+}
 
 /// Hotkey trigger implementation
 ///
 /// Listens for a specific key press/release to activate/deactivate recording.
 pub struct HotkeyTrigger {
-    /// The key that triggers activation
-    trigger_key: Key,
+    /// The key that triggers activation (`None` when reading an evdev device)
+    trigger_key: Option<Key>,
     /// Current activation state
     is_active: Arc<AtomicBool>,
-    /// Channel sender for trigger events
-    event_tx: mpsc::Sender<TriggerEvent>,
+    /// Where the listener sends trigger events
+    events: EventSink,
     /// Channel receiver for trigger events (wrapped in Mutex for Sync)
     event_rx: Arc<Mutex<mpsc::Receiver<TriggerEvent>>>,
 }
@@ -27,39 +180,159 @@ impl HotkeyTrigger {
     ///
     /// # Arguments
     /// * `trigger_key` - The key to use as trigger (e.g., `Key::ControlLeft`)
+    /// * `mode` - Hold to talk, tap to toggle, or hold with double tap to lock
+    /// * `min_hold` - Presses shorter than this are ignored, so brushing the key doesn't
+    ///   start an empty recording (e.g. 150ms; zero counts every press)
     ///
     /// # Returns
-    /// A new `HotkeyTrigger` instance
-    pub fn new(trigger_key: Key) -> Result<Self> {
+    /// A new `HotkeyTrigger` instance, or `Error::Config` in a Linux Wayland session, where
+    /// global key grabs never see a key press (use [`from_evdev`](Self::from_evdev) there)
+    pub fn new(trigger_key: Key, mode: HotkeyMode, min_hold: Duration) -> Result<Self> {
+        #[cfg(target_os = "linux")]
+        if is_wayland_session() {
+            return Err(Error::Config(
+                "Global hotkeys don't work in a Wayland session; log in to an X11 session or use HotkeyTrigger::from_evdev".into(),
+            ));
+        }
+
         let (tx, rx) = mpsc::channel();
         let is_active = Arc::new(AtomicBool::new(false));
 
         let trigger = Self {
-            trigger_key,
+            trigger_key: Some(trigger_key),
             is_active: is_active.clone(),
-            event_tx: tx.clone(),
+            events: EventSink::new(tx),
             event_rx: Arc::new(Mutex::new(rx)),
         };
 
-        // Spawn thread to listen for keyboard events
-        let tx_for_listener = tx.clone();
+        // Spawn thread to listen for keyboard events. `listen` only returns on failure
+        // (e.g. accessibility permission revoked), so restart it with backoff.
+        let tx_for_listener = trigger.events.clone();
         std::thread::spawn(move || {
-            listen(move |event: Event| {
-                match event.event_type {
-                    EventType::KeyPress(key) if key == trigger_key => {
-                        let _ = tx_for_listener.send(TriggerEvent::Activated);
-                    }
-                    EventType::KeyRelease(key) if key == trigger_key => {
-                        let _ = tx_for_listener.send(TriggerEvent::Deactivated);
-                    }
+            let mut backoff = LISTENER_MIN_BACKOFF;
+            // Shared across restarts so a restart mid-gesture doesn't lose a lock
+            let gesture = Arc::new(Mutex::new(HotkeyGesture::new(mode, min_hold)));
+            loop {
+                let started = Instant::now();
+                let tx = tx_for_listener.clone();
+                let gesture = gesture.clone();
+                let result = listen(move |event: Event| match event.event_type {
+                    EventType::KeyPress(key) if key == trigger_key => handle_key(&gesture, &tx, true),
+                    EventType::KeyRelease(key) if key == trigger_key => handle_key(&gesture, &tx, false),
                     _ => {}
+                });
+                if started.elapsed() >= LISTENER_STABLE_AFTER {
+                    backoff = LISTENER_MIN_BACKOFF;
+                }
+                eprintln!(
+                    "Hotkey listener stopped ({:?}), restarting in {}s. On macOS, check Accessibility access in System Settings > Privacy & Security.",
+                    result.err(),
+                    backoff.as_secs()
+                );
+                std::thread::sleep(backoff);
+                backoff = (backoff * 2).min(LISTENER_MAX_BACKOFF);
+            }
+        });
+
+        Ok(trigger)
+    }
+
+    /// Create a hotkey trigger that reads a keyboard's evdev device directly, which works
+    /// under Wayland and without a display server.
+    ///
+    /// Needs read access to the device, usually by being in the `input` group. The device is
+    /// opened here, so a missing permission is reported right away.
+    ///
+    /// # Arguments
+    /// * `device` - The keyboard, e.g. `/dev/input/by-id/usb-...-event-kbd`
+    /// * `keycode` - Linux key code from `linux/input-event-codes.h` (e.g. 97 for
+    ///   `KEY_RIGHTCTRL`; X11 key codes are these plus 8)
+    /// * `mode` - Hold to talk, tap to toggle, or hold with double tap to lock
+    /// * `min_hold` - Presses shorter than this are ignored (zero counts every press)
+    ///
+    /// # Returns
+    /// A new `HotkeyTrigger` instance
+    #[cfg(target_os = "linux")]
+    pub fn from_evdev(device: impl AsRef<std::path::Path>, keycode: u16, mode: HotkeyMode, min_hold: Duration) -> Result<Self> {
+        let device = device.as_ref();
+        let mut file = std::fs::File::open(device).map_err(|e| {
+            Error::Config(format!(
+                "Cannot read keyboard device {}: {} (add yourself to the `input` group)",
+                device.display(),
+                e
+            ))
+        })?;
+
+        let (tx, rx) = mpsc::channel();
+        let trigger = Self {
+            trigger_key: None,
+            is_active: Arc::new(AtomicBool::new(false)),
+            events: EventSink::new(tx),
+            event_rx: Arc::new(Mutex::new(rx)),
+        };
+        let tx = trigger.events.clone();
+
+        let device = device.to_path_buf();
+        std::thread::spawn(move || {
+            use std::io::Read;
+            let gesture = Arc::new(Mutex::new(HotkeyGesture::new(mode, min_hold)));
+            let mut event = [0u8; EVDEV_EVENT_SIZE];
+            loop {
+                if let Err(e) = file.read_exact(&mut event) {
+                    eprintln!("Keyboard device {} stopped: {}", device.display(), e);
+                    return;
                 }
-            }).ok();
+                // struct input_event: timeval, then u16 type, u16 code, i32 value
+                let at = EVDEV_EVENT_SIZE - 8;
+                let kind = u16::from_ne_bytes([event[at], event[at + 1]]);
+                let code = u16::from_ne_bytes([event[at + 2], event[at + 3]]);
+                let value = i32::from_ne_bytes([event[at + 4], event[at + 5], event[at + 6], event[at + 7]]);
+                if kind != EV_KEY || code != keycode {
+                    continue;
+                }
+                match value {
+                    1 => handle_key(&gesture, &tx, true),
+                    0 => handle_key(&gesture, &tx, false),
+                    _ => {} // 2 is key repeat
+                }
+            }
         });
 
         Ok(trigger)
     }
 
+    /// Receive trigger events asynchronously, e.g. to `tokio::select!` between the hotkey and
+    /// other events without a thread blocked in [`recv`](Self::recv)
+    ///
+    /// Each call returns a new receiver that gets every event from then on; dropping it
+    /// unsubscribes. Events also still reach [`recv`](Self::recv), and `is_active` is only
+    /// updated by the blocking `Trigger` methods.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut events = trigger.subscribe();
+    /// loop {
+    ///     tokio::select! {
+    ///         Some(event) = events.recv() => match event {
+    ///             TriggerEvent::Activated => start_recording(),
+    ///             TriggerEvent::Deactivated => stop_recording(),
+    ///         },
+    ///         _ = shutdown.recv() => break,
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "async")]
+    pub fn subscribe(&self) -> tokio::sync::mpsc::UnboundedReceiver<TriggerEvent> {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        self.events.subscribers.lock().unwrap_or_else(|e| e.into_inner()).push(tx);
+        rx
+    }
+
+    /// The key this trigger listens for (`None` when reading an evdev device)
+    pub fn trigger_key(&self) -> Option<Key> {
+        self.trigger_key
+    }
+
     /// Get the next trigger event (non-blocking)
     ///
     /// # Returns
@@ -114,6 +387,107 @@ impl Trigger for HotkeyTrigger {
     }
 }
 
+/// Feed a press or release of the trigger key to `gesture` and send the resulting events.
+/// A press that has to be held first is checked again once the minimum hold has passed.
+fn handle_key(gesture: &Arc<Mutex<HotkeyGesture>>, tx: &EventSink, pressed: bool) {
+    let now = Instant::now();
+    let mut state = gesture.lock().unwrap_or_else(|e| e.into_inner());
+    let events = if pressed { [state.press(now), None] } else { [state.poll(now), state.release(now)] };
+    for event in events.into_iter().flatten() {
+        tx.send(event);
+    }
+    if pressed && state.is_pending() {
+        let min_hold = state.min_hold;
+        let (gesture, tx) = (gesture.clone(), tx.clone());
+        std::thread::spawn(move || {
+            std::thread::sleep(min_hold);
+            let event = gesture.lock().unwrap_or_else(|e| e.into_inner()).poll(Instant::now());
+            if let Some(event) = event {
+                tx.send(event);
+            }
+        });
+    }
+}
+
+/// Where a listener sends trigger events: the channel behind [`HotkeyTrigger::recv`], and
+/// any async subscribers
+#[derive(Clone)]
+struct EventSink {
+    tx: mpsc::Sender<TriggerEvent>,
+    #[cfg(feature = "async")]
+    subscribers: Arc<Mutex<Vec<tokio::sync::mpsc::UnboundedSender<TriggerEvent>>>>,
+}
+
+impl EventSink {
+    fn new(tx: mpsc::Sender<TriggerEvent>) -> Self {
+        Self {
+            tx,
+            #[cfg(feature = "async")]
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    fn send(&self, event: TriggerEvent) {
+        let _ = self.tx.send(event);
+        // Dropped receivers unsubscribe
+        #[cfg(feature = "async")]
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|subscriber| subscriber.send(event).is_ok());
+    }
+}
+
+/// Whether this is a Wayland session, where the X11 key grab `rdev` uses never sees a key
+/// press (even with XWayland's `DISPLAY` set)
+#[cfg(target_os = "linux")]
+fn is_wayland_session() -> bool {
+    match std::env::var("XDG_SESSION_TYPE") {
+        Ok(t) if t.trim().eq_ignore_ascii_case("wayland") => true,
+        Ok(t) if t.trim().eq_ignore_ascii_case("x11") => false,
+        _ => std::env::var_os("WAYLAND_DISPLAY").is_some(),
+    }
+}
+
+#[cfg(all(test, feature = "async"))]
+mod tests {
+    use super::{EventSink, HotkeyTrigger, TriggerEvent};
+    use std::sync::{atomic::AtomicBool, mpsc, Arc, Mutex};
+
+    /// A trigger without a listener thread; events are sent through `events` by hand
+    fn trigger() -> HotkeyTrigger {
+        let (tx, rx) = mpsc::channel();
+        HotkeyTrigger {
+            trigger_key: None,
+            is_active: Arc::new(AtomicBool::new(false)),
+            events: EventSink::new(tx),
+            event_rx: Arc::new(Mutex::new(rx)),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribers_get_every_event_after_subscribing() {
+        let trigger = trigger();
+        trigger.events.send(TriggerEvent::Activated);
+        let mut first = trigger.subscribe();
+        let mut second = trigger.subscribe();
+        trigger.events.send(TriggerEvent::Deactivated);
 
+        assert_eq!(first.recv().await, Some(TriggerEvent::Deactivated));
+        assert_eq!(second.recv().await, Some(TriggerEvent::Deactivated));
+        // The blocking receiver still sees everything
+        assert_eq!(trigger.try_recv(), Some(TriggerEvent::Activated));
+        assert_eq!(trigger.try_recv(), Some(TriggerEvent::Deactivated));
+    }
 
+    #[tokio::test]
+    async fn dropped_subscribers_are_removed() {
+        let trigger = trigger();
+        let mut kept = trigger.subscribe();
+        drop(trigger.subscribe());
+        trigger.events.send(TriggerEvent::Activated);
 
+        assert_eq!(kept.recv().await, Some(TriggerEvent::Activated));
+        assert_eq!(trigger.events.subscribers.lock().unwrap().len(), 1);
+    }
+}
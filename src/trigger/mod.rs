@@ -4,7 +4,7 @@
 //! to activate/deactivate STT recording. Different trigger types can be implemented
 //! (hotkey, button, wake word, etc.) and easily swapped at compile time.
 
-use crate::utils::error::Result;
+use crate::Result;
 
 /// Trigger event types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -41,13 +41,11 @@ pub enum TriggerType {
     Hotkey,
     /// Button trigger (hardware button - placeholder)
     Button,
-    /// Wake word trigger (voice activation - placeholder)
+    /// Wake word trigger (voice activation)
     WakeWord,
 }
 
 // Re-export trigger implementations
+#[cfg(feature = "hotkey")]
 pub mod hotkey;
-
-
-
-
+pub mod wake_word;
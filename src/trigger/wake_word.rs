@@ -0,0 +1,274 @@
+//! Wake word trigger implementation
+//!
+//! Transcribes a rolling window of microphone audio whenever it is loud enough to be speech,
+//! and activates when the wake phrase shows up in it. Deactivates after trailing silence.
+
+use crate::audio::rms;
+use crate::trigger::{Trigger, TriggerEvent};
+use crate::{Error, Result};
+use crate::SttEngine;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, atomic::{AtomicBool, Ordering}};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Audio transcribed when looking for the phrase; long enough for a two or three word phrase
+const WINDOW: Duration = Duration::from_millis(2500);
+/// New audio between two looks at the window
+const HOP: Duration = Duration::from_millis(500);
+/// Quiet audio after activation that counts as the end of the command
+const SILENCE_TIMEOUT: Duration = Duration::from_millis(1500);
+/// Speech energy (RMS, i16 scale) needed before the window is transcribed, at sensitivity 0
+/// and 1; quieter audio is never sent to whisper
+const MAX_SPEECH_RMS: f32 = 800.0;
+const MIN_SPEECH_RMS: f32 = 200.0;
+/// Chunks queued for the detector before new ones are dropped (it fell behind inference)
+const AUDIO_QUEUE: usize = 64;
+
+/// Wake word trigger implementation
+///
+/// Feed it microphone audio with [`push_audio`](Self::push_audio); it activates when the
+/// wake phrase is heard and deactivates once the speaker has been quiet for
+/// [`SILENCE_TIMEOUT`]. Detection runs `engine` on a background thread, so give it an engine
+/// of its own (a small English model such as `ggml-tiny.en.bin` is plenty).
+pub struct WakeWordTrigger {
+    /// Current activation state
+    is_active: Arc<AtomicBool>,
+    /// Audio for the detector thread
+    audio_tx: mpsc::SyncSender<Vec<i16>>,
+    /// Channel receiver for trigger events (wrapped in Mutex for Sync)
+    event_rx: Arc<Mutex<mpsc::Receiver<TriggerEvent>>>,
+}
+
+impl WakeWordTrigger {
+    /// Create a new wake word trigger
+    ///
+    /// # Arguments
+    /// * `phrase` - The wake phrase, e.g. `"hey memo"` (case and punctuation don't matter)
+    /// * `sensitivity` - `0.0` (only clear, exact matches) to `1.0` (quiet speech and near
+    ///   misses also activate, at the cost of false activations)
+    /// * `engine` - Engine used for spotting the phrase, at the rate audio will be pushed
+    ///
+    /// # Returns
+    /// A new `WakeWordTrigger` instance, or `Error::Config` if the phrase has no words or
+    /// the sensitivity is outside `0.0..=1.0`
+    pub fn new(phrase: &str, sensitivity: f32, mut engine: SttEngine) -> Result<Self> {
+        let phrase_words = words(phrase);
+        if phrase_words.is_empty() {
+            return Err(Error::Config(format!("Wake phrase {:?} has no words", phrase)));
+        }
+        if !(0.0..=1.0).contains(&sensitivity) {
+            return Err(Error::Config(format!("Sensitivity must be between 0 and 1, got {}", sensitivity)));
+        }
+
+        let (event_tx, event_rx) = mpsc::channel();
+        let (audio_tx, audio_rx) = mpsc::sync_channel::<Vec<i16>>(AUDIO_QUEUE);
+        let is_active = Arc::new(AtomicBool::new(false));
+
+        // Bias whisper towards the phrase, which is often a made-up word
+        engine.set_prompt(Some(phrase.to_string()));
+        let rate = engine.input_sample_rate() as usize;
+        let window = rate * WINDOW.as_millis() as usize / 1000;
+        let hop = rate * HOP.as_millis() as usize / 1000;
+        let silence_timeout = rate * SILENCE_TIMEOUT.as_millis() as usize / 1000;
+        let speech_rms = MAX_SPEECH_RMS - (MAX_SPEECH_RMS - MIN_SPEECH_RMS) * sensitivity;
+
+        // The thread ends when the trigger (and with it `audio_tx`) is dropped
+        std::thread::spawn(move || {
+            let mut buffer: VecDeque<i16> = VecDeque::with_capacity(window);
+            let mut since_check = 0;
+            let mut active = false;
+            let mut quiet = 0;
+            for chunk in audio_rx {
+                if active {
+                    if rms(&chunk) < speech_rms / 2.0 {
+                        quiet += chunk.len();
+                    } else {
+                        quiet = 0;
+                    }
+                    if quiet >= silence_timeout {
+                        active = false;
+                        buffer.clear();
+                        since_check = 0;
+                        let _ = event_tx.send(TriggerEvent::Deactivated);
+                    }
+                    continue;
+                }
+
+                buffer.extend(&chunk);
+                if buffer.len() > window {
+                    buffer.drain(..buffer.len() - window);
+                }
+                since_check += chunk.len();
+                if since_check < hop || buffer.len() < window {
+                    continue;
+                }
+                since_check = 0;
+
+                let samples: Vec<i16> = buffer.iter().copied().collect();
+                if rms(&samples) < speech_rms {
+                    continue;
+                }
+                match engine.transcribe(&samples) {
+                    Ok(text) if matches_phrase(&text, &phrase_words, sensitivity) => {
+                        active = true;
+                        quiet = 0;
+                        buffer.clear();
+                        let _ = event_tx.send(TriggerEvent::Activated);
+                    }
+                    Ok(_) => {}
+                    Err(e) => eprintln!("Wake word detection failed: {}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            is_active,
+            audio_tx,
+            event_rx: Arc::new(Mutex::new(event_rx)),
+        })
+    }
+
+    /// Feed microphone audio (mono, at the engine's input rate) to the detector.
+    ///
+    /// Never blocks: if detection has fallen behind, the chunk is dropped.
+    pub fn push_audio(&self, samples: &[i16]) {
+        let _ = self.audio_tx.try_send(samples.to_vec());
+    }
+
+    /// Get the next trigger event (non-blocking)
+    ///
+    /// # Returns
+    /// `Some(TriggerEvent)` if an event is available, `None` otherwise
+    pub fn try_recv(&self) -> Option<TriggerEvent> {
+        self.event_rx.lock().ok()?.try_recv().ok()
+    }
+
+    /// Get the next trigger event (blocking)
+    ///
+    /// # Returns
+    /// The next `TriggerEvent`
+    pub fn recv(&self) -> Result<TriggerEvent> {
+        self.event_rx.lock()
+            .map_err(|e| Error::Inference(format!("Failed to lock receiver: {}", e)))?
+            .recv()
+            .map_err(|e| Error::Inference(format!("Failed to receive trigger event: {}", e)))
+    }
+}
+
+impl Trigger for WakeWordTrigger {
+    fn is_active(&self) -> bool {
+        self.is_active.load(Ordering::SeqCst)
+    }
+
+    fn wait_for_activation(&self) -> Result<()> {
+        loop {
+            match self.recv()? {
+                TriggerEvent::Activated => {
+                    self.is_active.store(true, Ordering::SeqCst);
+                    return Ok(());
+                }
+                TriggerEvent::Deactivated => {
+                    self.is_active.store(false, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+
+    fn wait_for_deactivation(&self) -> Result<()> {
+        loop {
+            match self.recv()? {
+                TriggerEvent::Activated => {
+                    self.is_active.store(true, Ordering::SeqCst);
+                }
+                TriggerEvent::Deactivated => {
+                    self.is_active.store(false, Ordering::SeqCst);
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Lowercase alphanumeric words of `text`
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .map(|w| w.replace('\'', "").to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Whether `text` contains the phrase. Higher sensitivity accepts one-letter misspellings of
+/// longer words, the phrase run together ("heymemo"), and some of its words missing.
+fn matches_phrase(text: &str, phrase: &[String], sensitivity: f32) -> bool {
+    let heard = words(text);
+    if sensitivity > 0.0 && heard.concat().contains(&phrase.concat()) {
+        return true;
+    }
+    let required = ((1.0 - sensitivity / 2.0) * phrase.len() as f32).ceil() as usize;
+    let close = |expected: &str, actual: &str| {
+        expected == actual || (sensitivity >= 0.5 && expected.len() >= 4 && edit_distance(expected, actual) <= 1)
+    };
+    (0..heard.len()).any(|start| {
+        let matched = phrase
+            .iter()
+            .zip(&heard[start..])
+            .filter(|(expected, actual)| close(expected, actual))
+            .count();
+        matched >= required.max(1)
+    })
+}
+
+/// Levenshtein distance between two words
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let next = (row[j + 1] + 1).min(row[j] + 1).min(diagonal + usize::from(ca != cb));
+            diagonal = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edit_distance, matches_phrase, words};
+
+    #[test]
+    fn splits_text_into_lowercase_words() {
+        assert_eq!(words("Hey, Memo! What's up?"), ["hey", "memo", "whats", "up"]);
+        assert!(words(" ... ").is_empty());
+    }
+
+    #[test]
+    fn exact_match_at_zero_sensitivity() {
+        let phrase = words("hey memo");
+        assert!(matches_phrase("Okay. Hey memo, take a note.", &phrase, 0.0));
+        assert!(!matches_phrase("Hey, demo.", &phrase, 0.0));
+        assert!(!matches_phrase("Heymemo", &phrase, 0.0));
+        assert!(!matches_phrase("memo", &phrase, 0.0));
+    }
+
+    #[test]
+    fn higher_sensitivity_accepts_near_misses() {
+        let phrase = words("hey memo");
+        assert!(matches_phrase("Heymemo", &phrase, 0.2));
+        assert!(matches_phrase("Hey, memmo.", &phrase, 0.5));
+        assert!(!matches_phrase("Hey, memmo.", &phrase, 0.4));
+        assert!(matches_phrase("Hey...", &phrase, 1.0));
+    }
+
+    #[test]
+    fn counts_single_character_edits() {
+        assert_eq!(edit_distance("memo", "memo"), 0);
+        assert_eq!(edit_distance("memo", "demo"), 1);
+        assert_eq!(edit_distance("memo", "memmo"), 1);
+        assert_eq!(edit_distance("memo", "mem"), 1);
+        assert_eq!(edit_distance("memo", ""), 4);
+    }
+}
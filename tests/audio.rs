@@ -0,0 +1,200 @@
+use memo_stt::audio::{decode_pcm, decode_wav, downmix, find_silence_boundaries, is_silent, resample_i16, to_whisper_input, to_whisper_input_f32, Endianness, PcmFormat, SampleFormat};
+
+#[test]
+fn whisper_input_at_16k_is_normalized_without_resampling() {
+    let mut out = Vec::new();
+    to_whisper_input(&[0, 16384, -16384, i16::MIN], 16000, &mut out);
+    assert_eq!(out, vec![0.0, 0.5, -0.5, -1.0]);
+}
+
+#[test]
+fn whisper_input_is_resampled_to_16k() {
+    let mut out = Vec::new();
+    for rate in [8000u32, 22050, 44100, 48000] {
+        to_whisper_input(&vec![1000i16; rate as usize * 2], rate, &mut out);
+        assert!((31_999..=32_000).contains(&out.len()), "{} Hz -> {} samples", rate, out.len());
+        // A constant signal stays constant through linear interpolation
+        assert!(out.iter().all(|&s| (s - 1000.0 / 32768.0).abs() < 1e-6));
+    }
+}
+
+#[test]
+fn whisper_input_interpolates_between_samples() {
+    let mut out = Vec::new();
+    // 8 kHz -> 16 kHz doubles the sample count, inserting midpoints
+    to_whisper_input(&[0, 16384, 0, -16384], 8000, &mut out);
+    assert_eq!(out.len(), 8);
+    assert_eq!(&out[..4], &[0.0, 0.25, 0.5, 0.25]);
+}
+
+#[test]
+fn whisper_input_reuses_buffer() {
+    let mut out = vec![9.0; 100];
+    to_whisper_input(&[], 16000, &mut out);
+    assert!(out.is_empty());
+    to_whisper_input(&[0; 10], 16000, &mut out);
+    assert_eq!(out.len(), 10);
+}
+
+#[test]
+fn f32_whisper_input_matches_i16_path() {
+    let pcm: Vec<i16> = (0..4800).map(|i| ((i % 200) as i16 - 100) * 100).collect();
+    let float: Vec<f32> = pcm.iter().map(|&s| s as f32 / 32768.0).collect();
+    for rate in [16000u32, 48000] {
+        let (mut from_i16, mut from_f32) = (Vec::new(), Vec::new());
+        to_whisper_input(&pcm, rate, &mut from_i16);
+        to_whisper_input_f32(&float, rate, &mut from_f32);
+        assert_eq!(from_i16.len(), from_f32.len());
+        assert!(from_i16.iter().zip(&from_f32).all(|(a, b)| (a - b).abs() < 1e-6));
+    }
+}
+
+#[test]
+fn resample_i16_changes_length_by_rate_ratio() {
+    assert_eq!(resample_i16(&[7; 48000], 48000, 16000), vec![7; 16000]);
+    assert_eq!(resample_i16(&[7; 8000], 8000, 16000).len(), 16000);
+    assert_eq!(resample_i16(&[7; 44100], 44100, 16000).len(), 16000);
+}
+
+#[test]
+fn resample_i16_interpolates_and_rounds() {
+    // 8 kHz -> 16 kHz inserts midpoints, rounded to the nearest sample
+    assert_eq!(resample_i16(&[0, 3, i16::MAX, i16::MIN], 8000, 16000), vec![0, 2, 3, 16385, 32767, -1, -32768, -32768]);
+}
+
+#[test]
+fn resample_i16_equal_rates_and_empty_input_pass_through() {
+    assert_eq!(resample_i16(&[1, -2, 3], 16000, 16000), vec![1, -2, 3]);
+    assert!(resample_i16(&[], 48000, 16000).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "non-zero")]
+fn resample_i16_rejects_zero_rate() {
+    resample_i16(&[0; 10], 0, 16000);
+}
+
+#[test]
+fn decode_pcm_big_endian_stereo_is_downmixed() {
+    let format = PcmFormat { sample_format: SampleFormat::I16, channels: 2, endianness: Endianness::Big };
+    let bytes = [0x10, 0x00, 0x30, 0x00, 0xFF, 0xFF, 0x01, 0x00];
+    assert_eq!(decode_pcm(&bytes, format).unwrap(), vec![0x2000, 127]);
+}
+
+#[test]
+fn decode_pcm_i32_and_f32_scale_to_i16() {
+    let i32_format = PcmFormat { sample_format: SampleFormat::I32, ..PcmFormat::default() };
+    let bytes: Vec<u8> = [i32::MAX, i32::MIN].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(&bytes, i32_format).unwrap(), vec![i16::MAX, i16::MIN]);
+
+    let f32_format = PcmFormat { sample_format: SampleFormat::F32, ..PcmFormat::default() };
+    let bytes: Vec<u8> = [1.0f32, -0.5, 4.0].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_pcm(&bytes, f32_format).unwrap(), vec![32767, -16383, 32767]);
+}
+
+#[test]
+fn decode_pcm_rejects_partial_frames_and_zero_channels() {
+    let stereo = PcmFormat { channels: 2, ..PcmFormat::default() };
+    assert!(matches!(decode_pcm(&[0; 6], stereo), Err(memo_stt::Error::Audio(_))));
+
+    let none = PcmFormat { channels: 0, ..PcmFormat::default() };
+    assert!(matches!(decode_pcm(&[0; 4], none), Err(memo_stt::Error::Config(_))));
+}
+
+#[test]
+fn downmix_averages_or_applies_channel_weights() {
+    let stereo = [1000, -400, 2000, 600, i16::MAX, i16::MAX];
+    assert_eq!(downmix(&stereo, 2, None).unwrap(), vec![300, 1300, i16::MAX]);
+    assert_eq!(downmix(&stereo, 2, Some(&[1.0, 0.0])).unwrap(), vec![1000, 2000, i16::MAX]);
+    assert_eq!(downmix(&stereo, 2, Some(&[0.7, 0.3])).unwrap(), vec![580, 1580, i16::MAX]);
+    // Gain above unity clamps rather than wrapping
+    assert_eq!(downmix(&stereo, 2, Some(&[1.0, 1.0])).unwrap()[2], i16::MAX);
+    assert_eq!(downmix(&[5, 6, 7], 1, None).unwrap(), vec![5, 6, 7]);
+}
+
+#[test]
+fn downmix_rejects_bad_layouts() {
+    assert!(matches!(downmix(&[1, 2], 0, None), Err(memo_stt::Error::Config(_))));
+    assert!(matches!(downmix(&[1, 2], 2, Some(&[1.0])), Err(memo_stt::Error::Config(_))));
+    assert!(matches!(downmix(&[1, 2], 2, Some(&[1.0, f32::NAN])), Err(memo_stt::Error::Config(_))));
+    assert!(matches!(downmix(&[1, 2, 3], 2, None), Err(memo_stt::Error::Audio(_))));
+}
+
+/// A canonical WAV file: `fmt ` chunk with the given tag and layout, then `data`
+fn wav(tag: u16, channels: u16, sample_rate: u32, bits: u16, data: &[u8]) -> Vec<u8> {
+    let mut out = b"RIFF\0\0\0\0WAVE".to_vec();
+    out.extend_from_slice(b"fmt \x10\0\0\0");
+    out.extend_from_slice(&tag.to_le_bytes());
+    out.extend_from_slice(&channels.to_le_bytes());
+    out.extend_from_slice(&sample_rate.to_le_bytes());
+    out.extend_from_slice(&(sample_rate * (channels * bits / 8) as u32).to_le_bytes());
+    out.extend_from_slice(&(channels * bits / 8).to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    // An odd-sized chunk the parser has to skip, with its padding byte
+    out.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out.extend_from_slice(data);
+    out
+}
+
+#[test]
+fn decode_wav_reads_integer_and_float_pcm() {
+    let stereo: Vec<u8> = [1000i16, 3000, -200, -400].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_wav(&wav(1, 2, 44100, 16, &stereo)).unwrap(), (vec![2000, -300], 44100));
+
+    let float: Vec<u8> = [0.5f32, -1.0].iter().flat_map(|s| s.to_le_bytes()).collect();
+    assert_eq!(decode_wav(&wav(3, 1, 16000, 32, &float)).unwrap(), (vec![16383, -32767], 16000));
+
+    // Header never finalized: the data chunk claims more than the file holds
+    let mut truncated = wav(1, 1, 16000, 16, &[1, 0, 2, 0, 3]);
+    let len_at = truncated.len() - 9;
+    truncated[len_at..len_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(decode_wav(&truncated).unwrap(), (vec![1, 2], 16000));
+}
+
+#[test]
+fn decode_wav_rejects_unsupported_files() {
+    assert!(matches!(decode_wav(b"not a wav file"), Err(memo_stt::Error::Audio(_))));
+    assert!(matches!(decode_wav(&wav(1, 1, 16000, 8, &[0, 1])), Err(memo_stt::Error::Audio(_))));
+    assert!(matches!(decode_wav(&wav(1, 0, 16000, 16, &[])), Err(memo_stt::Error::Audio(_))));
+    assert!(matches!(decode_wav(&wav(1, 1, 16000, 16, &[])[..44]), Err(memo_stt::Error::Audio(_))));
+}
+
+/// 440 Hz sine at 16 kHz
+fn tone(len: usize) -> Vec<i16> {
+    (0..len).map(|i| ((i as f32 * 440.0 * std::f32::consts::TAU / 16000.0).sin() * 8000.0) as i16).collect()
+}
+
+#[test]
+fn silence_boundaries_fall_in_the_middle_of_gaps() {
+    // 1s tone, 500ms gap, 1s tone, 400ms gap (quiet noise), 1s tone
+    let noise: Vec<i16> = (0..6400).map(|i| if i % 3 == 0 { 40 } else { -40 }).collect();
+    let samples = [tone(16000), vec![0; 8000], tone(16000), noise, tone(16000)].concat();
+    let gaps = find_silence_boundaries(&samples, 16000, 300);
+    assert_eq!(gaps.len(), 2, "{:?}", gaps);
+    // One 10ms analysis window of tolerance
+    assert!(gaps[0].abs_diff(16000 + 4000) <= 160, "{:?}", gaps);
+    assert!(gaps[1].abs_diff(40000 + 3200) <= 160, "{:?}", gaps);
+}
+
+#[test]
+fn silence_boundaries_ignore_short_gaps_and_edges() {
+    let samples = [vec![0; 16000], tone(16000), vec![0; 3200], tone(16000), vec![0; 16000]].concat();
+    // The 200ms gap is too short; leading and trailing silence aren't between speech
+    assert!(find_silence_boundaries(&samples, 16000, 300).is_empty());
+    assert_eq!(find_silence_boundaries(&samples, 16000, 150).len(), 1);
+    assert!(find_silence_boundaries(&[0; 32000], 16000, 300).is_empty());
+    assert!(find_silence_boundaries(&[], 16000, 300).is_empty());
+}
+
+#[test]
+fn is_silent_compares_whole_clip_rms() {
+    let noise: Vec<i16> = (0..16000).map(|i| if i % 3 == 0 { 40 } else { -40 }).collect();
+    assert!(is_silent(&noise, 50.0));
+    assert!(!is_silent(&noise, 40.0));
+    assert!(!is_silent(&tone(16000), 100.0));
+    // A short word in a long clip still counts as sound
+    assert!(!is_silent(&[vec![0; 32000], tone(1600)].concat(), 100.0));
+    assert!(is_silent(&[], 1.0));
+}
@@ -0,0 +1,10 @@
+use memo_stt::{Check, CheckStatus};
+
+#[test]
+fn checks_display_as_report_lines() {
+    let pass = Check::new("gpu", CheckStatus::Pass, "Metal (device 0)");
+    assert_eq!(pass.to_string(), "✅ gpu: Metal (device 0)");
+    let fail = Check::new("microphone", CheckStatus::Fail, String::from("No input device found"));
+    assert_eq!(fail.to_string(), "❌ microphone: No input device found");
+    assert!(Check::new("model", CheckStatus::Warn, "not downloaded yet").to_string().contains("model: not downloaded yet"));
+}
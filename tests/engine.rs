@@ -0,0 +1,590 @@
+//! End-to-end tests against a real model.
+//!
+//! Tests that need a whisper model are `#[ignore]`d; run them with
+//! `MEMO_TEST_MODEL=models/ggml-tiny.en.bin cargo test --test engine -- --ignored`. Without
+//! `MEMO_TEST_MODEL` the default model is used if it is already cached; tests never download.
+//! The speech fixture is committed (see `tests/fixtures/README.md`).
+
+use memo_stt::pool::SttEnginePool;
+use memo_stt::streaming::StreamingSession;
+use memo_stt::{Backend, CancelToken, Error, GpuOptions, Preset, Segment, SttEngine, TranscribeOptions, TranscribeOutcome, TranscriptUpdate, Word};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/jfk.wav");
+
+/// Path of the test model
+fn model_path() -> PathBuf {
+    let model = std::env::var("MEMO_TEST_MODEL")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| memo_stt::default_model_path());
+    assert!(model.exists(), "no model at {} (set MEMO_TEST_MODEL)", model.display());
+    model
+}
+
+/// CPU engine (deterministic timings and output) at `sample_rate`
+fn engine(sample_rate: u32) -> SttEngine {
+    SttEngine::new_cpu(model_path(), sample_rate).expect("failed to load model")
+}
+
+/// 16-bit mono fixture samples and their sample rate
+fn fixture() -> (Vec<i16>, u32) {
+    let mut reader = hound::WavReader::open(FIXTURE).expect("failed to open the speech fixture");
+    let spec = reader.spec();
+    assert_eq!((spec.channels, spec.bits_per_sample), (1, 16), "fixture must be 16-bit mono");
+    let samples = reader.samples::<i16>().map(|s| s.unwrap()).collect();
+    (samples, spec.sample_rate)
+}
+
+#[test]
+fn fixture_is_eleven_seconds_of_16k_speech() {
+    let (samples, rate) = fixture();
+    assert_eq!(rate, 16000);
+    assert_eq!(samples.len(), 11 * 16000);
+    assert!(!memo_stt::audio::is_silent(&samples, 500.0));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn transcript_contains_expected_words() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let transcript = engine.transcribe_detailed(&samples).unwrap();
+    let text = transcript.raw.to_lowercase();
+    for word in ["ask not", "your country", "can do for you"] {
+        assert!(text.contains(word), "{:?} missing from {:?}", word, transcript.raw);
+    }
+    assert!(!transcript.is_silent());
+    assert!(transcript.stats.inference_secs > 0.0);
+    let first = transcript.stats.time_to_first_segment_secs.expect("no first-segment time");
+    assert!(first > 0.0 && first <= transcript.stats.inference_secs, "{:?}", transcript.stats);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn segments_are_ordered_and_within_clip() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+    engine.set_max_segment_len(20);
+
+    let duration = samples.len() as f32 / rate as f32;
+    let segments = engine.transcribe_segments(&samples).unwrap();
+    assert!(segments.len() > 1, "max segment length should split the clip: {:?}", segments);
+
+    let mut prev_end = 0.0;
+    for seg in &segments {
+        assert!(seg.start_secs >= prev_end - 0.01, "segments overlap: {:?}", segments);
+        assert!(seg.end_secs >= seg.start_secs);
+        assert!(!seg.text.is_empty(), "empty segment in {:?}", segments);
+        // Whisper timestamps have 10ms resolution
+        assert!(seg.end_secs <= duration + 0.01, "{:?} ends after the {}s clip", seg, duration);
+        prev_end = seg.end_secs;
+    }
+}
+
+#[test]
+fn segment_times_convert_to_milliseconds() {
+    let seg = Segment { start_secs: 1.23, end_secs: 4.5, text: "ask not".into() };
+    assert_eq!((seg.start_ms(), seg.end_ms()), (1230, 4500));
+}
+
+#[test]
+fn word_times_convert_to_milliseconds() {
+    let word = Word { start_secs: 0.07, end_secs: 0.3, text: "Ask".into(), probability: 0.9 };
+    assert_eq!((word.start_ms(), word.end_ms()), (70, 300));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn short_clip_reports_sample_count() {
+    let mut engine = engine(16000);
+
+    match engine.transcribe(&[0; 8000]) {
+        Err(Error::AudioTooShort { samples, min_samples }) => {
+            assert_eq!((samples, min_samples), (8000, 16000));
+        }
+        other => panic!("expected AudioTooShort, got {:?}", other),
+    }
+    assert!(matches!(engine.transcribe(&[]), Err(Error::AudioTooShort { samples: 0, .. })));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn preprocess_matches_engine_rate() {
+    let engine = engine(48000);
+    assert_eq!(engine.preprocess(&vec![0; 48000]).len(), 16000);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn words_carry_timings_and_probabilities() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let words = engine.transcribe_words(&samples).unwrap();
+    assert!(words.iter().any(|w| w.text.to_lowercase().starts_with("country")), "{:?}", words);
+    for word in &words {
+        assert!(!word.text.is_empty() && !word.text.starts_with(' '));
+        assert!((0.0..=1.0).contains(&word.probability), "{:?}", word);
+        assert!(word.end_secs >= word.start_secs);
+    }
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn robust_rejects_bad_overlap_and_matches_short_clips() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    assert!(matches!(engine.transcribe_robust(&samples, 0.95), Err(Error::Config(_))));
+    // The fixture fits in one window, so robust mode is a single plain pass
+    let plain = engine.transcribe(&samples).unwrap();
+    assert_eq!(engine.transcribe_robust(&samples, 0.5).unwrap(), plain);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn callback_reports_partials_before_each_segment() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let mut updates = Vec::new();
+    let text = engine.transcribe_with_callback(&samples, |u| updates.push(u)).unwrap();
+    let segments: Vec<_> = updates
+        .iter()
+        .filter_map(|u| match u {
+            TranscriptUpdate::Segment(seg) => Some(seg.text.clone()),
+            TranscriptUpdate::Partial(_) => None,
+        })
+        .collect();
+    assert_eq!(segments.join(" "), text);
+    assert!(matches!(updates.first(), Some(TranscriptUpdate::Partial(_))), "{:?}", updates);
+    assert!(matches!(updates.last(), Some(TranscriptUpdate::Segment(_))), "{:?}", updates);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn every_preset_transcribes_the_fixture() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    for preset in [Preset::Fast, Preset::Balanced, Preset::Accurate] {
+        engine.set_preset(preset);
+        let text = engine.transcribe(&samples).unwrap().to_lowercase();
+        assert!(text.contains("your country"), "{:?}: {:?}", preset, text);
+    }
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn logits_filter_sees_every_step_and_can_be_removed() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+    let plain = engine.transcribe(&samples).unwrap();
+
+    let calls = Arc::new(AtomicUsize::new(0));
+    let seen = Arc::clone(&calls);
+    engine.set_logits_filter(Some(Box::new(move |_tokens, logits| {
+        assert!(!logits.is_empty());
+        seen.fetch_add(1, Ordering::Relaxed);
+    })));
+    assert_eq!(engine.transcribe(&samples).unwrap(), plain);
+    assert!(calls.load(Ordering::Relaxed) > 0);
+
+    engine.set_logits_filter(None);
+    let before = calls.load(Ordering::Relaxed);
+    engine.transcribe(&samples).unwrap();
+    assert_eq!(calls.load(Ordering::Relaxed), before);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn cpu_engine_reports_cpu_backend() {
+    let engine = engine(16000);
+    assert_eq!(engine.backend(), Backend::Cpu);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn unloaded_engine_reloads_on_next_transcription() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+    let before = engine.transcribe(&samples).unwrap();
+
+    engine.unload();
+    assert!(!engine.is_loaded());
+    assert_eq!(engine.transcribe(&samples).unwrap(), before);
+    assert!(engine.is_loaded());
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn idle_engine_unloads_itself() {
+    let mut engine = engine(16000);
+    engine.set_idle_unload(Some(Duration::from_millis(50)));
+    std::thread::sleep(Duration::from_millis(1500));
+    assert!(!engine.is_loaded());
+
+    engine.set_idle_unload(None);
+    engine.reload().unwrap();
+    std::thread::sleep(Duration::from_millis(1500));
+    assert!(engine.is_loaded());
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn nbest_returns_distinct_sorted_hypotheses() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    assert!(matches!(engine.transcribe_nbest(&samples, 0), Err(Error::Config(_))));
+    let hypotheses = engine.transcribe_nbest(&samples, 3).unwrap();
+    assert!((1..=3).contains(&hypotheses.len()), "{:?}", hypotheses);
+    assert!(hypotheses.windows(2).all(|w| w[0].1 >= w[1].1), "{:?}", hypotheses);
+    assert!(hypotheses.iter().all(|(_, score)| *score <= 0.0));
+    for (i, (text, _)) in hypotheses.iter().enumerate() {
+        assert!(hypotheses[i + 1..].iter().all(|(other, _)| other != text));
+    }
+    assert!(hypotheses.iter().any(|(text, _)| text.to_lowercase().contains("your country")));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn transcribe_with_overrides_only_that_call() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let bad = TranscribeOptions { language: Some("xx".to_string()), ..TranscribeOptions::default() };
+    assert!(matches!(engine.transcribe_with(&samples, &bad), Err(Error::Config(_))));
+
+    let baseline = engine.transcribe(&samples).unwrap();
+    let options = TranscribeOptions {
+        prompt: Some("Ask not. Fellow Americans.".to_string()),
+        preset: Some(Preset::Accurate),
+        ..TranscribeOptions::default()
+    };
+    let text = engine.transcribe_with(&samples, &options).unwrap();
+    assert!(text.to_lowercase().contains("your country"), "{}", text);
+    assert_eq!(engine.transcribe(&samples).unwrap(), baseline);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn shrink_buffers_releases_long_clip_memory() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let long = samples.repeat(6);
+    engine.transcribe(&long).unwrap();
+    let grown = engine.buffer_bytes();
+    assert!(grown >= long.len() * 4 * 16000 / rate as usize, "{}", grown);
+
+    engine.shrink_buffers();
+    assert!(engine.buffer_bytes() < grown);
+    assert!(engine.buffer_bytes() <= 48000 * 4);
+    assert!(engine.transcribe(&samples).unwrap().to_lowercase().contains("your country"));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn streaming_session_transcribes_full_windows() {
+    let (samples, rate) = fixture();
+    let engine = engine(rate);
+
+    let mut session = StreamingSession::new(engine, Duration::from_secs(4)).unwrap();
+    let mut text = String::new();
+    for chunk in samples.chunks(rate as usize / 50) {
+        session.push(chunk);
+        while let Some(window) = session.poll().unwrap() {
+            text.push_str(&window);
+            text.push(' ');
+        }
+    }
+    assert!(session.buffered_samples() < 4 * rate as usize);
+    assert!(text.to_lowercase().contains("fellow americans"), "{}", text);
+
+    let engine = session.into_engine();
+    assert!(matches!(StreamingSession::new(engine, Duration::from_millis(500)), Err(Error::Config(_))));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn spawned_streaming_session_is_fed_through_a_channel() {
+    let (samples, rate) = fixture();
+    let engine = engine(rate);
+
+    let (audio, results) = StreamingSession::new(engine, Duration::from_secs(4)).unwrap().spawn();
+    let feeder = audio.clone();
+    std::thread::spawn(move || {
+        for chunk in samples.chunks(rate as usize / 50) {
+            feeder.send(chunk.to_vec()).unwrap();
+        }
+    })
+    .join()
+    .unwrap();
+    drop(audio);
+
+    let windows: Vec<String> = results.into_iter().map(|r| r.unwrap()).collect();
+    assert!(windows.len() >= 2, "{:?}", windows);
+    assert!(windows.concat().to_lowercase().contains("fellow americans"), "{:?}", windows);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn finalize_transcribes_a_partial_window() {
+    let (samples, rate) = fixture();
+    let engine = engine(rate);
+
+    let mut session = StreamingSession::new(engine, Duration::from_secs(5)).unwrap();
+    session.push(&samples[..rate as usize * 5 / 2]);
+    assert_eq!(session.poll().unwrap(), None);
+    let text = session.finalize().unwrap();
+    assert!(text.to_lowercase().contains("and so"), "{}", text);
+    assert_eq!(session.buffered_samples(), 0);
+
+    assert!(session.is_finished());
+    session.push(&samples);
+    assert_eq!(session.poll().unwrap(), None);
+    assert_eq!(session.finalize().unwrap(), "");
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn thread_count_overrides_the_preset_until_reset() {
+    let mut engine = engine(16000);
+
+    let preset_threads = engine.n_threads();
+    assert!(preset_threads >= 1);
+    engine.set_n_threads(3);
+    assert_eq!(engine.n_threads(), 3);
+    engine.set_preset(Preset::Fast);
+    assert_eq!(engine.n_threads(), 3);
+    engine.warmup().unwrap();
+
+    engine.set_n_threads(0);
+    engine.set_preset(Preset::Balanced);
+    assert_eq!(engine.n_threads(), preset_threads);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn language_is_validated_against_the_model() {
+    let mut engine = engine(16000);
+
+    assert!(matches!(engine.set_language(Some("klingon")), Err(Error::Config(_))));
+    if engine.model_info().is_multilingual {
+        engine.set_language(Some("Spanish")).unwrap();
+        assert_eq!(engine.language(), Some("es"));
+        engine.set_language(None).unwrap();
+        assert_eq!(engine.language(), None);
+    } else {
+        assert!(matches!(engine.set_language(Some("es")), Err(Error::Config(_))));
+        assert!(matches!(engine.set_language(None), Err(Error::Config(_))));
+        engine.set_language(Some("en")).unwrap();
+        assert_eq!(engine.language(), Some("en"));
+    }
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn detect_language_reports_english_for_the_fixture() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    if !engine.model_info().is_multilingual {
+        assert!(matches!(engine.detect_language(&samples), Err(Error::Config(_))));
+        return;
+    }
+    assert!(matches!(engine.detect_language(&samples[..rate as usize / 2]), Err(Error::AudioTooShort { .. })));
+    let (language, probability) = engine.detect_language(&samples).unwrap();
+    assert_eq!(language, "en");
+    assert!(probability > 0.5 && probability <= 1.0, "{}", probability);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn f32_input_transcribes_like_i16() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let float: Vec<f32> = samples.iter().map(|&s| s as f32 / 32768.0).collect();
+    assert_eq!(engine.transcribe_f32(&float).unwrap(), engine.transcribe(&samples).unwrap());
+    assert!(matches!(engine.transcribe_f32(&float[..100]), Err(Error::AudioTooShort { samples: 100, .. })));
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn stereo_input_is_downmixed_by_frame() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+    let mono = engine.transcribe(&samples).unwrap();
+
+    let stereo: Vec<i16> = samples.iter().flat_map(|&s| [s, s]).collect();
+    engine.set_channels(2).unwrap();
+    assert_eq!(engine.transcribe(&stereo).unwrap(), mono);
+    let stats = engine.last_transcription_stats().unwrap();
+    assert!((stats.audio_duration_secs - samples.len() as f32 / rate as f32).abs() < 1e-3);
+
+    // The minimum length counts frames: one second of stereo is 2 * rate samples
+    let short = &stereo[..rate as usize];
+    assert!(matches!(engine.transcribe(short), Err(Error::AudioTooShort { samples, .. }) if samples == rate as usize / 2));
+    assert!(matches!(engine.transcribe(&stereo[..stereo.len() - 1]), Err(Error::Audio(_))));
+    assert!(engine.set_channels(0).is_err());
+    assert_eq!(engine.channels(), 2);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn builder_applies_and_validates_decoding_settings() {
+    let model = model_path();
+    let builder = || SttEngine::builder().model_path(&model).allow_download(false).gpu(GpuOptions::cpu());
+
+    let engine = builder().language("en").n_threads(2).initial_prompt("Kubernetes").build().unwrap();
+    assert_eq!(engine.language(), Some("en"));
+    assert_eq!(engine.n_threads(), 2);
+
+    assert!(matches!(builder().language("klingon").build(), Err(Error::Config(_))));
+    if !engine.model_info().is_multilingual {
+        assert!(matches!(builder().translate(true).build(), Err(Error::Config(_))));
+    }
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn trim_silence_drops_dead_air_around_speech() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let pad = vec![0i16; 2 * rate as usize];
+    let padded = [pad.clone(), samples.clone(), pad].concat();
+    engine.set_trim_silence(true);
+    let text = engine.transcribe(&padded).unwrap().to_lowercase();
+    assert!(text.contains("country"), "{}", text);
+    let trimmed_secs = engine.last_transcription_stats().unwrap().audio_duration_secs;
+    assert!(trimmed_secs < padded.len() as f32 / rate as f32 - 3.0, "{}s", trimmed_secs);
+
+    // All silence is transcribed whole rather than trimmed to nothing
+    engine.transcribe(&vec![0i16; 2 * rate as usize]).unwrap();
+    assert_eq!(engine.last_transcription_stats().unwrap().audio_duration_secs, 2.0);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn stream_reports_each_chunk_once() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    assert!(matches!(engine.transcribe_stream(&samples, 0.5, |_| {}), Err(Error::Config(_))));
+
+    let mut segments: Vec<Segment> = Vec::new();
+    let text = engine.transcribe_stream(&samples, 4.0, |seg| segments.push(seg)).unwrap();
+    assert!(segments.len() >= 2, "{:?}", segments);
+    assert!(segments.windows(2).all(|w| w[0].end_secs <= w[1].start_secs + 0.5), "{:?}", segments);
+    let joined = segments.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join(" ").to_lowercase();
+    assert!(joined.contains("country"), "{}", joined);
+    let words: Vec<&str> = joined.split_whitespace().collect();
+    assert!(words.windows(2).all(|w| w[0] != w[1]), "repeated word at a chunk boundary: {}", joined);
+    assert!(!text.is_empty());
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn cancelled_token_stops_transcription() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let cancel = CancelToken::new();
+    let outcome = engine.transcribe_cancellable(&samples, &cancel).unwrap();
+    assert_eq!(outcome, TranscribeOutcome::Completed(engine.transcribe(&samples).unwrap()));
+
+    cancel.cancel();
+    let outcome = engine.transcribe_cancellable(&samples, &cancel).unwrap();
+    assert!(outcome.is_cancelled(), "{:?}", outcome);
+    // The engine is still usable afterwards
+    assert!(!engine.transcribe(&samples).unwrap().is_empty());
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn pool_transcribes_concurrently() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SttEnginePool>();
+
+    let (samples, rate) = fixture();
+    let unpooled = engine(rate);
+    assert!(matches!(SttEnginePool::new(unpooled, 0), Err(Error::Config(_))));
+
+    let pool = SttEnginePool::new(engine(rate), 2).unwrap();
+    assert_eq!((pool.size(), pool.available()), (2, 2));
+    let expected = pool.transcribe(&samples).unwrap();
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4).map(|_| scope.spawn(|| pool.transcribe(&samples).unwrap())).collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), expected);
+        }
+    });
+    assert_eq!(pool.available(), 2);
+
+    pool.for_each(|engine| engine.set_n_threads(1));
+    assert_eq!(pool.with(|engine| engine.n_threads()), 1);
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn progress_callback_reports_long_clips() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = Arc::clone(&seen);
+    engine.set_progress_callback(Some(Box::new(move |percent| sink.lock().unwrap().push(percent))));
+    let long = samples.repeat(6); // Several 30 second windows
+    engine.transcribe(&long).unwrap();
+
+    let seen = std::mem::take(&mut *seen.lock().unwrap());
+    assert!(!seen.is_empty());
+    assert!(seen.windows(2).all(|w| w[0] <= w[1]), "{:?}", seen);
+    assert!(seen.iter().all(|p| (0..=100).contains(p)), "{:?}", seen);
+
+    engine.set_progress_callback(None);
+    engine.transcribe(&long).unwrap();
+}
+
+#[test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+fn reduced_audio_ctx_still_transcribes() {
+    let (samples, rate) = fixture();
+    let mut engine = engine(rate);
+
+    let full = engine.model_info().n_audio_ctx;
+    assert!(matches!(engine.set_audio_ctx(full + 1), Err(Error::Config(_))));
+
+    engine.set_auto_audio_ctx(true);
+    let text = engine.transcribe(&samples).unwrap().to_lowercase();
+    assert!(text.contains("country"), "{}", text);
+
+    engine.set_auto_audio_ctx(false);
+    engine.set_audio_ctx(full).unwrap();
+    engine.set_audio_ctx(0).unwrap();
+    assert!(engine.transcribe(&samples).unwrap().to_lowercase().contains("country"));
+}
+
+#[cfg(feature = "async")]
+#[tokio::test]
+#[ignore = "needs a whisper model (MEMO_TEST_MODEL)"]
+async fn async_transcription_applies_silence_gate_and_trimming() {
+    let (speech, rate) = fixture();
+    let mut engine = engine(rate);
+    engine.set_silence_threshold(Some(200.0));
+    engine.set_trim_silence(true);
+
+    let silence = vec![0i16; 3 * rate as usize];
+    assert_eq!(engine.transcribe_async(&silence).await.unwrap(), "");
+
+    let padded = [silence.clone(), speech, silence].concat();
+    let expected = engine.transcribe(&padded).unwrap();
+    assert_eq!(engine.transcribe_async(&padded).await.unwrap(), expected);
+}
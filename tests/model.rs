@@ -0,0 +1,251 @@
+use memo_stt::model::{default_model_path, download_model, download_model_with, ensure_model, ensure_model_in, ensure_model_with_progress, known_model, locate_model, prefetch_model, CancelToken, DownloadOptions, ModelKind, KNOWN_MODELS};
+use memo_stt::{Error, DEFAULT_MODEL};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn recommended_models_are_registered() {
+    for name in ["ggml-small.en-q5_1.bin", "ggml-distil-large-v3-q5_1.bin", "ggml-distil-large-v3-q8_0.bin", "ggml-large-v3-turbo.bin"] {
+        let model = known_model(name).unwrap_or_else(|| panic!("{} is not registered", name));
+        assert!(model.url().starts_with("https://huggingface.co/"));
+        assert!(model.url().ends_with(name));
+    }
+    assert!(known_model("ggml-nonexistent.bin").is_none());
+}
+
+#[test]
+fn model_kinds_cover_the_registry() {
+    assert_eq!(ModelKind::ALL.len(), KNOWN_MODELS.len());
+    for (kind, model) in ModelKind::ALL.into_iter().zip(KNOWN_MODELS) {
+        assert_eq!(kind.info(), model);
+        assert_eq!(kind.download_url(), model.url());
+        assert_eq!(kind.approx_size_bytes(), u64::from(model.size_mb) * 1024 * 1024);
+    }
+    assert_eq!(ModelKind::default().file_name(), DEFAULT_MODEL);
+    assert_eq!(ModelKind::DistilLargeV3Q8.to_string(), "ggml-distil-large-v3-q8_0.bin");
+}
+
+#[test]
+fn model_kinds_find_their_own_registry_entry() {
+    for kind in ModelKind::ALL {
+        assert_eq!(kind.info().file_name, kind.file_name(), "{:?}", kind);
+    }
+}
+
+#[test]
+fn registry_names_are_unique() {
+    for (i, model) in KNOWN_MODELS.iter().enumerate() {
+        assert!(KNOWN_MODELS[i + 1..].iter().all(|m| m.file_name != model.file_name), "{} listed twice", model.file_name);
+    }
+}
+
+#[test]
+fn memory_estimates_cover_the_weights() {
+    for model in KNOWN_MODELS {
+        let estimate = model.estimated_memory();
+        assert!(estimate.ram_mb > model.size_mb && estimate.vram_mb > model.size_mb, "{} underestimated", model.file_name);
+    }
+    let q5 = known_model("ggml-distil-large-v3-q5_1.bin").unwrap().estimated_memory();
+    let q8 = known_model("ggml-distil-large-v3-q8_0.bin").unwrap().estimated_memory();
+    assert!(q5.ram_mb < q8.ram_mb && q5.vram_mb < q8.vram_mb);
+}
+
+#[test]
+fn registered_checksums_are_sha256_hex() {
+    for model in KNOWN_MODELS {
+        if let Some(sha256) = model.sha256() {
+            assert!(sha256.len() == 64 && sha256.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')), "{} has a malformed checksum", model.file_name);
+        }
+    }
+    assert!(DownloadOptions::default().sha256.is_none());
+}
+
+#[test]
+fn download_rejects_paths_as_names() {
+    for name in ["", "../ggml-tiny.bin", "models/ggml-tiny.bin", ".hidden"] {
+        assert!(matches!(download_model(name), Err(Error::Config(_))), "{:?} accepted", name);
+    }
+}
+
+#[test]
+fn locate_reports_missing_model() {
+    match locate_model("definitely-missing-model.bin") {
+        Err(Error::ModelNotFound { path, .. }) => assert_eq!(path.to_str(), Some("definitely-missing-model.bin")),
+        other => panic!("expected ModelNotFound, got {:?}", other),
+    }
+}
+
+/// Paths `ensure_model` searched before giving up on `model_path`
+fn searched_paths(model_path: &str) -> Vec<PathBuf> {
+    match ensure_model(model_path) {
+        Err(e @ Error::ModelNotFound { .. }) => {
+            let Error::ModelNotFound { searched, .. } = &e else { unreachable!() };
+            for path in searched {
+                assert!(e.to_string().contains(&path.display().to_string()), "{} not in {}", path.display(), e);
+            }
+            searched.clone()
+        }
+        other => panic!("expected ModelNotFound for {}, got {:?}", model_path, other),
+    }
+}
+
+/// Write a stand-in model file (locating doesn't validate)
+fn touch(path: &Path) {
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(path, b"not a model").unwrap();
+}
+
+#[test]
+fn absolute_paths_are_used_as_is() {
+    let path = std::env::temp_dir().join("ggml-memo-test-absolute.bin");
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(searched_paths(path.to_str().unwrap()), std::slice::from_ref(&path));
+
+    touch(&path);
+    assert_eq!(locate_model(&path).unwrap(), path);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn bare_default_name_resolves_to_the_cache() {
+    match locate_model(DEFAULT_MODEL) {
+        Ok(path) => assert!(path == Path::new(DEFAULT_MODEL) || path == Path::new("models").join(DEFAULT_MODEL) || path == default_model_path()),
+        Err(Error::ModelNotFound { searched, .. }) => assert_eq!(
+            searched,
+            [PathBuf::from(DEFAULT_MODEL), Path::new("models").join(DEFAULT_MODEL), default_model_path()]
+        ),
+        Err(e) => panic!("unexpected error {:?}", e),
+    }
+}
+
+#[test]
+fn bare_unknown_name_is_not_downloaded() {
+    let name = "ggml-memo-test-unknown-bare.bin";
+    let cached = default_model_path().with_file_name(name);
+    assert_eq!(searched_paths(name), [PathBuf::from(name), Path::new("models").join(name), cached.clone()]);
+    assert!(!cached.exists());
+
+    // A model fetched with download_model is found by its bare name
+    touch(&cached);
+    assert_eq!(locate_model(name).unwrap(), cached);
+    std::fs::remove_file(&cached).unwrap();
+}
+
+#[test]
+fn relative_paths_are_also_looked_up_in_models_dir() {
+    let name = "ggml-memo-test-local.bin";
+    let local = Path::new("models").join(name);
+    touch(&local);
+    assert_eq!(locate_model(name).unwrap(), local);
+    std::fs::remove_file(&local).unwrap();
+
+    // Paths with directories aren't looked up in the cache
+    let nested = "nested/ggml-memo-test-missing.bin";
+    assert_eq!(searched_paths(nested), [PathBuf::from(nested), Path::new("models").join(nested)]);
+}
+
+#[test]
+fn ensure_model_in_creates_the_directory() {
+    let dir = std::env::temp_dir().join("memo-test-model-dir").join("nested");
+    let _ = std::fs::remove_dir_all(dir.parent().unwrap());
+    let path = dir.join("ggml-memo-test-missing.bin");
+    match ensure_model_in(&dir, "ggml-memo-test-missing.bin") {
+        Err(Error::ModelNotFound { searched, .. }) => assert_eq!(searched, std::slice::from_ref(&path)),
+        other => panic!("expected ModelNotFound, got {:?}", other),
+    }
+    assert!(dir.is_dir());
+
+    touch(&path);
+    assert_eq!(ensure_model_in(&dir, "ggml-memo-test-missing.bin").unwrap(), path);
+    assert!(matches!(ensure_model_in(&dir, "../ggml-memo-test-missing.bin"), Err(Error::Config(_))));
+
+    // A file where the directory should be: the error names the directory
+    let blocked = path.join("models");
+    match ensure_model_in(&blocked, "ggml-small.en-q5_1.bin") {
+        Err(Error::Model(message)) => assert!(message.contains(&blocked.display().to_string()), "{}", message),
+        other => panic!("expected a Model error, got {:?}", other),
+    }
+    std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn stale_downloads_are_cleaned_up() {
+    let dir = std::env::temp_dir().join("memo-test-stale-downloads");
+    let _ = std::fs::remove_dir_all(&dir);
+    let legacy = dir.join("ggml-old.bin.part");
+    let abandoned = dir.join("ggml-abandoned.bin.partial");
+    let recent = dir.join("ggml-recent.bin.partial");
+    // Someone else's downloads in a shared directory
+    let foreign = dir.join("foo.part");
+    let foreign_old = dir.join("foo.iso.partial");
+    for path in [&legacy, &abandoned, &recent, &foreign, &foreign_old] {
+        touch(path);
+    }
+    let month_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(30 * 24 * 60 * 60);
+    for path in [&abandoned, &foreign_old] {
+        std::fs::File::options().write(true).open(path).unwrap().set_modified(month_ago).unwrap();
+    }
+
+    assert!(matches!(ensure_model_in(&dir, "ggml-memo-test-missing.bin"), Err(Error::ModelNotFound { .. })));
+    assert!(!legacy.exists() && !abandoned.exists());
+    assert!(recent.exists(), "a recent partial download is kept for resuming");
+    assert!(foreign.exists() && foreign_old.exists(), "files that aren't model downloads are left alone");
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn download_rejects_invalid_proxy() {
+    let options = DownloadOptions { proxy: Some("ftp://proxy.invalid:21".into()), ..DownloadOptions::default() };
+    assert!(matches!(download_model_with("ggml-memo-test-missing.bin", &options), Err(Error::Config(_))));
+}
+
+#[test]
+fn download_uses_the_url_override() {
+    std::env::set_var("MEMO_DOWNLOAD_ATTEMPTS", "1");
+    // Nothing listens on the discard port, so the connection is refused right away
+    let options = DownloadOptions { url: Some("http://127.0.0.1:9/mirror/ggml-memo-test-mirror.bin".into()), ..DownloadOptions::default() };
+    match download_model_with("ggml-memo-test-mirror.bin", &options) {
+        Err(Error::Model(message)) => assert!(message.contains("127.0.0.1:9"), "{}", message),
+        other => panic!("expected a download error, got {:?}", other),
+    }
+    let options = DownloadOptions { base_url: Some("http://127.0.0.1:9/mirror/".into()), ..DownloadOptions::default() };
+    match download_model_with("ggml-memo-test-mirror.bin", &options) {
+        Err(Error::Model(message)) => assert!(message.contains("127.0.0.1:9/mirror/ggml-memo-test-mirror.bin"), "{}", message),
+        other => panic!("expected a download error, got {:?}", other),
+    }
+    assert!(!default_model_path().with_file_name("ggml-memo-test-mirror.bin").exists());
+}
+
+#[test]
+fn cancelled_download_leaves_no_files() {
+    let cancel = CancelToken::new();
+    cancel.cancel();
+    let options = DownloadOptions { cancel: Some(cancel.clone()), ..DownloadOptions::default() };
+    assert!(matches!(download_model_with("ggml-memo-test-cancelled.bin", &options), Err(Error::Cancelled)));
+
+    let dir = default_model_path().parent().unwrap().to_path_buf();
+    assert!(!dir.join("ggml-memo-test-cancelled.bin").exists());
+    assert!(!dir.join("ggml-memo-test-cancelled.bin.partial").exists());
+}
+
+#[test]
+fn prefetch_reports_errors_through_the_handle() {
+    let handle = prefetch_model("ggml-memo-test-unknown.bin", DownloadOptions::default());
+    assert!(matches!(handle.join().unwrap(), Err(Error::ModelNotFound { .. })));
+}
+
+#[test]
+fn progress_is_not_reported_for_present_models() {
+    let path = std::env::temp_dir().join("ggml-memo-test-progress.bin");
+    touch(&path);
+    let calls = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let counter = std::sync::Arc::clone(&calls);
+    let found = ensure_model_with_progress(&path, move |_, _| {
+        counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    });
+    assert_eq!(found.unwrap(), path);
+    assert_eq!(calls.load(std::sync::atomic::Ordering::Relaxed), 0);
+    std::fs::remove_file(&path).unwrap();
+
+    // Unknown and missing: nothing to download, nothing reported
+    assert!(matches!(ensure_model_with_progress("ggml-memo-test-missing.bin", |_, _| panic!()), Err(Error::ModelNotFound { .. })));
+}
@@ -0,0 +1,29 @@
+//! Offline mode is process-wide, so it gets its own test binary
+
+use memo_stt::model::{default_model_path, download_model, ensure_model, is_offline, set_offline};
+use memo_stt::{Error, SttEngine};
+
+#[test]
+fn offline_mode_never_downloads() {
+    set_offline(true);
+    assert!(is_offline());
+
+    match download_model("ggml-memo-test-offline.bin") {
+        Err(e @ Error::ModelNotFound { .. }) => assert!(e.to_string().contains("Downloads are disabled"), "{}", e),
+        other => panic!("expected ModelNotFound, got {:?}", other),
+    }
+    assert!(!default_model_path().with_file_name("ggml-memo-test-offline.bin.partial").exists());
+
+    // A known model that isn't there is reported with every path searched, not fetched
+    let missing = "ggml-large-v3-turbo-q8_0.bin";
+    if !default_model_path().with_file_name(missing).exists() {
+        match ensure_model(missing) {
+            Err(Error::ModelNotFound { searched, .. }) => assert!(searched.len() > 1, "{:?}", searched),
+            other => panic!("expected ModelNotFound, got {:?}", other),
+        }
+        assert!(matches!(SttEngine::new_offline(missing, 16000), Err(Error::ModelNotFound { .. })));
+    }
+
+    set_offline(false);
+    assert!(std::env::var("MEMO_STT_OFFLINE").is_ok() || !is_offline());
+}
@@ -0,0 +1,112 @@
+use memo_stt::postprocess::{remove_disfluencies, remove_disfluencies_with, Locale, NoopProcessor, OutputStyle, RuleBasedProcessor, TranscriptProcessor};
+
+#[test]
+fn noop_returns_input() {
+    assert_eq!(NoopProcessor.process("um, hello"), "um, hello");
+    assert_eq!(NoopProcessor.process(""), "");
+}
+
+#[test]
+fn rule_based_removes_fillers_and_capitalizes() {
+    let processor = RuleBasedProcessor::default();
+    assert_eq!(processor.process("um, so we ship it. uh it works"), "So we ship it. It works");
+    assert_eq!(processor.process(""), "");
+}
+
+#[test]
+fn rule_based_replacements_run_after_filler_removal() {
+    let processor = RuleBasedProcessor::default()
+        .with_capitalization(false)
+        .with_replacement(r"\bgit hub\b", "GitHub")
+        .unwrap();
+    assert_eq!(processor.process("push to uh git hub"), "push to GitHub");
+}
+
+#[test]
+fn rule_based_rejects_invalid_pattern() {
+    assert!(matches!(
+        RuleBasedProcessor::default().with_replacement("(", ""),
+        Err(memo_stt::Error::Config(_))
+    ));
+}
+
+#[test]
+fn disfluencies_keep_sentence_punctuation() {
+    assert_eq!(remove_disfluencies("Let's go um. Then stop"), "Let's go. Then stop");
+    assert_eq!(remove_disfluencies("Um, so we start."), "So we start.");
+}
+
+#[test]
+fn multi_word_fillers_need_a_comma() {
+    assert_eq!(remove_disfluencies("It was, you know, fine"), "It was fine");
+    assert_eq!(remove_disfluencies("Do you know the way"), "Do you know the way");
+}
+
+#[test]
+fn stutters_collapse_but_legit_repeats_stay() {
+    assert_eq!(remove_disfluencies("the the the meeting"), "the meeting");
+    assert_eq!(remove_disfluencies("She had had enough"), "She had had enough");
+    assert_eq!(remove_disfluencies("Done. Done."), "Done. Done.");
+}
+
+#[test]
+fn custom_fillers() {
+    assert_eq!(remove_disfluencies_with("like, it was like big", &["like"]), "it was big");
+}
+
+#[test]
+fn output_style_is_off_by_default() {
+    assert_eq!(OutputStyle::default().apply(" Hello there. "), " Hello there. ");
+}
+
+#[test]
+fn output_style_for_inline_dictation() {
+    let inline = OutputStyle { lowercase_first: true, strip_trailing_period: true, trim: true };
+    assert_eq!(inline.apply(" Then we ship it. "), "then we ship it");
+    assert_eq!(inline.apply("Wait for it..."), "wait for it...");
+    assert_eq!(inline.apply("I think NASA is right."), "I think NASA is right");
+    assert_eq!(inline.apply("NASA said so."), "NASA said so");
+    assert_eq!(inline.apply(""), "");
+}
+
+#[test]
+fn output_style_strips_period_before_untrimmed_whitespace() {
+    let style = OutputStyle { strip_trailing_period: true, ..OutputStyle::default() };
+    assert_eq!(style.apply("Done. \n"), "Done \n");
+}
+
+#[test]
+fn locale_formats_spoken_dates_and_money() {
+    let cases = [
+        ("March third twenty twenty four", "3/3/2024", "3/3/2024", "2024-03-03", "3.3.2024"),
+        ("on the twenty first of May, two thousand and five.", "on 5/21/2005.", "on 21/5/2005.", "on 2005-05-21.", "on 21.5.2005."),
+        ("June 1st, 1999", "6/1/1999", "1/6/1999", "1999-06-01", "1.6.1999"),
+        ("February twenty-ninth twenty twenty four", "2/29/2024", "29/2/2024", "2024-02-29", "29.2.2024"),
+        ("twenty dollars and fifty cents", "$20.50", "$20.50", "$20.50", "20,50 $"),
+        ("one thousand two hundred fifty euros", "€1,250", "€1,250", "€1,250", "1.250 €"),
+        ("a hundred pounds and five pence", "£100.05", "£100.05", "£100.05", "100,05 £"),
+        ("20 dollars 50 cents", "$20.50", "$20.50", "$20.50", "20,50 $"),
+    ];
+    for (spoken, us, gb, ca, de) in cases {
+        assert_eq!(Locale::EnUs.apply(spoken), us, "{:?}", spoken);
+        assert_eq!(Locale::EnGb.apply(spoken), gb, "{:?}", spoken);
+        assert_eq!(Locale::EnCa.apply(spoken), ca, "{:?}", spoken);
+        assert_eq!(Locale::De.apply(spoken), de, "{:?}", spoken);
+    }
+}
+
+#[test]
+fn locale_leaves_other_numbers_alone() {
+    for text in [
+        "you may first two thousand",
+        "February thirtieth twenty twenty four",
+        "February 29 2023",
+        "nineteen oh five",
+        "we shipped twenty five builds",
+        "fifty cents",
+        "café dix euros",
+        "",
+    ] {
+        assert_eq!(Locale::EnUs.apply(text), text);
+    }
+}
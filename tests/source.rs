@@ -0,0 +1,22 @@
+use memo_stt::audio::{Endianness, PcmFormat, SampleFormat};
+use memo_stt::source::{AudioSource, FileSource};
+
+#[test]
+fn file_source_yields_the_file_once() {
+    let mut source = FileSource::from_samples(vec![1, 2, 3], 8000);
+    assert_eq!(source.sample_rate(), 8000);
+    assert_eq!(source.next_utterance().unwrap(), Some(vec![1, 2, 3]));
+    assert_eq!(source.next_utterance().unwrap(), None);
+}
+
+#[test]
+fn file_source_reads_raw_pcm_files() {
+    let path = std::env::temp_dir().join(format!("memo-stt-source-{}.pcm", std::process::id()));
+    std::fs::write(&path, [0x00, 0x10, 0x00, 0x20]).unwrap();
+    let format = PcmFormat { sample_format: SampleFormat::I16, channels: 1, endianness: Endianness::Big };
+    let mut source = FileSource::open_raw(&path, format, 16000).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(source.next_utterance().unwrap(), Some(vec![0x0010, 0x0020]));
+
+    assert!(matches!(FileSource::open("definitely-missing.wav"), Err(memo_stt::Error::Audio(_))));
+}
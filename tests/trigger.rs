@@ -0,0 +1,155 @@
+#![cfg(feature = "hotkey")]
+
+use memo_stt::trigger::hotkey::{HotkeyGesture, HotkeyMode, HotkeyTrigger};
+use memo_stt::trigger::TriggerEvent::{Activated, Deactivated};
+use memo_stt::Error;
+use std::time::{Duration, Instant};
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn push_to_talk_is_active_while_held() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::PushToTalk, Duration::ZERO);
+    assert_eq!(gesture.press(t), Some(Activated));
+    assert!(gesture.is_active());
+    // Key repeat
+    assert_eq!(gesture.press(t + ms(30)), None);
+    assert_eq!(gesture.release(t + ms(500)), Some(Deactivated));
+    assert!(!gesture.is_active());
+}
+
+#[test]
+fn toggle_flips_on_each_press() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::Toggle, Duration::ZERO);
+    assert_eq!(gesture.press(t), Some(Activated));
+    assert_eq!(gesture.release(t + ms(100)), None);
+    assert!(gesture.is_active());
+    assert_eq!(gesture.press(t + ms(2000)), Some(Deactivated));
+    assert_eq!(gesture.release(t + ms(2100)), None);
+    assert!(!gesture.is_active());
+}
+
+#[test]
+fn double_tap_locks_until_next_press() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::DoubleTapLock, Duration::ZERO);
+    assert_eq!(gesture.press(t), Some(Activated));
+    assert_eq!(gesture.release(t + ms(100)), Some(Deactivated));
+    assert_eq!(gesture.press(t + ms(300)), Some(Activated));
+    assert!(gesture.is_locked());
+    assert_eq!(gesture.release(t + ms(400)), None);
+    assert!(gesture.is_active());
+
+    assert_eq!(gesture.press(t + ms(5000)), Some(Deactivated));
+    assert!(!gesture.is_locked());
+    // Releasing the unlocking press isn't the first tap of another double tap
+    assert_eq!(gesture.release(t + ms(5100)), None);
+    assert_eq!(gesture.press(t + ms(5300)), Some(Activated));
+    assert!(!gesture.is_locked());
+}
+
+#[test]
+fn slow_second_tap_is_push_to_talk() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::DoubleTapLock, Duration::ZERO);
+    gesture.press(t);
+    gesture.release(t + ms(100));
+    assert_eq!(gesture.press(t + ms(1000)), Some(Activated));
+    assert!(!gesture.is_locked());
+    assert_eq!(gesture.release(t + ms(1500)), Some(Deactivated));
+}
+
+#[test]
+fn presses_shorter_than_min_hold_are_ignored() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::PushToTalk, ms(150));
+    assert_eq!(gesture.press(t), None);
+    assert!(gesture.is_pending());
+    // Key repeat doesn't restart the hold
+    assert_eq!(gesture.press(t + ms(40)), None);
+    assert_eq!(gesture.poll(t + ms(100)), None);
+    assert_eq!(gesture.release(t + ms(120)), None);
+    assert!(!gesture.is_active() && !gesture.is_pending());
+
+    assert_eq!(gesture.press(t + ms(1000)), None);
+    assert_eq!(gesture.poll(t + ms(1150)), Some(Activated));
+    assert_eq!(gesture.release(t + ms(1600)), Some(Deactivated));
+}
+
+#[test]
+fn toggle_counts_a_press_once_held_long_enough() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::Toggle, ms(150));
+    gesture.press(t);
+    assert_eq!(gesture.poll(t + ms(300)), Some(Activated));
+    assert_eq!(gesture.release(t + ms(300)), None);
+    assert!(gesture.is_active());
+}
+
+#[test]
+fn quick_tap_still_starts_a_double_tap_lock() {
+    let t = Instant::now();
+    let mut gesture = HotkeyGesture::new(HotkeyMode::DoubleTapLock, ms(150));
+    gesture.press(t);
+    assert_eq!(gesture.release(t + ms(60)), None);
+    // The second press of a double tap counts at once
+    assert_eq!(gesture.press(t + ms(200)), Some(Activated));
+    assert!(gesture.is_locked());
+}
+
+/// A Linux `struct input_event` as read from an evdev device
+#[cfg(target_os = "linux")]
+fn input_event(kind: u16, code: u16, value: i32) -> Vec<u8> {
+    let mut event = vec![0u8; 2 * std::mem::size_of::<usize>()];
+    event.extend(kind.to_ne_bytes());
+    event.extend(code.to_ne_bytes());
+    event.extend(value.to_ne_bytes());
+    event
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn evdev_trigger_follows_the_key_code() {
+    const EV_KEY: u16 = 0x01;
+    const KEY_RIGHTCTRL: u16 = 97;
+    let path = std::env::temp_dir().join(format!("memo-stt-evdev-{}", std::process::id()));
+    let events: Vec<u8> = [
+        input_event(EV_KEY, 30, 1),
+        input_event(EV_KEY, KEY_RIGHTCTRL, 1),
+        input_event(EV_KEY, KEY_RIGHTCTRL, 2),
+        input_event(0x00, 0, 0),
+        input_event(EV_KEY, KEY_RIGHTCTRL, 0),
+    ]
+    .concat();
+    std::fs::write(&path, events).unwrap();
+
+    let trigger = HotkeyTrigger::from_evdev(&path, KEY_RIGHTCTRL, HotkeyMode::PushToTalk, Duration::ZERO).unwrap();
+    assert_eq!(trigger.trigger_key(), None);
+    assert_eq!(trigger.recv().unwrap(), Activated);
+    assert_eq!(trigger.recv().unwrap(), Deactivated);
+    assert_eq!(trigger.try_recv(), None);
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn evdev_trigger_reports_an_unreadable_device() {
+    let result = HotkeyTrigger::from_evdev("/dev/input/no-such-keyboard", 97, HotkeyMode::PushToTalk, Duration::ZERO);
+    match result {
+        Err(Error::Config(msg)) => assert!(msg.contains("/dev/input/no-such-keyboard"), "{}", msg),
+        Err(e) => panic!("unexpected error: {}", e),
+        Ok(_) => panic!("opened a missing device"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn global_hotkeys_are_refused_under_wayland() {
+    std::env::set_var("XDG_SESSION_TYPE", "wayland");
+    let result = HotkeyTrigger::new(rdev::Key::ControlRight, HotkeyMode::PushToTalk, Duration::ZERO);
+    assert!(matches!(result, Err(Error::Config(_))));
+}